@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::time::{Date, DateTime, Format, Locale, Time, Weekday};
+
+/// user configuration read from `$XDG_CONFIG_HOME/koi/config`
+///
+/// The file consists of `key = value` lines, empty lines and lines starting with `#` are
+/// ignored.
+///
+/// ```text
+/// store = /home/me/notes/koi.db
+/// week_start = sunday
+/// locale = de
+/// date_format = %a %d %b
+/// time_format = %-I:%M %p
+/// query.week = state:open when:today..+1w sort:scheduled
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    /// see [`Store::open`](crate::store::Store::open), a new directory of files is created for a
+    /// path ending with a `/`
+    pub store: Option<PathBuf>,
+
+    /// the first day of the week, monday by default
+    pub week_start: Weekday,
+
+    /// the language of dates, taken from the environment if not set, see [`Locale::from_env`]
+    pub locale: Option<Locale>,
+
+    /// how dates are printed, `%Y-%m-%d` by default, see [`Format`]
+    pub date_format: Format,
+
+    /// how times are printed, `%H:%M` by default
+    pub time_format: Format,
+
+    /// how date times are printed, the date and the time format separated by a space by default
+    pub datetime_format: Option<Format>,
+
+    /// named query strings, see [`crate::store::Query::parse`]
+    pub queries: HashMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            store: None,
+            week_start: Weekday::Monday,
+            locale: None,
+            date_format: "%Y-%m-%d"
+                .parse()
+                .expect("the default date format is valid"),
+            time_format: "%H:%M".parse().expect("the default time format is valid"),
+            datetime_format: None,
+            queries: HashMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// the path of the config file, `$KOI_CONFIG` takes precedence over the default location
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("KOI_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+
+        let config = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+
+        Some(config.join("koi").join("config"))
+    }
+
+    /// loads the config file, a missing file results in the default config
+    pub fn load() -> anyhow::Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                Self::parse(&content).with_context(|| format!("invalid config {}", path.display()))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("could not read {}", path.display())),
+        }
+    }
+
+    /// the configured locale or the one of the environment
+    pub fn locale(&self) -> Locale {
+        self.locale.unwrap_or_else(Locale::from_env)
+    }
+
+    pub fn format_date(&self, date: Date) -> String {
+        self.date_format.format_date(date)
+    }
+
+    pub fn format_time(&self, time: Time) -> String {
+        self.time_format.format_time(time)
+    }
+
+    pub fn format_datetime(&self, datetime: DateTime) -> String {
+        match &self.datetime_format {
+            Some(format) => format.format_datetime(datetime),
+            None => format!(
+                "{} {}",
+                self.format_date(datetime.date()),
+                self.format_time(datetime.time())
+            ),
+        }
+    }
+
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        let mut config = Self::default();
+
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .with_context(|| format!("line {}: expected 'key = value'", i + 1))?;
+            let key = key.trim();
+            let value = value.trim();
+
+            if key == "store" {
+                config.store = Some(value.into());
+            } else if key == "week_start" {
+                config.week_start = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("line {}: invalid weekday '{}'", i + 1, value))?;
+            } else if key == "locale" {
+                config.locale =
+                    Some(value.parse().map_err(|err| {
+                        anyhow::anyhow!("line {}: invalid locale, {}", i + 1, err)
+                    })?);
+            } else if key == "date_format" {
+                config.date_format = parse_format(i, value)?;
+            } else if key == "time_format" {
+                config.time_format = parse_format(i, value)?;
+            } else if key == "datetime_format" {
+                config.datetime_format = Some(parse_format(i, value)?);
+            } else if let Some(name) = key.strip_prefix("query.") {
+                config.queries.insert(name.into(), value.into());
+            } else {
+                anyhow::bail!("line {}: unknown key '{}'", i + 1, key);
+            }
+        }
+
+        let locale = config.locale();
+        config.date_format = config.date_format.with_locale(locale);
+        config.time_format = config.time_format.with_locale(locale);
+        config.datetime_format = config
+            .datetime_format
+            .map(|format| format.with_locale(locale));
+        Ok(config)
+    }
+}
+
+/// parses the format `value` on line `i`, counting from zero
+fn parse_format(i: usize, value: &str) -> anyhow::Result<Format> {
+    value
+        .parse()
+        .map_err(|err| anyhow::anyhow!("line {}: invalid format, {}", i + 1, err))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(
+            "
+            # where the store lives
+            store = /tmp/koi.db
+            week_start = Sun
+
+            query.week = state:open when:today..+1w
+            query.done=state:closed closed:>=-1w
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(Some(PathBuf::from("/tmp/koi.db")), config.store);
+        assert_eq!(Weekday::Sunday, config.week_start);
+        assert_eq!(
+            Some("state:open when:today..+1w"),
+            config.queries.get("week").map(|s| s.as_str())
+        );
+        assert_eq!(
+            Some("state:closed closed:>=-1w"),
+            config.queries.get("done").map(|s| s.as_str())
+        );
+    }
+
+    #[test]
+    fn test_parse_fail() {
+        assert!(Config::parse("store").is_err());
+        assert!(Config::parse("colour = red").is_err());
+        assert!(Config::parse("week_start = someday").is_err());
+        assert_eq!(Weekday::Monday, Config::parse("").unwrap().week_start);
+        assert!(Config::parse("date_format = %d.%m.%Q").is_err());
+        assert!(Config::parse("locale = klingon").is_err());
+    }
+
+    #[test]
+    fn test_formats() {
+        let at: DateTime = "2025-03-03 14:30".parse().unwrap();
+
+        let config = Config::default();
+        assert_eq!(at.to_string(), config.format_datetime(at));
+        assert_eq!(at.date().to_string(), config.format_date(at.date()));
+
+        let config = Config::parse(
+            "
+            date_format = %a %d %b
+            time_format = %-I:%M %p
+            locale = en
+            ",
+        )
+        .unwrap();
+        assert_eq!("Mon 03 Mar 2:30 PM", config.format_datetime(at));
+
+        let config = Config::parse(
+            "
+            locale = de_DE.UTF-8
+            datetime_format = %A, %-d. %B %H:%M
+            ",
+        )
+        .unwrap();
+        assert_eq!(Some(Locale::German), config.locale);
+        assert_eq!("Montag, 3. März 14:30", config.format_datetime(at));
+
+        let config = Config::parse("datetime_format = %d.%m.%Y %H:%M").unwrap();
+        assert_eq!("03.03.2025 14:30", config.format_datetime(at));
+        assert_eq!("14:30", config.format_time(at.time()));
+    }
+}
@@ -0,0 +1,1014 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::entry::{Entry, EntryState, EntryType, Recurrence, is_valid_tag};
+use crate::ical;
+use crate::org;
+use crate::store::{ImportMode, Query, SortKey, SortOrder, Store};
+use crate::time::ParseError;
+use crate::time::prelude::*;
+use crate::todotxt;
+
+mod config;
+use config::Config;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "koi",
+    version,
+    about = "keep track of todos, meetings and deadlines",
+    after_help = "Dates can be relative like `tomorrow`, `fri 14:00`, `in 2 weeks`, `end of month` or \
+                  `first monday of next month`.\n\
+                  ISO 8601 dates like `2025-W09-6` or `2025-060` and date times with an offset like \
+                  `2025-03-01T14:30:00+02:00` work as well.\n\
+                  Relative dates can be german like `morgen` or `nächsten Montag` with `locale = de` in the config \
+                  or a german $LANG.\n\
+                  Times are local to the zone of the system, set $TZ to use another zone."
+)]
+pub struct Cli {
+    /// path of the store, a database file or a directory with one Markdown file per entry,
+    /// which is created if the path ends with a `/`, defaults to `$XDG_DATA_HOME/koi/koi.db`
+    #[arg(long, short, env = "KOI_STORE", global = true)]
+    store: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// add a new entry
+    Add {
+        title: String,
+
+        #[arg(long, short, default_value = "")]
+        body: String,
+
+        #[arg(long, short, default_value = "")]
+        prefix: String,
+
+        #[arg(long = "type", short = 't', value_parser = parse_entry_type, default_value = "todo")]
+        entry_type: EntryType,
+
+        #[arg(long, value_parser = parse_datetime, allow_hyphen_values = true)]
+        scheduled: Option<DateTime>,
+
+        #[arg(long, value_parser = parse_datetime, allow_hyphen_values = true)]
+        scheduled_end: Option<DateTime>,
+
+        #[arg(long, short, value_parser = parse_datetime, allow_hyphen_values = true)]
+        deadline: Option<DateTime>,
+
+        /// like `every 1w`, `every 1m until 2025-12-31` or `every 2w 5 times`
+        #[arg(long, short, value_parser = parse_recurrence)]
+        repeat: Option<Recurrence>,
+
+        /// may be given multiple times
+        #[arg(long = "tag", short = 'T', value_parser = parse_tag)]
+        tags: Vec<String>,
+    },
+
+    /// list entries matching a query, only open ones unless the query says otherwise
+    List {
+        /// a query like `prefix:work due:<+7d "budget"`
+        query: Vec<String>,
+
+        /// use the query saved as `query.<NAME>` in the config
+        #[arg(long, short = 'q', value_name = "NAME", conflicts_with = "query")]
+        saved: Option<String>,
+
+        #[arg(long, short)]
+        prefix: Option<String>,
+
+        /// list open and closed entries
+        #[arg(long, short, conflicts_with = "closed")]
+        all: bool,
+
+        /// list only closed entries
+        #[arg(long, short)]
+        closed: bool,
+
+        #[arg(long = "type", short = 't', value_parser = parse_entry_type)]
+        entry_type: Option<EntryType>,
+
+        /// only entries whose title or body contains this text
+        #[arg(long)]
+        text: Option<String>,
+
+        /// one of id, opened, closed, scheduled or deadline
+        #[arg(long, value_parser = parse_sort_key)]
+        sort: Option<SortKey>,
+
+        /// sort in descending order
+        #[arg(long, short)]
+        reverse: bool,
+
+        #[arg(long, short)]
+        limit: Option<u64>,
+
+        /// print entries as JSON, one object per line
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// full text search over titles and bodies, best matches first
+    Search {
+        text: String,
+
+        /// a query restricting the searched entries
+        query: Vec<String>,
+    },
+
+    /// show a single entry
+    Show {
+        id: u64,
+
+        /// print the entry as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// close an entry
+    Close {
+        id: u64,
+
+        /// when the entry was closed, defaults to now
+        #[arg(long, value_parser = parse_datetime, allow_hyphen_values = true)]
+        at: Option<DateTime>,
+    },
+
+    /// change fields of an existing entry
+    Edit {
+        id: u64,
+
+        #[arg(long)]
+        title: Option<String>,
+
+        #[arg(long, short)]
+        body: Option<String>,
+
+        #[arg(long, short)]
+        prefix: Option<String>,
+
+        #[arg(long = "type", short = 't', value_parser = parse_entry_type)]
+        entry_type: Option<EntryType>,
+
+        #[arg(
+            long,
+            value_parser = parse_datetime,
+            allow_hyphen_values = true,
+            conflicts_with = "no_scheduled"
+        )]
+        scheduled: Option<DateTime>,
+
+        #[arg(
+            long,
+            value_parser = parse_datetime,
+            allow_hyphen_values = true,
+            conflicts_with = "no_scheduled"
+        )]
+        scheduled_end: Option<DateTime>,
+
+        /// remove the scheduled time together with its end
+        #[arg(long)]
+        no_scheduled: bool,
+
+        #[arg(
+            long,
+            short,
+            value_parser = parse_datetime,
+            allow_hyphen_values = true,
+            conflicts_with = "no_deadline"
+        )]
+        deadline: Option<DateTime>,
+
+        /// remove the deadline
+        #[arg(long)]
+        no_deadline: bool,
+
+        /// like `every 1w`, `every 1m until 2025-12-31` or `every 2w 5 times`
+        #[arg(long, short, value_parser = parse_recurrence, conflicts_with = "no_repeat")]
+        repeat: Option<Recurrence>,
+
+        /// stop repeating the entry
+        #[arg(long)]
+        no_repeat: bool,
+
+        /// add a tag, may be given multiple times
+        #[arg(long = "tag", short = 'T', value_parser = parse_tag)]
+        tags: Vec<String>,
+
+        /// remove a tag, may be given multiple times
+        #[arg(long = "untag", short = 'U')]
+        untags: Vec<String>,
+    },
+
+    /// open a closed entry again
+    Reopen { id: u64 },
+
+    /// delete an entry
+    Delete { id: u64 },
+
+    /// list tags with the number of entries they are attached to
+    Tags {
+        #[command(subcommand)]
+        command: Option<TagsCommand>,
+    },
+
+    /// show open entries scheduled or due in the next days
+    Agenda {
+        /// first day of the agenda, defaults to today
+        #[arg(long, short, value_parser = parse_date, allow_hyphen_values = true)]
+        from: Option<Date>,
+
+        /// number of days to show
+        #[arg(long, short, default_value_t = 7, conflicts_with = "week")]
+        days: u32,
+
+        /// show the whole week containing the first day, see `week_start` in the config
+        #[arg(long, short)]
+        week: bool,
+
+        #[arg(long, short)]
+        prefix: Option<String>,
+    },
+
+    /// write entries to stdout or a file in another format
+    Export {
+        #[arg(long, short, value_enum)]
+        format: Format,
+
+        /// the file to write to instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// a query selecting the exported entries
+        query: Vec<String>,
+    },
+
+    /// read entries from a file, entries imported before are updated
+    Import {
+        #[arg(long, short, value_enum)]
+        format: Format,
+
+        /// the prefix of the imported entries
+        #[arg(long, short, default_value = "")]
+        prefix: String,
+
+        file: PathBuf,
+
+        /// a query selecting the imported entries, only supported for todo.txt
+        query: Vec<String>,
+    },
+
+    /// write every entry to stdout or a file as JSON, to be read by `restore`
+    Backup {
+        /// the file to write to instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+
+    /// read every entry of a file written by `backup`
+    Restore {
+        /// what to do with entries whose id is already taken
+        #[arg(long, short, value_enum, default_value_t = RestoreMode::Empty)]
+        mode: RestoreMode,
+
+        file: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum RestoreMode {
+    /// only restore into a store without entries
+    Empty,
+    /// replace entries with the same id
+    Merge,
+    /// give entries whose id is taken a new id
+    Renumber,
+}
+
+impl From<RestoreMode> for ImportMode {
+    fn from(mode: RestoreMode) -> Self {
+        match mode {
+            RestoreMode::Empty => ImportMode::Empty,
+            RestoreMode::Merge => ImportMode::MergeById,
+            RestoreMode::Renumber => ImportMode::Renumber,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// iCalendar, meetings become events and todos become tasks
+    Ical,
+    /// org-mode, prefixes become headings and entries become headlines below them
+    Org,
+    /// todo.txt, meetings are left out and prefixes become projects
+    Todotxt,
+}
+
+#[derive(Debug, Subcommand)]
+enum TagsCommand {
+    /// rename a tag on every entry
+    Rename {
+        from: String,
+        #[arg(value_parser = parse_tag)]
+        to: String,
+    },
+
+    /// replace a tag with another, existing tag on every entry
+    Merge {
+        from: String,
+        #[arg(value_parser = parse_tag)]
+        into: String,
+    },
+}
+
+fn parse_date(s: &str) -> Result<Date, String> {
+    s.parse().map_err(|err: ParseError| err.to_string())
+}
+
+/// parses a date time with an optional zone like `2025-03-01 14:00 Europe/Berlin` into local time
+fn parse_datetime(s: &str) -> Result<DateTime, String> {
+    s.parse::<ZonedDateTime>()
+        .map(|datetime| datetime.in_zone(Zone::Local))
+        .map_err(|err| err.to_string())
+}
+
+fn parse_entry_type(s: &str) -> Result<EntryType, String> {
+    s.parse().map_err(|err: ParseError| err.to_string())
+}
+
+fn parse_recurrence(s: &str) -> Result<Recurrence, String> {
+    s.parse().map_err(|err: ParseError| err.to_string())
+}
+
+fn parse_tag(s: &str) -> Result<String, String> {
+    if !is_valid_tag(s) {
+        return Err(format!(
+            "invalid tag '{}', tags must not be empty or contain whitespace",
+            s
+        ));
+    }
+    Ok(s.into())
+}
+
+fn parse_sort_key(s: &str) -> Result<SortKey, String> {
+    s.parse().map_err(|err: ParseError| err.to_string())
+}
+
+fn default_store_path() -> anyhow::Result<PathBuf> {
+    let data = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            let home = std::env::var_os("HOME").context("could not determine home directory")?;
+            PathBuf::from(home).join(".local").join("share")
+        }
+    };
+
+    let dir = data.join("koi");
+    std::fs::create_dir_all(&dir).with_context(|| format!("could not create {}", dir.display()))?;
+
+    Ok(dir.join("koi.db"))
+}
+
+fn entry_line(entry: &Entry, config: &Config) -> String {
+    let mut line = format!("{:>4} {} ", entry.id, entry.entry_type);
+
+    if entry.is_closed() {
+        line.push_str("[x] ");
+    }
+
+    if !entry.prefix.is_empty() {
+        line.push_str(&entry.prefix);
+        line.push(' ');
+    }
+
+    line.push_str(&entry.title);
+
+    if !entry.tags.is_empty() {
+        line.push_str(&format!(" :{}:", entry.tags.join(":")));
+    }
+
+    if let Some(scheduled) = entry.scheduled {
+        line.push_str(&format!(" S:{}", config.format_datetime(scheduled)));
+    }
+
+    if let Some(deadline) = entry.deadline {
+        line.push_str(&format!(" D:{}", config.format_datetime(deadline)));
+
+        if !entry.is_closed() {
+            line.push_str(&format!(" ({})", deadline_label(deadline, now())));
+        }
+    }
+
+    line
+}
+
+/// like `due in 3d` or `overdue by 2w`
+fn deadline_label(deadline: DateTime, now: DateTime) -> String {
+    if deadline < now {
+        format!("overdue by {}", deadline.until(now))
+    } else if deadline.minutes_between(now) == 0 {
+        "due now".into()
+    } else {
+        format!("due in {}", now.until(deadline))
+    }
+}
+
+fn print_entry(entry: &Entry, config: &Config) {
+    println!("id:        {}", entry.id);
+    println!("title:     {}", entry.title);
+    println!("type:      {}", entry.entry_type);
+    println!("prefix:    {}", entry.prefix);
+
+    if !entry.tags.is_empty() {
+        println!("tags:      {}", entry.tags.join(" "));
+    }
+    println!("opened:    {}", config.format_datetime(entry.opened));
+
+    if let Some(closed) = entry.closed {
+        println!("closed:    {}", config.format_datetime(closed));
+    }
+
+    if let Some(scheduled) = entry.scheduled {
+        match entry.scheduled_end {
+            Some(end) => println!(
+                "scheduled: {} - {} ({})",
+                config.format_datetime(scheduled),
+                config.format_datetime(end),
+                end - scheduled
+            ),
+            None => println!("scheduled: {}", config.format_datetime(scheduled)),
+        }
+    }
+
+    if let Some(deadline) = entry.deadline {
+        if entry.is_closed() {
+            println!("deadline:  {}", config.format_datetime(deadline));
+        } else {
+            println!(
+                "deadline:  {} ({})",
+                config.format_datetime(deadline),
+                deadline_label(deadline, now())
+            );
+        }
+    }
+
+    if let Some(recurrence) = entry.recurrence {
+        println!("repeat:    {}", recurrence);
+    }
+
+    if !entry.body.is_empty() {
+        println!();
+        println!("{}", entry.body);
+    }
+}
+
+/// the entries scheduled or due on `day` with the time they are listed at, ordered by that
+/// time, `true` marks a deadline
+fn agenda_items(entries: &[Entry], day: Date) -> Vec<(DateTime, bool, &Entry)> {
+    let day = DateTimeRange::day(day);
+    let on_day = |at: Option<DateTime>| at.filter(|at| day.contains(*at));
+
+    let mut items = vec![];
+    for entry in entries {
+        if let Some(scheduled) = on_day(entry.scheduled) {
+            items.push((scheduled, false, entry));
+        }
+        if let Some(deadline) = on_day(entry.deadline) {
+            items.push((deadline, true, entry));
+        }
+    }
+    // the sort is stable, so entries listed at the same time keep the order of the agenda
+    items.sort_by_key(|(at, _, _)| *at);
+    items
+}
+
+fn print_agenda(entries: &[Entry], range: DateRange, config: &Config) {
+    for day in range.days() {
+        println!("{}", config.format_date(day));
+
+        for (at, due, entry) in agenda_items(entries, day) {
+            if due {
+                println!("  due    {}", entry_line(entry, config));
+            } else {
+                println!(
+                    "  {}  {}",
+                    config.format_time(at.time()),
+                    entry_line(entry, config)
+                );
+            }
+        }
+    }
+}
+
+/// parses a query string, on failure the offending token is underlined in the error message
+fn parse_query(s: &str) -> anyhow::Result<Query<'_>> {
+    Query::parse(s).map_err(|err| {
+        let indent = s[..err.position].chars().count();
+        let width = err.token.chars().count().max(1);
+        anyhow::anyhow!(
+            "{}\n  {}\n  {}{}",
+            err,
+            s,
+            " ".repeat(indent),
+            "^".repeat(width)
+        )
+    })
+}
+
+pub fn run() -> anyhow::Result<()> {
+    // the locale has to be set before dates in the arguments are parsed
+    let config = Config::load()?;
+    Locale::set_current(config.locale());
+    let cli = Cli::parse();
+
+    let path = match cli.store.or(config.store.clone()) {
+        Some(path) => path,
+        None => default_store_path()?,
+    };
+
+    let mut store = Store::open(&path)
+        .with_context(|| format!("could not open store at {}", path.display()))?;
+
+    match cli.command {
+        Command::Add {
+            title,
+            body,
+            prefix,
+            entry_type,
+            scheduled,
+            scheduled_end,
+            deadline,
+            repeat,
+            mut tags,
+        } => {
+            tags.sort();
+            tags.dedup();
+
+            let entry = Entry {
+                id: store.new_entry_id()?,
+                title,
+                body,
+                prefix,
+                entry_type,
+                opened: now(),
+                closed: None,
+                scheduled,
+                scheduled_end,
+                deadline,
+                recurrence: repeat,
+                tags,
+            };
+            store.add_entry(&entry)?;
+            println!("{}", entry.id);
+        }
+
+        Command::List {
+            query,
+            saved,
+            prefix,
+            all,
+            closed,
+            entry_type,
+            text,
+            sort,
+            reverse,
+            limit,
+            json,
+        } => {
+            let query_string = match saved {
+                Some(name) => config
+                    .queries
+                    .get(&name)
+                    .with_context(|| format!("no saved query '{}'", name))?
+                    .clone(),
+                None => query.join(" "),
+            };
+
+            let mut query = parse_query(&query_string)?;
+
+            if closed {
+                query.state = Some(EntryState::Closed);
+            } else if !all && query.state.is_none() {
+                query.state = Some(EntryState::Open);
+            }
+
+            if let Some(entry_type) = entry_type {
+                query.entry_type = Some(entry_type);
+            }
+            if let Some(prefix) = &prefix {
+                query.prefix = Some(prefix.into());
+            }
+            if let Some(text) = &text {
+                query.text.push(text.into());
+            }
+            if let Some(limit) = limit {
+                query.limit = Some(limit);
+            }
+
+            let (key, order) = query.sort.unwrap_or((SortKey::Id, SortOrder::Ascending));
+            let key = sort.unwrap_or(key);
+            let order = match (reverse, order) {
+                (false, order) => order,
+                (true, SortOrder::Ascending) => SortOrder::Descending,
+                (true, SortOrder::Descending) => SortOrder::Ascending,
+            };
+            query.sort = Some((key, order));
+
+            for entry in &store.query(query)? {
+                if json {
+                    println!("{}", serde_json::to_string(entry)?);
+                } else {
+                    println!("{}", entry_line(entry, &config));
+                }
+            }
+        }
+
+        Command::Search { text, query } => {
+            let query_string = query.join(" ");
+            let query = parse_query(&query_string)?;
+
+            for hit in store.search(&text, query)? {
+                println!("{}", entry_line(&hit.entry, &config));
+                if !hit.snippet.is_empty() {
+                    println!("       {}", hit.snippet);
+                }
+            }
+        }
+
+        Command::Show { id, json } => {
+            let entry = store
+                .query_by_id(id)
+                .with_context(|| format!("no entry with id {}", id))?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&entry)?);
+            } else {
+                print_entry(&entry, &config);
+            }
+        }
+
+        Command::Close { id, at } => {
+            if let Some(next) = store.close_entry(id, at.unwrap_or_else(now))? {
+                println!("{}", next);
+            }
+        }
+
+        Command::Edit {
+            id,
+            title,
+            body,
+            prefix,
+            entry_type,
+            scheduled,
+            scheduled_end,
+            no_scheduled,
+            deadline,
+            no_deadline,
+            repeat,
+            no_repeat,
+            tags,
+            untags,
+        } => {
+            let mut entry = store
+                .query_by_id(id)
+                .with_context(|| format!("no entry with id {}", id))?;
+
+            if let Some(title) = title {
+                entry.title = title;
+            }
+            if let Some(body) = body {
+                entry.body = body;
+            }
+            if let Some(prefix) = prefix {
+                entry.prefix = prefix;
+            }
+            if let Some(entry_type) = entry_type {
+                entry.entry_type = entry_type;
+            }
+            if scheduled.is_some() {
+                entry.scheduled = scheduled;
+            }
+            if scheduled_end.is_some() {
+                entry.scheduled_end = scheduled_end;
+            }
+            if no_scheduled {
+                entry.scheduled = None;
+                entry.scheduled_end = None;
+            }
+            if deadline.is_some() {
+                entry.deadline = deadline;
+            }
+            if no_deadline {
+                entry.deadline = None;
+            }
+
+            if repeat.is_some() {
+                entry.recurrence = repeat;
+            }
+            if no_repeat {
+                entry.recurrence = None;
+            }
+
+            entry.tags.extend(tags);
+            entry.tags.retain(|tag| !untags.contains(tag));
+            entry.tags.sort();
+            entry.tags.dedup();
+
+            store.update_entry(&entry)?;
+        }
+
+        Command::Reopen { id } => {
+            store.reopen_entry(id)?;
+        }
+
+        Command::Delete { id } => {
+            store.delete_entry(id)?;
+        }
+
+        Command::Tags { command: None } => {
+            for (tag, count) in store.tags()? {
+                println!("{:>4} {}", count, tag);
+            }
+        }
+
+        Command::Tags {
+            command: Some(TagsCommand::Rename { from, to }),
+        } => {
+            store.rename_tag(&from, &to)?;
+        }
+
+        Command::Tags {
+            command: Some(TagsCommand::Merge { from, into }),
+        } => {
+            store.merge_tags(&from, &into)?;
+        }
+
+        Command::Agenda {
+            from,
+            days,
+            week,
+            prefix,
+        } => {
+            let from = from.unwrap_or_else(today);
+            let range = match week {
+                true => DateRange::week(from, config.week_start),
+                false => DateRange::new(from, from.add_days(days.try_into().unwrap_or(i32::MAX))),
+            };
+
+            let entries = store.agenda(
+                range.to_datetime_range(),
+                Query {
+                    state: Some(EntryState::Open),
+                    prefix: prefix.as_deref().map(Cow::from),
+                    ..Default::default()
+                },
+            )?;
+
+            print_agenda(&entries, range, &config);
+        }
+
+        Command::Export {
+            format,
+            output,
+            query,
+        } => {
+            let query_string = query.join(" ");
+            let query = parse_query(&query_string)?;
+
+            let content = match format {
+                Format::Ical => ical::export(&mut store, query)?,
+                Format::Org => org::export(&mut store, query)?,
+                Format::Todotxt => todotxt::export(&mut store, query)?,
+            };
+
+            match output {
+                Some(path) => std::fs::write(&path, content)
+                    .with_context(|| format!("could not write {}", path.display()))?,
+                None => print!("{}", content),
+            }
+        }
+
+        Command::Import {
+            format,
+            prefix,
+            file,
+            query,
+        } => {
+            let query_string = query.join(" ");
+            let query = parse_query(&query_string)?;
+            if format != Format::Todotxt && query != Query::default() {
+                anyhow::bail!("a query is only supported when importing todo.txt");
+            }
+
+            let content = std::fs::read_to_string(&file)
+                .with_context(|| format!("could not read {}", file.display()))?;
+
+            match format {
+                Format::Ical => {
+                    let report = ical::import(&mut store, &content, &prefix)?;
+                    for skipped in &report.skipped {
+                        let name = skipped
+                            .summary
+                            .as_deref()
+                            .or(skipped.uid.as_deref())
+                            .unwrap_or("unnamed");
+                        println!(
+                            "skipped {} '{}': {}",
+                            skipped.component, name, skipped.reason
+                        );
+                    }
+                    println!(
+                        "{} added, {} updated, {} skipped",
+                        report.added.len(),
+                        report.updated.len(),
+                        report.skipped.len()
+                    );
+                }
+                Format::Org => {
+                    let report = org::import(&mut store, &content, &prefix)?;
+                    println!(
+                        "{} added, {} updated",
+                        report.added.len(),
+                        report.updated.len()
+                    );
+                }
+                Format::Todotxt => {
+                    let report = todotxt::import(&mut store, &content, &prefix, query)?;
+                    println!(
+                        "{} added, {} updated",
+                        report.added.len(),
+                        report.updated.len()
+                    );
+                }
+            }
+        }
+
+        Command::Backup { output } => {
+            let content = store.export_all()?;
+
+            match output {
+                Some(path) => std::fs::write(&path, content)
+                    .with_context(|| format!("could not write {}", path.display()))?,
+                None => print!("{}", content),
+            }
+        }
+
+        Command::Restore { mode, file } => {
+            let content = std::fs::read_to_string(&file)
+                .with_context(|| format!("could not read {}", file.display()))?;
+
+            let report = store.import_all(&content, mode.into())?;
+
+            for (from, to) in &report.renumbered {
+                println!("renumbered {} to {}", from, to);
+            }
+            println!(
+                "{} added, {} updated",
+                report.added.len(),
+                report.updated.len()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_add() {
+        let cli = Cli::try_parse_from([
+            "koi",
+            "add",
+            "write report",
+            "--prefix",
+            "work/acme",
+            "--deadline",
+            "+3d",
+            "--scheduled",
+            "tomorrow 14:00",
+        ])
+        .unwrap();
+
+        let Command::Add {
+            title,
+            prefix,
+            entry_type,
+            scheduled,
+            deadline,
+            ..
+        } = cli.command
+        else {
+            panic!("expected add command");
+        };
+
+        assert_eq!(title, "write report");
+        assert_eq!(prefix, "work/acme");
+        assert_eq!(entry_type, EntryType::Todo);
+        assert_eq!(
+            scheduled,
+            Some(today().next_day().with_time(Time::from_hm(14, 0).unwrap()))
+        );
+        assert_eq!(
+            deadline,
+            Some(today().add_days(3).with_time(Time::from_hm(0, 0).unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_parse_edit() {
+        let cli =
+            Cli::try_parse_from(["koi", "edit", "7", "--no-scheduled", "--no-deadline"]).unwrap();
+        let Command::Edit {
+            id,
+            scheduled,
+            no_scheduled,
+            deadline,
+            no_deadline,
+            ..
+        } = cli.command
+        else {
+            panic!("expected edit command");
+        };
+
+        assert_eq!(id, 7);
+        assert!(no_scheduled);
+        assert!(no_deadline);
+        assert_eq!(scheduled, None);
+        assert_eq!(deadline, None);
+
+        let edit = |args: &[&str]| Cli::try_parse_from([&["koi", "edit", "7"], args].concat());
+        assert!(edit(&["--no-scheduled", "--scheduled", "tomorrow"]).is_err());
+        assert!(edit(&["--no-scheduled", "--scheduled-end", "tomorrow"]).is_err());
+        assert!(edit(&["--no-deadline", "--deadline", "tomorrow"]).is_err());
+        assert!(edit(&["--no-scheduled", "--deadline", "tomorrow"]).is_ok());
+    }
+
+    #[test]
+    fn test_parse_fail() {
+        assert!(Cli::try_parse_from(["koi", "add", "title", "--deadline", "never"]).is_err());
+        assert!(Cli::try_parse_from(["koi", "add", "title", "--type", "note"]).is_err());
+        assert!(Cli::try_parse_from(["koi", "show", "abc"]).is_err());
+        assert!(Cli::try_parse_from(["koi", "close", "abc"]).is_err());
+        assert!(
+            Cli::try_parse_from(["koi", "add", "title", "--deadline", "2025-03-01 Mars"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_deadline_label() {
+        let now: DateTime = "2025-03-01 14:00".parse().unwrap();
+        let label = |deadline: &str| deadline_label(deadline.parse().unwrap(), now);
+
+        assert_eq!("due in 3d", label("2025-03-04 18:00"));
+        assert_eq!("due in 30min", label("2025-03-01 14:30"));
+        assert_eq!("due now", label("2025-03-01 14:00"));
+        assert_eq!("overdue by 2w", label("2025-02-14 09:00"));
+        assert_eq!("overdue by 1h", label("2025-03-01 12:30"));
+    }
+
+    #[test]
+    fn test_parse_zone() {
+        let cli =
+            Cli::try_parse_from(["koi", "add", "call", "--scheduled", "2025-03-03 14:00 UTC"])
+                .unwrap();
+        let Command::Add { scheduled, .. } = cli.command else {
+            panic!("expected add");
+        };
+
+        let utc = ZonedDateTime::new("2025-03-03 14:00".parse().unwrap(), Zone::UTC);
+        assert_eq!(Some(utc.in_zone(Zone::Local)), scheduled);
+    }
+
+    #[test]
+    fn test_agenda_items() {
+        let at = |s: &str| Some(s.parse::<DateTime>().unwrap());
+
+        let mut late = Entry::test_entry(1, "late".into());
+        late.scheduled = at("2025-03-03 16:00");
+        let mut due = Entry::test_entry(2, "due".into());
+        due.deadline = at("2025-03-03 09:00");
+        let mut both = Entry::test_entry(3, "both".into());
+        both.scheduled = at("2025-03-03 12:00");
+        both.deadline = at("2025-03-04 12:00");
+
+        let entries = [late, due, both];
+        let items = |day: &str| {
+            agenda_items(&entries, day.parse().unwrap())
+                .into_iter()
+                .map(|(_, due, entry)| (entry.id, due))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec![(2, true), (3, false), (1, false)], items("2025-03-03"));
+        assert_eq!(vec![(3, true)], items("2025-03-04"));
+    }
+}
@@ -1,8 +1,10 @@
 use std::{fmt::Display, str::FromStr};
 
 use rusqlite::{ToSql, types::FromSql};
+use serde::{Deserialize, Serialize};
 
 use crate::time::prelude::*;
+use crate::time::{ParseError, ParseErrorKind, parse_number};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum EntryType {
@@ -21,22 +23,30 @@ impl Display for EntryType {
 }
 
 impl FromStr for EntryType {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.trim() {
             "todo" => Ok(Self::Todo),
             "meeting" => Ok(Self::Meeting),
-            _ => Err(()),
+            token => {
+                let kind = ParseErrorKind::Unknown {
+                    what: "entry type",
+                    expected: "todo or meeting",
+                };
+                Err(ParseError::new(s, token, kind))
+            }
         }
     }
 }
 
+serde_with_str!(EntryType);
+
 impl FromSql for EntryType {
     fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
         <String as FromSql>::column_result(value)?
             .parse()
-            .map_err(|_| rusqlite::types::FromSqlError::InvalidType)
+            .map_err(|err: ParseError| rusqlite::types::FromSqlError::Other(Box::new(err)))
     }
 }
 
@@ -54,12 +64,146 @@ pub enum EntryState {
     Closed,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+impl Display for EntryState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            EntryState::Open => "open",
+            EntryState::Closed => "closed",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for EntryState {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "open" => Ok(Self::Open),
+            "closed" => Ok(Self::Closed),
+            token => {
+                let kind = ParseErrorKind::Unknown {
+                    what: "entry state",
+                    expected: "open or closed",
+                };
+                Err(ParseError::new(s, token, kind))
+            }
+        }
+    }
+}
+
+serde_with_str!(EntryState);
+
+/// when a recurrence stops
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RecurrenceEnd {
+    /// no occurrence after this date
+    Until(Date),
+    /// the number of occurrences left, including the current one
+    Count(u32),
+}
+
+/// repeats an entry every `every`, shifting its scheduled time and deadline
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Recurrence {
+    pub every: Duration,
+    pub end: Option<RecurrenceEnd>,
+}
+
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "every {}", self.every)?;
+        match self.end {
+            Some(RecurrenceEnd::Until(date)) => write!(f, " until {}", date),
+            Some(RecurrenceEnd::Count(count)) => write!(f, " {} times", count),
+            None => Ok(()),
+        }
+    }
+}
+
+const EXPECTED_RECURRENCE: &str =
+    "a recurrence like 'every 1w', 'every 1m until 2025-12-31' or 'every 2w 5 times'";
+
+impl FromStr for Recurrence {
+    type Err = ParseError;
+
+    /// parses `every 1w`, `every 2d until 2025-06-01` or `every 1m 5 times`, `every` is optional
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let expected =
+            |token| ParseError::new(s, token, ParseErrorKind::Expected(EXPECTED_RECURRENCE));
+
+        let mut parts = trimmed.split_whitespace();
+
+        let Some(mut every) = parts.next() else {
+            return Err(ParseError::new(s, trimmed, ParseErrorKind::Empty));
+        };
+        if every == "every" {
+            every = parts.next().ok_or_else(|| expected(trimmed))?;
+        }
+
+        let every_value: Duration = every.parse().map_err(|_| expected(every))?;
+        if !every_value.is_positive() {
+            let kind = ParseErrorKind::Expected("a positive interval like 1w");
+            return Err(ParseError::new(s, every, kind));
+        }
+
+        let end = match (parts.next(), parts.next(), parts.next()) {
+            (None, None, None) => None,
+            (Some("until"), Some(date), None) => {
+                let date = date
+                    .parse()
+                    .map_err(|err: ParseError| err.within(s, date))?;
+                Some(RecurrenceEnd::Until(date))
+            }
+            (Some(count), Some("times"), None) => {
+                let count_value = parse_number(s, count)?;
+                if count_value == 0 {
+                    let kind = ParseErrorKind::OutOfRange {
+                        field: "count",
+                        value: 0,
+                    };
+                    return Err(ParseError::new(s, count, kind));
+                }
+                Some(RecurrenceEnd::Count(count_value))
+            }
+            _ => return Err(expected(trimmed)),
+        };
+
+        Ok(Self {
+            every: every_value,
+            end,
+        })
+    }
+}
+
+serde_with_str!(Recurrence);
+
+impl FromSql for Recurrence {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        <String as FromSql>::column_result(value)?
+            .parse()
+            .map_err(|err: ParseError| rusqlite::types::FromSqlError::Other(Box::new(err)))
+    }
+}
+
+impl ToSql for Recurrence {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::Owned(
+            rusqlite::types::Value::Text(self.to_string()),
+        ))
+    }
+}
+
+/// an entry, in JSON dates are local like `2025-03-01 14:30`, see [`DateTime`]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct Entry {
     pub id: u64,
 
     pub title: String,
+    #[serde(default)]
     pub body: String,
+    #[serde(default)]
     pub prefix: String,
     pub entry_type: EntryType,
 
@@ -70,6 +214,17 @@ pub struct Entry {
     pub scheduled_end: Option<DateTime>,
 
     pub deadline: Option<DateTime>,
+
+    pub recurrence: Option<Recurrence>,
+
+    /// sorted and without duplicates, see [`is_valid_tag`]
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// a tag is a non empty word without whitespace, like `@waiting` or `client-x`
+pub fn is_valid_tag(tag: &str) -> bool {
+    !tag.is_empty() && !tag.contains(char::is_whitespace)
 }
 
 impl Entry {
@@ -81,16 +236,18 @@ impl Entry {
             body: "".into(),
             entry_type: EntryType::Todo,
             prefix: "".into(),
-            opened: now().into(),
+            opened: now(),
             closed: None,
             scheduled: None,
             scheduled_end: None,
             deadline: None,
+            recurrence: None,
+            tags: vec![],
         }
     }
 
     pub fn is_open(&self) -> bool {
-        self.closed.map(|_| true).unwrap_or(false)
+        self.closed.is_none()
     }
 
     pub fn is_closed(&self) -> bool {
@@ -104,4 +261,260 @@ impl Entry {
             EntryState::Closed
         }
     }
+
+    /// the date a recurrence is anchored at, the scheduled date or otherwise the deadline
+    fn anchor(&self) -> Option<DateTime> {
+        self.scheduled.or(self.deadline)
+    }
+
+    /// this entry with every date shifted by `duration`, `None` if a date leaves the range of
+    /// date times
+    fn shifted(&self, duration: Duration) -> Option<Self> {
+        let shift = |at: Option<DateTime>| match at {
+            Some(at) => at.checked_add_duration(duration).map(Some),
+            None => Some(None),
+        };
+        Some(Self {
+            scheduled: shift(self.scheduled)?,
+            scheduled_end: shift(self.scheduled_end)?,
+            deadline: shift(self.deadline)?,
+            ..self.clone()
+        })
+    }
+
+    /// the `n`th occurrence after this one, with the same id
+    ///
+    /// The dates are shifted from this entry directly instead of from the previous occurrence,
+    /// so uneven month lengths do not add up over time.
+    ///
+    /// # Returns
+    /// - `None` if the entry does not recur, has neither a scheduled date nor a deadline, the
+    ///   recurrence ends before the `n`th occurrence or it is after [`DateTime::MAX`]
+    pub fn nth_occurrence(&self, n: u32) -> Option<Self> {
+        let recurrence = self.recurrence?;
+        let anchor = self.anchor()?;
+
+        let mut next = self.shifted(recurrence.every * i32::try_from(n).ok()?)?;
+        if n > 0 && next.anchor()? <= anchor {
+            return None;
+        }
+
+        match recurrence.end {
+            Some(RecurrenceEnd::Until(until)) if next.anchor()?.date() > until => return None,
+            Some(RecurrenceEnd::Count(count)) if n >= count => return None,
+            Some(RecurrenceEnd::Count(count)) => {
+                next.recurrence = Some(Recurrence {
+                    end: Some(RecurrenceEnd::Count(count - n)),
+                    ..recurrence
+                });
+            }
+            _ => (),
+        }
+
+        Some(next)
+    }
+
+    /// the occurrence following this one, see [`Entry::nth_occurrence`]
+    pub fn next_occurrence(&self) -> Option<Self> {
+        self.nth_occurrence(1)
+    }
+
+    /// this entry followed by all of its further occurrences, possibly infinitely many
+    pub fn occurrences(&self) -> impl Iterator<Item = Self> + '_ {
+        let first = std::iter::once(self.clone());
+        let rest = (1..).map_while(|n| self.nth_occurrence(n));
+        first.chain(rest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_entry_type_parse() {
+        assert_eq!(Ok(EntryType::Meeting), " meeting ".parse());
+        assert_eq!(
+            "unknown entry type 'note', expected todo or meeting",
+            "note".parse::<EntryType>().unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_recurrence_parse() {
+        let parse = |s: &str| s.parse::<Recurrence>();
+
+        assert_eq!(
+            Ok(Recurrence {
+                every: Duration::Week(1),
+                end: None
+            }),
+            parse("every 1w")
+        );
+        assert_eq!(parse("every 1w"), parse("1w"));
+        assert_eq!(
+            Ok(Recurrence {
+                every: Duration::Day(2),
+                end: Some(RecurrenceEnd::Until(Date::from_ymd(2025, 6, 1).unwrap())),
+            }),
+            parse("every 2d until 2025-06-01")
+        );
+        assert_eq!(
+            Ok(Recurrence {
+                every: Duration::Month(1),
+                end: Some(RecurrenceEnd::Count(5)),
+            }),
+            parse("every 1m 5 times")
+        );
+
+        assert!(parse("").is_err());
+        assert!(parse("every").is_err());
+        assert!(parse("every 0d").is_err());
+        assert!(parse("every -1w").is_err());
+        assert!(parse("every 1w 0 times").is_err());
+        assert!(parse("every 1w until").is_err());
+        assert!(parse("every 1w until never").is_err());
+        assert!(parse("every 1w sometimes").is_err());
+
+        let err = parse("every 2x").unwrap_err();
+        assert_eq!((6, "2x".into()), (err.position, err.token));
+        let err = parse("every 1w until 2025-13-01").unwrap_err();
+        assert_eq!(
+            "month 13 out of range in 'every 1w until 2025-13-01' at position 20",
+            err.to_string()
+        );
+        let err = parse("every 1w 0 times").unwrap_err();
+        assert_eq!(
+            ParseErrorKind::OutOfRange {
+                field: "count",
+                value: 0
+            },
+            err.kind
+        );
+        assert_eq!(ParseErrorKind::Empty, parse(" ").unwrap_err().kind);
+    }
+
+    #[test]
+    fn test_recurrence_from_sql() {
+        let db = rusqlite::Connection::open_in_memory().unwrap();
+        let recurrence =
+            |s: &str| db.query_row("SELECT ?;", [s], |row| row.get::<_, Recurrence>(0));
+
+        assert_eq!(Ok("every 1w".parse().unwrap()), recurrence("every 1w"));
+        let err = recurrence("every day").unwrap_err().to_string();
+        assert!(err.contains("expected a recurrence like"), "{}", err);
+    }
+
+    #[test]
+    fn test_recurrence_display_parse() {
+        for s in ["every 1w", "every 3d until 2025-06-01", "every 1y 2 times"] {
+            let recurrence: Recurrence = s.parse().unwrap();
+            assert_eq!(s, recurrence.to_string());
+        }
+    }
+
+    #[test]
+    fn test_next_occurrence() {
+        let mut entry = Entry::test_entry(1, "weekly".into());
+        entry.scheduled = Some(at("2025-03-03 10:00"));
+        entry.scheduled_end = Some(at("2025-03-03 11:00"));
+        entry.deadline = Some(at("2025-03-05 00:00"));
+        assert_eq!(None, entry.next_occurrence());
+
+        entry.recurrence = Some("every 1w".parse().unwrap());
+        let next = entry.next_occurrence().unwrap();
+        assert_eq!(Some(at("2025-03-10 10:00")), next.scheduled);
+        assert_eq!(Some(at("2025-03-10 11:00")), next.scheduled_end);
+        assert_eq!(Some(at("2025-03-12 00:00")), next.deadline);
+        assert_eq!(entry.recurrence, next.recurrence);
+
+        entry.scheduled = None;
+        entry.scheduled_end = None;
+        let next = entry.next_occurrence().unwrap();
+        assert_eq!(Some(at("2025-03-12 00:00")), next.deadline);
+
+        entry.deadline = None;
+        assert_eq!(None, entry.next_occurrence());
+    }
+
+    #[test]
+    fn test_occurrences_end() {
+        let mut entry = Entry::test_entry(1, "monthly".into());
+        entry.scheduled = Some(at("2025-01-15 09:00"));
+
+        entry.recurrence = Some("every 1m 3 times".parse().unwrap());
+        let scheduled: Vec<_> = entry.occurrences().map(|e| e.scheduled.unwrap()).collect();
+        assert_eq!(
+            vec![
+                at("2025-01-15 09:00"),
+                at("2025-02-15 09:00"),
+                at("2025-03-15 09:00")
+            ],
+            scheduled
+        );
+        assert_eq!(
+            Some("every 1m 2 times".parse().unwrap()),
+            entry.next_occurrence().unwrap().recurrence
+        );
+
+        entry.recurrence = Some("every 1w until 2025-01-29".parse().unwrap());
+        assert_eq!(3, entry.occurrences().count());
+
+        entry.recurrence = Some("every 1w 1 times".parse().unwrap());
+        assert_eq!(None, entry.next_occurrence());
+
+        // occurrences end with the last representable date
+        entry.scheduled = Some(at("9999-12-20 10:00"));
+        entry.recurrence = Some("every 1w".parse().unwrap());
+        assert_eq!(2, entry.occurrences().count());
+        assert_eq!(None, entry.nth_occurrence(u32::MAX));
+    }
+
+    #[test]
+    fn test_serde() {
+        let mut entry = Entry::test_entry(7, "budget".into());
+        entry.entry_type = EntryType::Meeting;
+        entry.opened = at("2025-03-01 09:00");
+        entry.scheduled = Some(at("2025-03-03 14:00"));
+        entry.recurrence = Some("every 1w until 2025-06-01".parse().unwrap());
+        entry.tags = vec!["work".into()];
+
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!("meeting", json["entry_type"]);
+        assert_eq!("2025-03-01 09:00", json["opened"]);
+        assert_eq!("2025-03-03 14:00", json["scheduled"]);
+        assert_eq!(serde_json::Value::Null, json["deadline"]);
+        assert_eq!("every 1w until 2025-06-01", json["recurrence"]);
+        assert_eq!(entry, serde_json::from_value(json).unwrap());
+
+        let json = r#"{"id": 1, "title": "t", "entry_type": "todo", "opened": "2025-03-01 09:00"}"#;
+        let entry: Entry = serde_json::from_str(json).unwrap();
+        assert_eq!(("", None), (entry.body.as_str(), entry.deadline));
+
+        let json = r#"{"id": 1, "title": "t", "entry_type": "note", "opened": "2025-03-01 09:00"}"#;
+        let err = serde_json::from_str::<Entry>(json).unwrap_err().to_string();
+        assert!(err.contains("unknown entry type 'note'"), "{}", err);
+
+        let err = serde_json::from_str::<Recurrence>(r#""every day""#).unwrap_err();
+        assert!(err.to_string().contains("expected a recurrence like"));
+        assert_eq!(
+            Ok(EntryState::Closed),
+            serde_json::from_str(r#""closed""#).map_err(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_state() {
+        let mut entry = Entry::test_entry(1, "title".into());
+        assert!(entry.is_open());
+        assert_eq!(EntryState::Open, entry.state());
+
+        entry.closed = Some(now());
+        assert!(entry.is_closed());
+        assert_eq!(EntryState::Closed, entry.state());
+    }
 }
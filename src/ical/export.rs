@@ -0,0 +1,283 @@
+use crate::entry::{Entry, EntryType, Recurrence, RecurrenceEnd};
+use crate::store::{Query, Store, entry_uid};
+use crate::time::prelude::*;
+
+use super::{escape_text, fold_line, format_utc};
+
+const PRODID: &str = "-//koi//koi//EN";
+
+/// the `FREQ` and `INTERVAL` of a rule repeating every `every`
+///
+/// # Returns
+/// - `None` for compound durations mixing months or years with shorter units, no single
+///   frequency fits them
+fn frequency(every: Duration) -> Option<(&'static str, i32)> {
+    let frequency = match every {
+        Duration::Minute(x) => ("MINUTELY", x),
+        Duration::Hour(x) => ("HOURLY", x),
+        Duration::Day(x) => ("DAILY", x),
+        Duration::Week(x) => ("WEEKLY", x),
+        Duration::Month(x) => ("MONTHLY", x),
+        Duration::Year(x) => ("YEARLY", x),
+        Duration::Compound { .. } => {
+            let [years, months, weeks, days, hours, minutes] = every.parts();
+            let months = years * 12 + months;
+            let minutes = ((weeks * 7 + days) * 24 + hours) * 60 + minutes;
+            match (months, minutes) {
+                (months, 0) => ("MONTHLY", months),
+                (0, minutes) if minutes % (24 * 60) == 0 => ("DAILY", minutes / (24 * 60)),
+                (0, minutes) if minutes % 60 == 0 => ("HOURLY", minutes / 60),
+                (0, minutes) => ("MINUTELY", minutes),
+                _ => return None,
+            }
+        }
+    };
+    Some(frequency)
+}
+
+/// the `RRULE` value of `recurrence` of an entry in `zone`, see [`frequency`]
+fn rrule(recurrence: Recurrence, zone: Zone) -> Option<String> {
+    let (freq, interval) = frequency(recurrence.every)?;
+
+    let mut rule = format!("FREQ={};INTERVAL={}", freq, interval);
+    match recurrence.end {
+        Some(RecurrenceEnd::Until(date)) => {
+            rule.push_str(&format!(
+                ";UNTIL={}",
+                format_utc(date.with_time(Time::MAX), zone)
+            ));
+        }
+        Some(RecurrenceEnd::Count(count)) => rule.push_str(&format!(";COUNT={}", count)),
+        None => (),
+    }
+    Some(rule)
+}
+
+/// the content lines of the component of `entry` from the store named `store_uid` in `zone`,
+/// without folding
+///
+/// # Returns
+/// - `None` for meetings without a scheduled time, they can not be represented as `VEVENT`
+fn component(entry: &Entry, store_uid: &str, zone: Zone, stamp: &str) -> Option<Vec<String>> {
+    let name = match entry.entry_type {
+        EntryType::Meeting if entry.scheduled.is_none() => return None,
+        EntryType::Meeting => "VEVENT",
+        EntryType::Todo => "VTODO",
+    };
+
+    let mut lines = vec![
+        format!("BEGIN:{}", name),
+        format!("UID:{}", entry_uid(store_uid, entry.id)),
+        format!("DTSTAMP:{}", stamp),
+        format!("SUMMARY:{}", escape_text(&entry.title)),
+    ];
+
+    if !entry.body.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape_text(&entry.body)));
+    }
+
+    let categories: Vec<String> = std::iter::once(&entry.prefix)
+        .filter(|prefix| !prefix.is_empty())
+        .chain(&entry.tags)
+        .map(|category| escape_text(category))
+        .collect();
+    if !categories.is_empty() {
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+    }
+
+    if let Some(scheduled) = entry.scheduled {
+        lines.push(format!("DTSTART:{}", format_utc(scheduled, zone)));
+
+        if let Some(rule) = entry
+            .recurrence
+            .and_then(|recurrence| rrule(recurrence, zone))
+        {
+            lines.push(format!("RRULE:{}", rule));
+        }
+    }
+
+    match entry.entry_type {
+        EntryType::Meeting => {
+            if let Some(end) = entry.scheduled_end {
+                lines.push(format!("DTEND:{}", format_utc(end, zone)));
+            }
+        }
+        EntryType::Todo => {
+            if let Some(deadline) = entry.deadline {
+                lines.push(format!("DUE:{}", format_utc(deadline, zone)));
+            }
+
+            match entry.closed {
+                Some(closed) => {
+                    lines.push("STATUS:COMPLETED".into());
+                    lines.push(format!("COMPLETED:{}", format_utc(closed, zone)));
+                }
+                None => lines.push("STATUS:NEEDS-ACTION".into()),
+            }
+        }
+    }
+
+    lines.push(format!("END:{}", name));
+    Some(lines)
+}
+
+fn write_calendar(entries: &[Entry], store_uid: &str, zone: Zone, stamp: &str) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".into(),
+        format!("PRODID:{}", PRODID),
+    ];
+
+    for entry in entries {
+        lines.extend(
+            component(entry, store_uid, zone, stamp)
+                .into_iter()
+                .flatten(),
+        );
+    }
+
+    lines.push("END:VCALENDAR".into());
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+/// writes `entries` of the store named `store_uid` with date times in `zone` as one iCalendar
+/// object
+///
+/// Meetings without a scheduled time are left out, so are recurrences without a single frequency
+/// like `every 1m2w`.
+pub fn to_calendar(entries: &[Entry], store_uid: &str, zone: Zone) -> String {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    write_calendar(entries, store_uid, zone, &stamp)
+}
+
+/// writes every entry matching `query` as one iCalendar object, see [`to_calendar`]
+pub fn export(store: &mut Store, query: Query) -> anyhow::Result<String> {
+    let entries = store.query(query)?;
+    Ok(to_calendar(&entries, &store.uid()?, store.zone()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    const STAMP: &str = "20250301T120000Z";
+
+    fn berlin() -> Zone {
+        "Europe/Berlin".parse().unwrap()
+    }
+
+    #[test]
+    fn test_meeting() {
+        let mut entry = Entry::test_entry(7, "weekly, sync".into());
+        entry.entry_type = EntryType::Meeting;
+        entry.prefix = "work/acme".into();
+        entry.tags = vec!["team".into()];
+        entry.body = "agenda:\nbudget; hiring".into();
+        entry.scheduled = Some(at("2025-03-03 10:00"));
+        entry.scheduled_end = Some(at("2025-03-03 11:30"));
+        entry.recurrence = Some("every 1w until 2025-06-30".parse().unwrap());
+
+        let expected = [
+            "BEGIN:VEVENT",
+            "UID:7@a1b2.koi",
+            "DTSTAMP:20250301T120000Z",
+            "SUMMARY:weekly\\, sync",
+            "DESCRIPTION:agenda:\\nbudget\\; hiring",
+            "CATEGORIES:work/acme,team",
+            "DTSTART:20250303T090000Z",
+            "RRULE:FREQ=WEEKLY;INTERVAL=1;UNTIL=20250630T215900Z",
+            "DTEND:20250303T103000Z",
+            "END:VEVENT",
+        ];
+        assert_eq!(
+            Some(expected.map(String::from).to_vec()),
+            component(&entry, "a1b2", berlin(), STAMP)
+        );
+
+        entry.scheduled = None;
+        assert_eq!(None, component(&entry, "a1b2", berlin(), STAMP));
+    }
+
+    #[test]
+    fn test_todo() {
+        let mut entry = Entry::test_entry(3, "taxes".into());
+        entry.deadline = Some(at("2025-05-31 00:00"));
+
+        let expected = [
+            "BEGIN:VTODO",
+            "UID:3@a1b2.koi",
+            "DTSTAMP:20250301T120000Z",
+            "SUMMARY:taxes",
+            "DUE:20250530T220000Z",
+            "STATUS:NEEDS-ACTION",
+            "END:VTODO",
+        ];
+        assert_eq!(
+            Some(expected.map(String::from).to_vec()),
+            component(&entry, "a1b2", berlin(), STAMP)
+        );
+
+        entry.closed = Some(at("2025-05-20 18:00"));
+        let lines = component(&entry, "a1b2", berlin(), STAMP).unwrap();
+        assert!(lines.contains(&"STATUS:COMPLETED".into()));
+        assert!(lines.contains(&"COMPLETED:20250520T160000Z".into()));
+    }
+
+    #[test]
+    fn test_rrule() {
+        let rule = |s: &str| rrule(s.parse().unwrap(), Zone::UTC).unwrap();
+        assert_eq!("FREQ=DAILY;INTERVAL=2", rule("every 2d"));
+        assert_eq!("FREQ=MONTHLY;INTERVAL=1;COUNT=5", rule("every 1m 5 times"));
+        assert_eq!("FREQ=YEARLY;INTERVAL=1", rule("every 1y"));
+        assert_eq!("FREQ=HOURLY;INTERVAL=4", rule("every 4h"));
+        assert_eq!("FREQ=DAILY;INTERVAL=9", rule("every 1w2d"));
+        assert_eq!("FREQ=MINUTELY;INTERVAL=90", rule("every 1h30m"));
+        assert_eq!("FREQ=MONTHLY;INTERVAL=18", rule("every 1y6m"));
+        assert_eq!(
+            Some("FREQ=DAILY;INTERVAL=1;UNTIL=20251231T225900Z".into()),
+            rrule("every 1d until 2025-12-31".parse().unwrap(), berlin())
+        );
+        assert_eq!(None, rrule("every 1m2w".parse().unwrap(), Zone::UTC));
+    }
+
+    #[test]
+    fn test_calendar() {
+        let mut meeting = Entry::test_entry(1, "standup".into());
+        meeting.entry_type = EntryType::Meeting;
+        meeting.scheduled = Some(at("2025-03-03 09:00"));
+        let todo = Entry::test_entry(2, "report".into());
+
+        let calendar = write_calendar(&[meeting, todo], "a1b2", Zone::UTC, STAMP);
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(1, calendar.matches("BEGIN:VEVENT\r\n").count());
+        assert_eq!(1, calendar.matches("BEGIN:VTODO\r\n").count());
+    }
+
+    #[test]
+    fn test_export() {
+        let mut store = Store::open_test();
+        store.set_zone(berlin());
+
+        let mut entry = Entry::test_entry(1, "a".into());
+        entry.prefix = "work".into();
+        entry.deadline = Some(at("2025-03-07 17:00"));
+        store.add_entry(&entry).unwrap();
+        store.add_entry(&Entry::test_entry(2, "b".into())).unwrap();
+
+        let query = Query {
+            prefix: Some("work".into()),
+            ..Default::default()
+        };
+        let calendar = export(&mut store, query).unwrap();
+        let uid = entry_uid(&store.uid().unwrap(), 1);
+        assert!(calendar.contains(&format!("UID:{}\r\n", uid)));
+        assert!(calendar.contains("DUE:20250307T160000Z\r\n"));
+        assert!(!calendar.contains("SUMMARY:b\r\n"));
+        assert_eq!(Some(1), store.entry_id_by_uid(&uid).unwrap());
+    }
+}
@@ -0,0 +1,624 @@
+use anyhow::Context;
+
+use crate::entry::{Entry, EntryType, Recurrence, RecurrenceEnd};
+use crate::store::Store;
+use crate::time::prelude::*;
+
+use super::{parse_datetime, unescape_text};
+
+/// a content line like `DTSTART;TZID=Europe/Berlin:20250301T143000`
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Property {
+    /// upper case
+    name: String,
+    /// upper case names with their values, quotes removed
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// a `VEVENT` or `VTODO` with its own properties, nested components like `VALARM` are left out
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Component {
+    name: String,
+    properties: Vec<Property>,
+}
+
+impl Component {
+    fn property(&self, name: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.property(name)
+            .map(|property| unescape_text(&property.value))
+    }
+}
+
+/// a component which was not imported
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Skipped {
+    /// `VEVENT` or `VTODO`
+    pub component: String,
+    pub uid: Option<String>,
+    pub summary: Option<String>,
+    pub reason: String,
+}
+
+/// an entry read from a calendar, its id is `0` and it is opened now
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Imported {
+    pub uid: Option<String>,
+    pub entry: Entry,
+}
+
+/// the content of a calendar, see [`from_calendar`]
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Calendar {
+    pub entries: Vec<Imported>,
+    pub skipped: Vec<Skipped>,
+}
+
+/// the outcome of [`import`]
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ImportReport {
+    /// ids of the new entries
+    pub added: Vec<u64>,
+    /// ids of the entries which were imported before
+    pub updated: Vec<u64>,
+    pub skipped: Vec<Skipped>,
+}
+
+/// joins folded lines, the numbers are the line numbers the unfolded lines start at
+fn unfold(content: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some((_, last))) => last.push_str(continuation),
+            _ if line.is_empty() => (),
+            _ => lines.push((i + 1, line.to_string())),
+        }
+    }
+
+    lines
+}
+
+/// splits `s` at the first `delimiter` outside of double quotes
+fn split_unquoted(s: &str, delimiters: &[char]) -> (String, Option<(char, String)>) {
+    let mut quoted = false;
+    for (i, c) in s.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if !quoted && delimiters.contains(&c) {
+            return (s[..i].to_string(), Some((c, s[i + 1..].to_string())));
+        }
+    }
+    (s.to_string(), None)
+}
+
+fn parse_property(line: &str) -> anyhow::Result<Property> {
+    let (name, mut rest) = split_unquoted(line, &[';', ':']);
+    let mut params = vec![];
+
+    loop {
+        match rest {
+            Some((':', value)) => {
+                return Ok(Property {
+                    name: name.to_ascii_uppercase(),
+                    params,
+                    value,
+                });
+            }
+            Some((_, tail)) => {
+                let (param, next) = split_unquoted(&tail, &[';', ':']);
+                let (key, value) = param
+                    .split_once('=')
+                    .with_context(|| format!("invalid parameter '{}'", param))?;
+                params.push((key.to_ascii_uppercase(), value.replace('"', "")));
+                rest = next;
+            }
+            None => anyhow::bail!("expected 'NAME:value'"),
+        }
+    }
+}
+
+/// the `VEVENT` and `VTODO` components of a calendar
+fn parse_components(content: &str) -> anyhow::Result<Vec<Component>> {
+    let lines = unfold(content);
+    match lines.first() {
+        Some((_, line)) if line.eq_ignore_ascii_case("BEGIN:VCALENDAR") => (),
+        _ => anyhow::bail!("not an iCalendar file, expected 'BEGIN:VCALENDAR'"),
+    }
+
+    let mut components = vec![];
+    let mut stack: Vec<String> = vec![];
+    let mut current: Option<Component> = None;
+
+    for (number, line) in lines {
+        let property = parse_property(&line).with_context(|| format!("line {}", number))?;
+        let value = property.value.to_ascii_uppercase();
+
+        match property.name.as_str() {
+            "BEGIN" => {
+                if current.is_none() && (value == "VEVENT" || value == "VTODO") {
+                    current = Some(Component {
+                        name: value.clone(),
+                        properties: vec![],
+                    });
+                }
+                stack.push(value);
+            }
+            "END" => {
+                if stack.pop().as_ref() != Some(&value) {
+                    anyhow::bail!("line {}: unexpected 'END:{}'", number, property.value);
+                }
+                if let Some(component) = current.take_if(|component| component.name == value) {
+                    components.push(component);
+                }
+            }
+            _ => {
+                if let Some(component) = &mut current
+                    && stack.last() == Some(&component.name)
+                {
+                    component.properties.push(property);
+                }
+            }
+        }
+    }
+
+    if let Some(name) = stack.last() {
+        anyhow::bail!("missing 'END:{}'", name);
+    }
+
+    Ok(components)
+}
+
+/// parses a `DATE-TIME` property as a time in `zone`, see [`super::parse_datetime`]
+fn parse_time(property: &Property, zone: Zone) -> Result<DateTime, String> {
+    if property.param("VALUE") == Some("DATE") || property.value.len() == 8 {
+        return Err(format!(
+            "all-day values are not supported ({})",
+            property.name
+        ));
+    }
+    let datetime = parse_datetime(&property.value, zone)
+        .ok_or_else(|| format!("invalid {} '{}'", property.name, property.value))?;
+
+    match property
+        .param("TZID")
+        .and_then(|tzid| tzid.parse::<Zone>().ok())
+    {
+        Some(tzid) => Ok(tzid.convert(datetime, zone)),
+        None => Ok(datetime),
+    }
+}
+
+/// converts an `RRULE` value with an `UNTIL` in `zone`, only rules `every` can express are
+/// supported
+fn parse_rrule(rule: &str, zone: Zone) -> Result<Recurrence, String> {
+    let mut freq = None;
+    let mut interval = 1;
+    let mut end = None;
+
+    for part in rule.split(';') {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("invalid RRULE part '{}'", part))?;
+        let invalid = || format!("invalid RRULE {} '{}'", key, value);
+
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => freq = Some(value.to_ascii_uppercase()),
+            "INTERVAL" => interval = value.parse().ok().filter(|x| *x > 0).ok_or_else(invalid)?,
+            "COUNT" => {
+                let count = value.parse().ok().filter(|x| *x > 0).ok_or_else(invalid)?;
+                end = Some(RecurrenceEnd::Count(count));
+            }
+            "UNTIL" => {
+                let until = match value.len() {
+                    8 => parse_datetime(&format!("{}T000000", value), zone),
+                    _ => parse_datetime(value, zone),
+                };
+                end = Some(RecurrenceEnd::Until(until.ok_or_else(invalid)?.date()));
+            }
+            // only matters for rules with BYWEEKNO or BYDAY
+            "WKST" => (),
+            _ => return Err(format!("unsupported RRULE part {}", key)),
+        }
+    }
+
+    let every = match freq.as_deref() {
+        Some("MINUTELY") => Duration::Minute(interval),
+        Some("HOURLY") => Duration::Hour(interval),
+        Some("DAILY") => Duration::Day(interval),
+        Some("WEEKLY") => Duration::Week(interval),
+        Some("MONTHLY") => Duration::Month(interval),
+        Some("YEARLY") => Duration::Year(interval),
+        Some(freq) => return Err(format!("unsupported RRULE frequency {}", freq)),
+        None => return Err("RRULE without FREQ".into()),
+    };
+
+    Ok(Recurrence { every, end })
+}
+
+/// converts a component into an entry with date times in `zone`
+///
+/// # Returns
+/// - the reason if the component can not be represented as entry
+fn to_entry(component: &Component, prefix: &str, zone: Zone) -> Result<Entry, String> {
+    let entry_type = match component.name.as_str() {
+        "VEVENT" => EntryType::Meeting,
+        _ => EntryType::Todo,
+    };
+
+    for name in ["RDATE", "EXDATE", "RECURRENCE-ID", "DURATION"] {
+        if component.property(name).is_some() {
+            return Err(format!("{} is not supported", name));
+        }
+    }
+
+    let time = |name: &str| {
+        component
+            .property(name)
+            .map(|property| parse_time(property, zone))
+            .transpose()
+    };
+
+    let title = component
+        .text("SUMMARY")
+        .filter(|title| !title.trim().is_empty())
+        .ok_or("missing SUMMARY")?;
+
+    let scheduled = time("DTSTART")?;
+    if entry_type == EntryType::Meeting && scheduled.is_none() {
+        return Err("missing DTSTART".into());
+    }
+
+    let recurrence = match component.property("RRULE") {
+        Some(_) if scheduled.is_none() => return Err("RRULE without DTSTART".into()),
+        Some(rule) => Some(parse_rrule(&rule.value, zone)?),
+        None => None,
+    };
+
+    let (scheduled_end, deadline, closed) = match entry_type {
+        EntryType::Meeting => (time("DTEND")?, None, None),
+        EntryType::Todo => {
+            let completed = component
+                .property("STATUS")
+                .is_some_and(|status| status.value.eq_ignore_ascii_case("COMPLETED"));
+            let closed = match time("COMPLETED")? {
+                Some(at) => Some(at),
+                None if completed => Some(zone.now()),
+                None => None,
+            };
+            (None, time("DUE")?, closed)
+        }
+    };
+
+    Ok(Entry {
+        id: 0,
+        title: title.trim().to_string(),
+        body: component.text("DESCRIPTION").unwrap_or_default(),
+        prefix: prefix.to_string(),
+        entry_type,
+        opened: zone.now(),
+        closed,
+        scheduled,
+        scheduled_end,
+        deadline,
+        recurrence,
+        tags: vec![],
+    })
+}
+
+/// reads the events and tasks of an iCalendar object as entries with the given prefix and
+/// date times in `zone`
+///
+/// Events become meetings, tasks become todos. Components koi can not represent, like all-day
+/// events or recurrence rules `every` can not express, are skipped.
+///
+/// # Errors
+/// - if `content` is not a well formed iCalendar object
+pub fn from_calendar(content: &str, prefix: &str, zone: Zone) -> anyhow::Result<Calendar> {
+    let mut calendar = Calendar::default();
+
+    for component in parse_components(content)? {
+        let uid = component.text("UID");
+        match to_entry(&component, prefix, zone) {
+            Ok(entry) => calendar.entries.push(Imported { uid, entry }),
+            Err(reason) => calendar.skipped.push(Skipped {
+                summary: component.text("SUMMARY"),
+                component: component.name,
+                uid,
+                reason,
+            }),
+        }
+    }
+
+    Ok(calendar)
+}
+
+/// imports the events and tasks of an iCalendar object into `store`, see [`from_calendar`]
+///
+/// Components with a `UID` which was imported before update their entry instead of adding a
+/// new one. The id, opening time and tags of updated entries are kept, as is the state of
+/// meetings.
+///
+/// # Errors
+/// - if `content` is not a well formed iCalendar object or an entry can not be stored, nothing
+///   is imported then
+pub fn import(store: &mut Store, content: &str, prefix: &str) -> anyhow::Result<ImportReport> {
+    let calendar = from_calendar(content, prefix, store.zone())?;
+    let entries = calendar
+        .entries
+        .into_iter()
+        .map(|Imported { uid, entry }| (uid, entry));
+    let imported = store.import_entries(entries, |old, new| {
+        let closed = match new.entry_type {
+            EntryType::Meeting => old.closed,
+            EntryType::Todo => new.closed,
+        };
+        Entry {
+            opened: old.opened,
+            closed,
+            tags: old.tags,
+            ..new
+        }
+    })?;
+
+    Ok(ImportReport {
+        added: imported.added,
+        updated: imported.updated,
+        skipped: calendar.skipped,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ical::{export, to_calendar};
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+PRODID:-//Example//Calendar//EN\r
+BEGIN:VEVENT\r
+UID:sync@example.com\r
+SUMMARY:Weekly sync\\, team\r
+DESCRIPTION:Agenda:\\n- budget\r
+DTSTART;TZID=Europe/Berlin:20250303T100000\r
+DTEND;TZID=Europe/Berlin:20250303T110000\r
+RRULE:FREQ=WEEKLY;INTERVAL=2;UNTIL=20250630T215959Z\r
+BEGIN:VALARM\r
+ACTION:DISPLAY\r
+DESCRIPTION:Reminder\r
+TRIGGER:-PT15M\r
+END:VALARM\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:holiday@example.com\r
+SUMMARY:Holiday\r
+DTSTART;VALUE=DATE:20250418\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:standup@example.com\r
+SUMMARY:Standup\r
+DTSTART:20250303T090000\r
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR\r
+END:VEVENT\r
+BEGIN:VTODO\r
+UID:taxes@example.com\r
+SUMMARY:Taxes\r
+DUE:20250531T000000\r
+STATUS:NEEDS-ACTION\r
+END:VTODO\r
+END:VCALENDAR\r
+";
+
+    #[test]
+    fn test_parse_property() {
+        assert_eq!(
+            Property {
+                name: "DTSTART".into(),
+                params: vec![("TZID".into(), "Europe/Berlin".into())],
+                value: "20250301T143000".into(),
+            },
+            parse_property("dtstart;tzid=Europe/Berlin:20250301T143000").unwrap()
+        );
+
+        let property = parse_property("ATTENDEE;CN=\"Doe; Jane\":mailto:jane@example.com").unwrap();
+        assert_eq!(Some("Doe; Jane"), property.param("CN"));
+        assert_eq!("mailto:jane@example.com", property.value);
+
+        assert!(parse_property("SUMMARY").is_err());
+        assert!(parse_property("SUMMARY;X:y").is_err());
+    }
+
+    #[test]
+    fn test_unfold() {
+        let lines = unfold("BEGIN:VCALENDAR\r\nDESCRIPTION:a\r\n  b\r\n\tc\r\nEND:VCALENDAR\r\n");
+        assert_eq!(
+            vec![
+                (1, "BEGIN:VCALENDAR".to_string()),
+                (2, "DESCRIPTION:a bc".to_string()),
+                (5, "END:VCALENDAR".to_string()),
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn test_parse_components() {
+        let components = parse_components(CALENDAR).unwrap();
+        assert_eq!(4, components.len());
+
+        // the alarm's description is not the event's
+        assert_eq!(
+            Some("Agenda:\n- budget".to_string()),
+            components[0].text("DESCRIPTION")
+        );
+        assert!(components[0].property("TRIGGER").is_none());
+
+        assert!(parse_components("").is_err());
+        assert!(parse_components("BEGIN:VEVENT\r\nEND:VEVENT\r\n").is_err());
+        assert!(parse_components("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nEND:VCALENDAR\r\n").is_err());
+        assert!(parse_components("BEGIN:VCALENDAR\r\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_rrule() {
+        let berlin = "Europe/Berlin".parse().unwrap();
+        assert_eq!(
+            Ok("every 1d until 2025-12-31".parse().unwrap()),
+            parse_rrule("FREQ=DAILY;UNTIL=20251231T225900Z", berlin)
+        );
+
+        let parse_rrule = |rule| parse_rrule(rule, Zone::UTC);
+        assert_eq!(Ok("every 1d".parse().unwrap()), parse_rrule("FREQ=DAILY"));
+        assert_eq!(
+            Ok("every 2w 10 times".parse().unwrap()),
+            parse_rrule("FREQ=WEEKLY;INTERVAL=2;COUNT=10;WKST=MO")
+        );
+        assert_eq!(
+            Ok("every 1m until 2025-12-31".parse().unwrap()),
+            parse_rrule("FREQ=MONTHLY;UNTIL=20251231")
+        );
+
+        assert_eq!(
+            Ok("every 90min".parse().unwrap()),
+            parse_rrule("FREQ=MINUTELY;INTERVAL=90")
+        );
+
+        assert!(parse_rrule("FREQ=SECONDLY").is_err());
+        assert!(parse_rrule("INTERVAL=2").is_err());
+        assert!(parse_rrule("FREQ=DAILY;INTERVAL=0").is_err());
+        assert!(parse_rrule("FREQ=MONTHLY;BYMONTHDAY=-1").is_err());
+    }
+
+    #[test]
+    fn test_from_calendar() {
+        let tokyo: Zone = "Asia/Tokyo".parse().unwrap();
+        let calendar = from_calendar(CALENDAR, "work", tokyo).unwrap();
+
+        assert_eq!(2, calendar.entries.len());
+
+        let sync = &calendar.entries[0];
+        assert_eq!(Some("sync@example.com".to_string()), sync.uid);
+        assert_eq!("Weekly sync, team", sync.entry.title);
+        assert_eq!("Agenda:\n- budget", sync.entry.body);
+        assert_eq!("work", sync.entry.prefix);
+        assert_eq!(EntryType::Meeting, sync.entry.entry_type);
+        let berlin = |s| ZonedDateTime::new(at(s), "Europe/Berlin".parse().unwrap());
+        assert_eq!(
+            Some(berlin("2025-03-03 10:00").in_zone(tokyo)),
+            sync.entry.scheduled
+        );
+        assert_eq!(
+            Some(berlin("2025-03-03 11:00").in_zone(tokyo)),
+            sync.entry.scheduled_end
+        );
+        assert_eq!(
+            Some("every 2w until 2025-07-01".parse().unwrap()),
+            sync.entry.recurrence
+        );
+
+        let taxes = &calendar.entries[1].entry;
+        assert_eq!(EntryType::Todo, taxes.entry_type);
+        assert_eq!(Some(at("2025-05-31 00:00")), taxes.deadline);
+        assert!(taxes.is_open());
+
+        let skipped: Vec<_> = calendar
+            .skipped
+            .iter()
+            .map(|skipped| (skipped.summary.as_deref(), skipped.reason.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    Some("Holiday"),
+                    "all-day values are not supported (DTSTART)"
+                ),
+                (Some("Standup"), "unsupported RRULE part BYDAY"),
+            ],
+            skipped
+        );
+    }
+
+    #[test]
+    fn test_import() {
+        let mut store = Store::open_test();
+        store.set_zone("Europe/Berlin".parse().unwrap());
+
+        let report = import(&mut store, CALENDAR, "work").unwrap();
+        assert_eq!(2, report.added.len());
+        assert!(report.updated.is_empty());
+        assert_eq!(2, report.skipped.len());
+
+        let id = report.added[1];
+        let mut taxes = store.query_by_id(id).unwrap();
+        taxes.tags = vec!["money".into()];
+        store.update_entry(&taxes).unwrap();
+
+        let changed = CALENDAR
+            .replace("SUMMARY:Taxes", "SUMMARY:Taxes 2024")
+            .replace(
+                "STATUS:NEEDS-ACTION",
+                "STATUS:COMPLETED\r\nCOMPLETED:20250520T160000Z",
+            );
+        let report = import(&mut store, &changed, "work").unwrap();
+        assert!(report.added.is_empty());
+        assert_eq!(2, report.updated.len());
+
+        assert_eq!(2, store.query(Default::default()).unwrap().len());
+        let updated = store.query_by_id(id).unwrap();
+        assert_eq!("Taxes 2024", updated.title);
+        assert_eq!(taxes.opened, updated.opened);
+        assert_eq!(taxes.tags, updated.tags);
+        assert_eq!(Some(at("2025-05-20 18:00")), updated.closed);
+
+        // an exported calendar updates the entries it was exported from
+        let exported = export(&mut store, Default::default()).unwrap();
+        let report = import(&mut store, &exported, "work").unwrap();
+        assert!(report.added.is_empty());
+        assert_eq!(2, report.updated.len());
+        assert_eq!(updated, store.query_by_id(id).unwrap());
+    }
+
+    #[test]
+    fn test_export_import() {
+        let mut meeting = Entry::test_entry(1, "planning; q2".into());
+        meeting.entry_type = EntryType::Meeting;
+        meeting.body = "first line\nsecond line, with comma".into();
+        meeting.scheduled = Some(at("2025-04-01 13:00"));
+        meeting.scheduled_end = Some(at("2025-04-01 14:30"));
+        meeting.recurrence = Some("every 1m 3 times".parse().unwrap());
+
+        // UTC in the file, the same wall clock times in the zone of the store
+        let tokyo = "Asia/Tokyo".parse().unwrap();
+        let content = to_calendar(&[meeting.clone()], "a1b2", tokyo);
+        assert!(content.contains("DTSTART:20250401T040000Z\r\n"));
+        assert!(content.contains("UID:1@a1b2.koi\r\n"));
+        let calendar = from_calendar(&content, "", tokyo).unwrap();
+        let imported = Entry {
+            id: 1,
+            opened: meeting.opened,
+            ..calendar.entries[0].entry.clone()
+        };
+        assert_eq!(meeting, imported);
+        assert!(calendar.skipped.is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+//! conversion between entries and iCalendar (RFC 5545)
+//!
+//! Meetings become `VEVENT`s, todos become `VTODO`s. Date times of entries are wall clock times
+//! in the zone of their store, they are written in UTC like `20250301T133000Z` so calendar
+//! applications show them at the same instant in every zone. When reading, UTC times and times
+//! with a known `TZID` are converted to the zone of the store, floating times and times with an
+//! unknown `TZID` are taken as wall clock times in it.
+
+use crate::time::prelude::*;
+
+mod export;
+pub use export::{export, to_calendar};
+
+mod import;
+pub use import::{Calendar, ImportReport, Imported, Skipped, from_calendar, import};
+
+/// the longest line allowed by RFC 5545 in octets, without the line break
+const MAX_LINE_LEN: usize = 75;
+
+/// escapes `,`, `;`, `\` and line breaks in a text value
+fn escape_text(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => (),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// undoes [`escape_text`], unknown escapes are kept as they are
+fn unescape_text(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(c @ ('\\' | ';' | ',')) => unescaped.push(c),
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// splits `line` into lines of at most [`MAX_LINE_LEN`] octets, continuation lines start with
+/// a space, every line ends with `\r\n`
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut len = 0;
+
+    for c in line.chars() {
+        if len + c.len_utf8() > MAX_LINE_LEN {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(c);
+        len += c.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
+
+/// formats a date time as floating `DATE-TIME` value like `20250301T143000`
+fn format_datetime(datetime: DateTime) -> String {
+    format!(
+        "{:>04}{:>02}{:>02}T{:>02}{:>02}00",
+        datetime.year(),
+        datetime.month(),
+        datetime.day(),
+        datetime.hour(),
+        datetime.minute()
+    )
+}
+
+/// formats a date time in `zone` as `DATE-TIME` value in UTC like `20250301T133000Z`
+fn format_utc(datetime: DateTime, zone: Zone) -> String {
+    format!("{}Z", format_datetime(zone.to_utc(datetime)))
+}
+
+/// parses a `DATE-TIME` value like `20250301T143000` as a time in `zone`, UTC values like
+/// `20250301T133000Z` are converted to `zone`, seconds are dropped
+///
+/// # Returns
+/// - `None` if `s` is not a `DATE-TIME` value, `DATE` values included
+fn parse_datetime(s: &str, zone: Zone) -> Option<DateTime> {
+    let (s, from) = match s.strip_suffix('Z') {
+        Some(s) => (s, Zone::UTC),
+        None => (s, zone),
+    };
+
+    let naive = chrono::NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%S").ok()?;
+    let datetime = DateTime::from_chrono(naive)?;
+    Some(from.convert(datetime, zone))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!("plain", escape_text("plain"));
+        assert_eq!("a\\, b\\; c\\\\d", escape_text("a, b; c\\d"));
+        assert_eq!("line\\nbreak", escape_text("line\r\nbreak"));
+    }
+
+    #[test]
+    fn test_unescape_text() {
+        for s in ["plain", "a, b; c\\d", "line\nbreak", "trailing\\"] {
+            assert_eq!(s, unescape_text(&escape_text(s)));
+        }
+        assert_eq!("line\nbreak", unescape_text("line\\Nbreak"));
+        assert_eq!("C:\\x", unescape_text("C:\\x"));
+    }
+
+    #[test]
+    fn test_fold_line() {
+        assert_eq!("SUMMARY:short\r\n", fold_line("SUMMARY:short"));
+
+        let line = format!("DESCRIPTION:{}", "x".repeat(100));
+        let folded = fold_line(&line);
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(3, lines.len());
+        assert_eq!(75, lines[0].len());
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(line, folded.replace("\r\n ", "").trim_end());
+
+        // multi byte characters are never split
+        let line = "ö".repeat(50);
+        for part in fold_line(&line).split("\r\n") {
+            assert!(part.len() <= 75);
+        }
+    }
+
+    #[test]
+    fn test_format_datetime() {
+        let datetime: DateTime = "2025-03-01 14:30".parse().unwrap();
+        assert_eq!("20250301T143000", format_datetime(datetime));
+    }
+
+    #[test]
+    fn test_format_utc() {
+        let datetime: DateTime = "2025-03-01 14:30".parse().unwrap();
+        let berlin = "Europe/Berlin".parse().unwrap();
+        assert_eq!("20250301T133000Z", format_utc(datetime, berlin));
+        assert_eq!("20250301T143000Z", format_utc(datetime, Zone::UTC));
+    }
+
+    #[test]
+    fn test_parse_datetime() {
+        let datetime: DateTime = "2025-03-01 14:30".parse().unwrap();
+        let berlin: Zone = "Europe/Berlin".parse().unwrap();
+        let tokyo: Zone = "Asia/Tokyo".parse().unwrap();
+        for zone in [berlin, tokyo, Zone::UTC, Zone::Local] {
+            assert_eq!(Some(datetime), parse_datetime("20250301T143000", zone));
+            assert_eq!(Some(datetime), parse_datetime("20250301T143059", zone));
+            let utc = format_utc(datetime, zone);
+            assert_eq!(Some(datetime), parse_datetime(&utc, zone));
+        }
+        assert_eq!(
+            Some("2025-03-01 15:30".parse().unwrap()),
+            parse_datetime("20250301T143000Z", berlin)
+        );
+
+        assert_eq!(None, parse_datetime("20250301", berlin));
+        assert_eq!(None, parse_datetime("20250230T143000", berlin));
+        assert_eq!(None, parse_datetime("2025-03-01 14:30", berlin));
+    }
+}
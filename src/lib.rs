@@ -1,3 +1,32 @@
+/// implements `Serialize` and `Deserialize` through `Display` and `FromStr`, so a value is the
+/// same text in JSON as everywhere else
+///
+/// Types whose `FromStr` understands more than they write, like relative dates, pass the stricter
+/// `$parse` for the text they write.
+macro_rules! serde_with_str {
+    ($type:ty) => {
+        serde_with_str!($type, |s: &str| s.parse::<$type>());
+    };
+    ($type:ty, $parse:expr) => {
+        impl serde::Serialize for $type {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $type {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+                ($parse)(&s).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
 pub mod time;
 pub mod entry;
 pub mod store;
+pub mod cli;
+pub mod ical;
+pub mod org;
+pub mod todotxt;
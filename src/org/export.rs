@@ -0,0 +1,271 @@
+use crate::entry::{Entry, EntryType, Recurrence};
+use crate::store::{Query, Store, entry_uid};
+use crate::time::prelude::*;
+
+use super::{Timestamp, format_timestamp, is_keyword, is_tags};
+
+/// the interval of the org repeater of `recurrence`
+///
+/// # Returns
+/// - `None` if the recurrence ends or repeats by minutes or several units at once, org
+///   repeaters can not express that
+fn repeater(recurrence: Recurrence) -> Option<Duration> {
+    match recurrence.every {
+        _ if recurrence.end.is_some() => None,
+        Duration::Minute(_) | Duration::Compound { .. } => None,
+        every => Some(every),
+    }
+}
+
+/// the segments of `prefix`, which become the headings above an entry
+fn segments(prefix: &str) -> Vec<&str> {
+    match prefix {
+        "" => vec![],
+        prefix => prefix.split('/').collect(),
+    }
+}
+
+/// escapes lines of a section which org would read as a headline, like `,* not a heading`
+fn escape_line(line: &str) -> String {
+    if line.trim_start_matches(',').starts_with('*') {
+        format!(",{}", line)
+    } else {
+        line.to_string()
+    }
+}
+
+/// escapes a title or heading which org would read with a keyword or tags, like `,DONE review`
+/// or `fix ,:bug:`
+fn escape_title(title: &str) -> String {
+    let words = title.trim();
+    let mut escaped = title.to_string();
+    let last = words.rsplit([' ', '\t']).next().unwrap_or_default();
+    if is_tags(last.trim_start_matches(',')) {
+        escaped.insert(title.trim_end().len() - last.len(), ',');
+    }
+    let first = words.split(' ').next().unwrap_or_default();
+    if is_keyword(first.trim_start_matches(',')) {
+        escaped.insert(title.len() - title.trim_start().len(), ',');
+    }
+    escaped
+}
+
+/// the lines of the headline of `entry` from the store named `store_uid` with its planning line,
+/// property drawer and section
+fn headline(entry: &Entry, store_uid: &str, level: usize) -> Vec<String> {
+    let keyword = match entry.entry_type {
+        EntryType::Todo if entry.is_open() => "TODO ",
+        EntryType::Todo => "DONE ",
+        EntryType::Meeting => "",
+    };
+    let mut headline = format!(
+        "{} {}{}",
+        "*".repeat(level),
+        keyword,
+        escape_title(&entry.title)
+    );
+    if !entry.tags.is_empty() {
+        headline.push_str(&format!(" :{}:", entry.tags.join(":")));
+    }
+    let mut lines = vec![headline];
+
+    let repeater = entry.recurrence.and_then(repeater);
+    let active = |start, end| {
+        let timestamp = Timestamp {
+            start,
+            end,
+            repeater,
+        };
+        format_timestamp(true, timestamp)
+    };
+
+    let mut planning = vec![];
+    if let Some(closed) = entry.closed {
+        let closed = Timestamp {
+            start: closed,
+            end: None,
+            repeater: None,
+        };
+        planning.push(format!("CLOSED: {}", format_timestamp(false, closed)));
+    }
+    if let Some(scheduled) = entry.scheduled {
+        planning.push(format!(
+            "SCHEDULED: {}",
+            active(scheduled, entry.scheduled_end)
+        ));
+    }
+    if let Some(deadline) = entry.deadline {
+        planning.push(format!("DEADLINE: {}", active(deadline, None)));
+    }
+    if !planning.is_empty() {
+        lines.push(planning.join(" "));
+    }
+
+    let opened = Timestamp {
+        start: entry.opened,
+        end: None,
+        repeater: None,
+    };
+    lines.push(":PROPERTIES:".into());
+    lines.push(format!(":ID: {}", entry_uid(store_uid, entry.id)));
+    lines.push(format!(":CREATED: {}", format_timestamp(false, opened)));
+    if let Some(recurrence) = entry.recurrence
+        && repeater.is_none()
+    {
+        lines.push(format!(":RECURRENCE: {}", recurrence));
+    }
+    if let (None, Some(end)) = (entry.scheduled, entry.scheduled_end) {
+        let end = Timestamp {
+            start: end,
+            end: None,
+            repeater: None,
+        };
+        lines.push(format!(":SCHEDULED_END: {}", format_timestamp(false, end)));
+    }
+    lines.push(":END:".into());
+
+    if !entry.body.is_empty() {
+        lines.extend(entry.body.split('\n').map(escape_line));
+    }
+
+    lines
+}
+
+/// writes `entries` of the store named `store_uid` as an org file, ordered by prefix and id
+///
+/// Tags are written as org tags, so tags containing `:` do not survive a round trip through
+/// [`from_org`](super::from_org).
+pub fn to_org(entries: &[Entry], store_uid: &str) -> String {
+    let mut entries: Vec<&Entry> = entries.iter().collect();
+    entries.sort_by_key(|entry| (segments(&entry.prefix), entry.id));
+
+    let mut lines = vec![];
+    let mut path: Vec<&str> = vec![];
+    for entry in entries {
+        let segments = segments(&entry.prefix);
+        let common = path
+            .iter()
+            .zip(&segments)
+            .take_while(|(a, b)| a == b)
+            .count();
+        for (i, segment) in segments.iter().enumerate().skip(common) {
+            lines.push(format!("{} {}", "*".repeat(i + 1), escape_title(segment)));
+        }
+
+        lines.extend(headline(entry, store_uid, segments.len() + 1));
+        path = segments;
+    }
+
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// writes every entry matching `query` as an org file, see [`to_org`]
+pub fn export(store: &mut Store, query: Query) -> anyhow::Result<String> {
+    let entries = store.query(query)?;
+    Ok(to_org(&entries, &store.uid()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_headline() {
+        let mut entry = Entry::test_entry(7, "weekly sync".into());
+        entry.entry_type = EntryType::Meeting;
+        entry.opened = at("2025-02-20 09:00");
+        entry.tags = vec!["team".into(), "work".into()];
+        entry.body = "agenda:\n* budget\n\nhiring".into();
+        entry.scheduled = Some(at("2025-03-03 10:00"));
+        entry.scheduled_end = Some(at("2025-03-03 11:30"));
+        entry.recurrence = Some("every 1w".parse().unwrap());
+
+        let expected = [
+            "** weekly sync :team:work:",
+            "SCHEDULED: <2025-03-03 Mon 10:00-11:30 +1w>",
+            ":PROPERTIES:",
+            ":ID: 7@a1b2.koi",
+            ":CREATED: [2025-02-20 Thu 09:00]",
+            ":END:",
+            "agenda:",
+            ",* budget",
+            "",
+            "hiring",
+        ];
+        assert_eq!(
+            expected.map(String::from).to_vec(),
+            headline(&entry, "a1b2", 2)
+        );
+
+        let mut entry = Entry::test_entry(3, "taxes".into());
+        entry.opened = at("2025-02-20 09:00");
+        entry.closed = Some(at("2025-05-20 18:00"));
+        entry.deadline = Some(at("2025-05-31 00:00"));
+        entry.recurrence = Some("every 1y 3 times".parse().unwrap());
+
+        let expected = [
+            "* DONE taxes",
+            "CLOSED: [2025-05-20 Tue 18:00] DEADLINE: <2025-05-31 Sat>",
+            ":PROPERTIES:",
+            ":ID: 3@a1b2.koi",
+            ":CREATED: [2025-02-20 Thu 09:00]",
+            ":RECURRENCE: every 1y 3 times",
+            ":END:",
+        ];
+        assert_eq!(
+            expected.map(String::from).to_vec(),
+            headline(&entry, "a1b2", 1)
+        );
+    }
+
+    #[test]
+    fn test_to_org() {
+        let entry = |id, title: &str, prefix: &str| {
+            let mut entry = Entry::test_entry(id, title.into());
+            entry.prefix = prefix.into();
+            entry.opened = at("2025-03-01 12:00");
+            entry
+        };
+        let entries = [
+            entry(4, "invoice", "work/acme"),
+            entry(1, "groceries", ""),
+            entry(3, "standup", "work"),
+            entry(2, "report", "work/acme"),
+            entry(5, "plants", "home"),
+        ];
+
+        let headings: Vec<String> = to_org(&entries, "a1b2")
+            .lines()
+            .filter(|line| line.starts_with('*'))
+            .map(String::from)
+            .collect();
+        let expected = [
+            "* TODO groceries",
+            "* home",
+            "** TODO plants",
+            "* work",
+            "** TODO standup",
+            "** acme",
+            "*** TODO report",
+            "*** TODO invoice",
+        ];
+        assert_eq!(expected.map(String::from).to_vec(), headings);
+    }
+
+    #[test]
+    fn test_escape_title() {
+        assert_eq!("weekly sync", escape_title("weekly sync"));
+        assert_eq!(",DONE review", escape_title("DONE review"));
+        assert_eq!(",TODO", escape_title("TODO"));
+        assert_eq!(",,TODO x", escape_title(",TODO x"));
+        assert_eq!("TODOs", escape_title("TODOs"));
+        assert_eq!("fix ,:bug:", escape_title("fix :bug:"));
+        assert_eq!(",:bug:", escape_title(":bug:"));
+        assert_eq!(",DONE ,,:a:b:", escape_title("DONE ,:a:b:"));
+        assert_eq!("a : b", escape_title("a : b"));
+    }
+}
@@ -0,0 +1,530 @@
+use anyhow::Context;
+
+use crate::entry::{Entry, EntryType, Recurrence};
+use crate::store::Store;
+use crate::time::prelude::*;
+
+use super::{Timestamp, is_keyword, is_tags, parse_timestamp};
+
+/// an entry read from an org file, with the `ID` property of its headline
+///
+/// Its id is `0`, entries without a `CREATED` property are opened now.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Imported {
+    pub uid: Option<String>,
+    pub entry: Entry,
+}
+
+/// the outcome of [`import`]
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ImportReport {
+    /// ids of the new entries
+    pub added: Vec<u64>,
+    /// ids of the entries which were imported before
+    pub updated: Vec<u64>,
+}
+
+/// a headline with the lines of its section, up to the next headline
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Headline<'a> {
+    /// the line number of the headline
+    number: usize,
+    level: usize,
+    /// `TODO` or `DONE`
+    keyword: Option<&'a str>,
+    title: String,
+    tags: Vec<String>,
+    section: Vec<&'a str>,
+}
+
+/// the timestamps of a planning line like `CLOSED: [...] SCHEDULED: <...>`
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+struct Planning {
+    closed: Option<Timestamp>,
+    scheduled: Option<Timestamp>,
+    deadline: Option<Timestamp>,
+}
+
+/// parses a headline like `** TODO title :tag:other:`
+///
+/// # Returns
+/// - `None` if `line` is no headline, stars have to be followed by a space
+fn parse_headline(number: usize, line: &str) -> Option<Headline<'_>> {
+    let rest = line.trim_start_matches('*');
+    let level = line.len() - rest.len();
+    if level == 0 || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    let mut rest = rest.trim();
+
+    let mut tags = vec![];
+    let (title, last) = rest.rsplit_once([' ', '\t']).unwrap_or(("", rest));
+    if is_tags(last) {
+        tags = last
+            .split(':')
+            .filter(|tag| !tag.is_empty())
+            .map(String::from)
+            .collect();
+        tags.sort();
+        tags.dedup();
+        rest = title.trim_end();
+    }
+
+    let (keyword, title) = match rest.split_once(' ').unwrap_or((rest, "")) {
+        (keyword, title) if is_keyword(keyword) => (Some(keyword), title.trim_start()),
+        _ => (None, rest),
+    };
+
+    Some(Headline {
+        number,
+        level,
+        keyword,
+        title: unescape_title(title),
+        tags,
+        section: vec![],
+    })
+}
+
+/// undoes the escaping of a title org would read with a keyword or tags, like `,DONE review`
+/// or `fix ,:bug:`
+fn unescape_title(title: &str) -> String {
+    let mut title = title.to_string();
+    let last = title.rsplit([' ', '\t']).next().unwrap_or_default();
+    if last.starts_with(',') && is_tags(last.trim_start_matches(',')) {
+        title.remove(title.len() - last.len());
+    }
+    let first = title.split(' ').next().unwrap_or_default();
+    if first.starts_with(',') && is_keyword(first.trim_start_matches(',')) {
+        title.remove(0);
+    }
+    title
+}
+
+/// the headlines of an org file, lines before the first one are left out
+fn parse_headlines(content: &str) -> Vec<Headline<'_>> {
+    let mut headlines: Vec<Headline> = vec![];
+    for (i, line) in content.lines().enumerate() {
+        match parse_headline(i + 1, line) {
+            Some(headline) => headlines.push(headline),
+            None => {
+                if let Some(headline) = headlines.last_mut() {
+                    headline.section.push(line);
+                }
+            }
+        }
+    }
+    headlines
+}
+
+/// parses a planning line
+///
+/// # Returns
+/// - `None` if `line` is no planning line
+/// - an error if it is one but a timestamp is invalid
+fn parse_planning(line: &str) -> Option<anyhow::Result<Planning>> {
+    let keywords = ["CLOSED:", "SCHEDULED:", "DEADLINE:"];
+    let mut rest = line.trim();
+    if !keywords.iter().any(|keyword| rest.starts_with(keyword)) {
+        return None;
+    }
+
+    let mut planning = Planning::default();
+    while !rest.is_empty() {
+        let Some((keyword, timestamp)) = rest.split_once(':') else {
+            return Some(Err(anyhow::anyhow!("expected a keyword in '{}'", rest)));
+        };
+        let timestamp = timestamp.trim_start();
+        let Some((parsed, after)) = parse_timestamp(timestamp) else {
+            return Some(Err(anyhow::anyhow!(
+                "invalid timestamp after {}: in '{}'",
+                keyword,
+                line.trim()
+            )));
+        };
+
+        let field = match keyword {
+            "CLOSED" => &mut planning.closed,
+            "SCHEDULED" => &mut planning.scheduled,
+            "DEADLINE" => &mut planning.deadline,
+            _ => return Some(Err(anyhow::anyhow!("unknown keyword '{}'", keyword))),
+        };
+        *field = Some(parsed);
+        rest = after.trim_start();
+    }
+
+    Some(Ok(planning))
+}
+
+/// undoes the escaping of lines starting with `*` like `,* not a heading`
+fn unescape_line(line: &str) -> &str {
+    match line.strip_prefix(',') {
+        Some(rest) if rest.trim_start_matches(',').starts_with('*') => rest,
+        _ => line,
+    }
+}
+
+/// the entry of `headline`
+///
+/// # Returns
+/// - `None` if the headline is a heading of a prefix segment, it has neither a keyword nor a
+///   planning line nor a property drawer
+fn to_entry(headline: &Headline, prefix: &str) -> anyhow::Result<Option<Imported>> {
+    let mut section = headline.section.iter().copied().peekable();
+
+    let planning = match section.peek().and_then(|line| parse_planning(line)) {
+        Some(planning) => {
+            section.next();
+            Some(planning?)
+        }
+        None => None,
+    };
+
+    let mut properties: Vec<(String, &str)> = vec![];
+    let has_drawer = section
+        .peek()
+        .is_some_and(|line| line.trim().eq_ignore_ascii_case(":PROPERTIES:"));
+    if has_drawer {
+        section.next();
+        loop {
+            let line = section
+                .next()
+                .context("missing :END: of the property drawer")?
+                .trim();
+            if line.eq_ignore_ascii_case(":END:") {
+                break;
+            }
+            let (name, value) = line
+                .strip_prefix(':')
+                .and_then(|line| line.split_once(':'))
+                .with_context(|| {
+                    format!("expected ':NAME: value' in the drawer, got '{}'", line)
+                })?;
+            properties.push((name.to_ascii_uppercase(), value.trim()));
+        }
+    }
+
+    if headline.keyword.is_none() && planning.is_none() && !has_drawer {
+        return Ok(None);
+    }
+
+    let property = |name: &str| {
+        properties
+            .iter()
+            .find(|(property, _)| property == name)
+            .map(|(_, value)| *value)
+    };
+    let timestamp = |name: &str| {
+        property(name)
+            .map(|value| match parse_timestamp(value) {
+                Some((timestamp, "")) => Ok(timestamp.start),
+                _ => Err(anyhow::anyhow!(
+                    "invalid timestamp in {}: '{}'",
+                    name,
+                    value
+                )),
+            })
+            .transpose()
+    };
+
+    let planning = planning.unwrap_or_default();
+    let (scheduled, scheduled_end) = match planning.scheduled {
+        Some(scheduled) => (Some(scheduled.start), scheduled.end),
+        None => (None, timestamp("SCHEDULED_END")?),
+    };
+
+    let recurrence = match property("RECURRENCE") {
+        Some(recurrence) => Some(
+            recurrence
+                .parse()
+                .map_err(|_| anyhow::anyhow!("invalid recurrence '{}'", recurrence))?,
+        ),
+        None => planning
+            .scheduled
+            .or(planning.deadline)
+            .and_then(|timestamp| timestamp.repeater)
+            .map(|every| Recurrence { every, end: None }),
+    };
+
+    let closed = match (planning.closed, headline.keyword) {
+        (Some(closed), _) => Some(closed.start),
+        (None, Some("DONE")) => Some(now()),
+        (None, _) => None,
+    };
+
+    let uid = property("ID").map(String::from);
+    let mut body: Vec<&str> = section.map(unescape_line).collect();
+    while body.last().is_some_and(|line| line.trim().is_empty()) {
+        body.pop();
+    }
+
+    let entry = Entry {
+        id: 0,
+        title: headline.title.clone(),
+        body: body.join("\n"),
+        prefix: prefix.to_string(),
+        entry_type: match headline.keyword {
+            Some(_) => EntryType::Todo,
+            None => EntryType::Meeting,
+        },
+        opened: timestamp("CREATED")?.unwrap_or_else(now),
+        closed,
+        scheduled,
+        scheduled_end,
+        deadline: planning.deadline.map(|deadline| deadline.start),
+        recurrence,
+        tags: headline.tags.clone(),
+    };
+
+    Ok(Some(Imported { uid, entry }))
+}
+
+/// reads the entries of an org file, their prefix is `prefix` followed by the headings above
+/// them
+///
+/// Headlines with a `TODO` or `DONE` keyword become todos, other headlines with a planning
+/// line or property drawer become meetings. The remaining headlines are headings of prefix
+/// segments. Trailing blank lines of a section are not part of the body.
+///
+/// # Errors
+/// - if a planning line, timestamp, property drawer or recurrence is invalid
+pub fn from_org(content: &str, prefix: &str) -> anyhow::Result<Vec<Imported>> {
+    let mut entries = vec![];
+
+    // the level of each headline above the current one, with its segment if it is a heading
+    let mut path: Vec<(usize, Option<String>)> = vec![];
+    for headline in parse_headlines(content) {
+        while path
+            .last()
+            .is_some_and(|(level, _)| *level >= headline.level)
+        {
+            path.pop();
+        }
+
+        let segments: Vec<&str> = std::iter::once(prefix)
+            .filter(|prefix| !prefix.is_empty())
+            .chain(path.iter().filter_map(|(_, segment)| segment.as_deref()))
+            .collect();
+        let imported = to_entry(&headline, &segments.join("/"))
+            .with_context(|| format!("line {}", headline.number))?;
+
+        match imported {
+            Some(imported) => {
+                entries.push(imported);
+                path.push((headline.level, None));
+            }
+            None => path.push((headline.level, Some(headline.title))),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// imports the entries of an org file into `store`, see [`from_org`]
+///
+/// Headlines with an `ID` which was imported before update their entry instead of adding a
+/// new one, the id and opening time of updated entries are kept.
+///
+/// # Errors
+/// - if `content` is not a valid org file or an entry can not be stored, nothing is imported
+///   then
+pub fn import(store: &mut Store, content: &str, prefix: &str) -> anyhow::Result<ImportReport> {
+    let entries = from_org(content, prefix)?
+        .into_iter()
+        .map(|Imported { uid, entry }| (uid, entry));
+    let imported = store.import_entries(entries, |old, new| Entry {
+        opened: old.opened,
+        ..new
+    })?;
+
+    Ok(ImportReport {
+        added: imported.added,
+        updated: imported.updated,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::org::{export, to_org};
+    use crate::store::Query;
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    const ORG: &str = "#+TITLE: notes
+
+* Work
+** TODO [#A] write report :urgent:
+DEADLINE: <2025-03-07 Fri 17:00 -1d> SCHEDULED: <2025-03-03 Mon>
+Outline first.
+** DONE call bank
+CLOSED: [2025-03-01 Sat 11:00]
+** Acme
+*** Kickoff :client:
+   SCHEDULED: <2025-03-04 Tue 10:00-11:30>
+   :PROPERTIES:
+   :ID:       kickoff@example.com
+   :END:
+   Bring slides.
+,* not a heading
+
+* Home
+** TODO water plants
+SCHEDULED: <2025-03-02 Sun 09:00 .+1w>
+";
+
+    #[test]
+    fn test_from_org() {
+        let entries = from_org(ORG, "org").unwrap();
+        let titles: Vec<(&str, &str)> = entries
+            .iter()
+            .map(|imported| {
+                (
+                    imported.entry.prefix.as_str(),
+                    imported.entry.title.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("org/Work", "[#A] write report"),
+                ("org/Work", "call bank"),
+                ("org/Work/Acme", "Kickoff"),
+                ("org/Home", "water plants"),
+            ],
+            titles
+        );
+
+        let report = &entries[0].entry;
+        assert_eq!(EntryType::Todo, report.entry_type);
+        assert_eq!(Some(at("2025-03-07 17:00")), report.deadline);
+        assert_eq!(Some(at("2025-03-03 00:00")), report.scheduled);
+        assert_eq!("Outline first.", report.body);
+        assert_eq!(vec!["urgent".to_string()], report.tags);
+        assert!(report.is_open());
+
+        assert_eq!(Some(at("2025-03-01 11:00")), entries[1].entry.closed);
+
+        let kickoff = &entries[2];
+        assert_eq!(Some("kickoff@example.com".into()), kickoff.uid);
+        assert_eq!(0, kickoff.entry.id);
+        assert_eq!(EntryType::Meeting, kickoff.entry.entry_type);
+        assert_eq!(Some(at("2025-03-04 11:30")), kickoff.entry.scheduled_end);
+        assert_eq!("   Bring slides.\n* not a heading", kickoff.entry.body);
+
+        assert_eq!(
+            Some("every 1w".parse().unwrap()),
+            entries[3].entry.recurrence
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = |content: &str| format!("{:#}", from_org(content, "").unwrap_err());
+        assert_eq!(
+            "line 1: invalid timestamp after SCHEDULED: in 'SCHEDULED: <2025-02-30 Sun>'",
+            error("* TODO a\nSCHEDULED: <2025-02-30 Sun>\n")
+        );
+        assert_eq!(
+            "line 1: missing :END: of the property drawer",
+            error("* a\n:PROPERTIES:\n:ID: 1\n")
+        );
+        assert_eq!(
+            "line 2: invalid recurrence 'every day'",
+            error("* x\n* TODO a\n:PROPERTIES:\n:RECURRENCE: every day\n:END:\n")
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let entry = |id, title: &str, prefix: &str| {
+            let mut entry = Entry::test_entry(id, title.into());
+            entry.prefix = prefix.into();
+            entry.opened = at("2025-02-20 09:15");
+            entry
+        };
+
+        let mut standup = entry(1, "standup", "work");
+        standup.entry_type = EntryType::Meeting;
+        standup.scheduled = Some(at("2025-03-03 09:00"));
+        standup.scheduled_end = Some(at("2025-03-03 09:15"));
+        standup.recurrence = Some("every 1d 10 times".parse().unwrap());
+        standup.tags = vec!["daily".into(), "team".into()];
+
+        let mut report = entry(2, "report", "work/acme");
+        report.body = "* intro\n,* escaped\n\n  indented".into();
+        report.deadline = Some(at("2025-03-07 00:00"));
+        report.scheduled = Some(at("2025-03-05 14:00"));
+        report.recurrence = Some("every 2w".parse().unwrap());
+
+        let mut taxes = entry(3, "taxes", "home");
+        taxes.closed = Some(at("2025-04-30 18:00"));
+        taxes.deadline = Some(at("2025-05-31 12:00"));
+
+        let mut offsite = entry(4, "offsite", "work/acme");
+        offsite.entry_type = EntryType::Meeting;
+        offsite.scheduled = Some(at("2025-03-10 22:00"));
+        offsite.scheduled_end = Some(at("2025-03-12 01:30"));
+
+        let mut retro = entry(5, "retro", "work");
+        retro.entry_type = EntryType::Meeting;
+        retro.closed = Some(at("2025-03-01 11:00"));
+        retro.scheduled_end = Some(at("2025-03-01 10:00"));
+
+        let groceries = entry(6, "groceries", "");
+
+        // titles and headings org would read with a keyword or tags
+        let mut review = entry(7, "DONE review", "TODO/DONE later");
+        review.entry_type = EntryType::Meeting;
+        review.scheduled = Some(at("2025-03-04 10:00"));
+        let mut bug = entry(8, "fix :bug:", "TODO/DONE later");
+        bug.tags = vec!["work".into()];
+        let todo = entry(9, ",TODO", "notes :x:");
+        let mut tags = entry(10, ":a:b:", "notes :x:");
+        tags.entry_type = EntryType::Meeting;
+        tags.scheduled = Some(at("2025-03-04 10:00"));
+
+        let entries = vec![
+            groceries, review, bug, taxes, todo, tags, standup, retro, report, offsite,
+        ];
+        let imported: Vec<Entry> = from_org(&to_org(&entries, "a1b2"), "")
+            .unwrap()
+            .into_iter()
+            .zip(&entries)
+            .map(|(imported, entry)| {
+                assert_eq!(Some(format!("{}@a1b2.koi", entry.id)), imported.uid);
+                Entry {
+                    id: entry.id,
+                    ..imported.entry
+                }
+            })
+            .collect();
+        assert_eq!(entries, imported);
+    }
+
+    #[test]
+    fn test_import() {
+        let mut store = Store::open_test();
+
+        let report = import(&mut store, ORG, "").unwrap();
+        assert_eq!(4, report.added.len());
+
+        let id = store.entry_id_by_uid("kickoff@example.com").unwrap();
+        let kickoff = store.query_by_id(id.unwrap()).unwrap();
+        assert_eq!("Work/Acme", kickoff.prefix);
+
+        let report = import(&mut store, &ORG.replace("Kickoff", "Kickoff call"), "").unwrap();
+        assert_eq!(vec![kickoff.id], report.updated);
+        assert_eq!(3, report.added.len());
+        assert_eq!("Kickoff call", store.query_by_id(kickoff.id).unwrap().title);
+        assert_eq!(7, store.query(Query::default()).unwrap().len());
+
+        // an exported file updates the entries it was exported from
+        let exported = export(&mut store, Query::default()).unwrap();
+        let report = import(&mut store, &exported, "").unwrap();
+        assert!(report.added.is_empty());
+        assert_eq!(7, report.updated.len());
+        assert_eq!(7, store.query(Query::default()).unwrap().len());
+    }
+}
@@ -0,0 +1,213 @@
+//! conversion between entries and org-mode headlines
+//!
+//! Every segment of a prefix becomes a heading, entries are the headlines below the heading of
+//! their last segment. Todos get the keyword `TODO` or `DONE`, meetings have no keyword.
+//! Closing and scheduled times and deadlines go to the planning line as timestamps like
+//! `<2025-03-01 Sat 14:00-15:00>`, the body becomes the section text. What org has no syntax
+//! for, like the id, the opening time or recurrences without a repeater, is kept in the
+//! property drawer. Titles and headings which org would read with a keyword or tags get a `,`
+//! in front of that word, like `,DONE review` or `fix ,:bug:`.
+//!
+//! Timestamps are wall clock times like the ones in org files written by Emacs.
+
+use crate::time::prelude::*;
+
+mod export;
+pub use export::{export, to_org};
+
+mod import;
+pub use import::{ImportReport, Imported, from_org, import};
+
+/// whether org reads `word` at the start of a headline as its keyword
+fn is_keyword(word: &str) -> bool {
+    matches!(word, "TODO" | "DONE")
+}
+
+/// whether org reads `word` at the end of a headline as its tags, like `:work:urgent:`
+fn is_tags(word: &str) -> bool {
+    word.len() > 1 && word.starts_with(':') && word.ends_with(':')
+}
+
+/// an org timestamp like `<2025-03-01 Sat 14:00-15:00 +1w>`, without a time it starts at
+/// midnight
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Timestamp {
+    start: DateTime,
+    /// the end of a time range like `14:00-15:00` or of a date range like `<…>--<…>`
+    end: Option<DateTime>,
+    /// the interval of a repeater like `+1w`, `++1w` or `.+1w`
+    repeater: Option<Duration>,
+}
+
+/// formats a single timestamp like `<2025-03-01 Sat 14:00>`, an active one in `<` and `>`, an
+/// inactive one in `[` and `]`
+///
+/// The time is left out at midnight unless there is an end time on the same day.
+fn format_timestamp(active: bool, timestamp: Timestamp) -> String {
+    let (open, close) = if active { ('<', '>') } else { ('[', ']') };
+    let Timestamp {
+        start,
+        end,
+        repeater,
+    } = timestamp;
+
+    let mut formatted = format!("{}{}", open, start.format("%Y-%m-%d %a"));
+    match end {
+        Some(end) if end.date() == start.date() => {
+            formatted.push_str(&format!(
+                " {}-{}",
+                start.format("%H:%M"),
+                end.format("%H:%M")
+            ));
+        }
+        None if start.time() == Time::MIN => (),
+        _ => formatted.push_str(&start.format(" %H:%M")),
+    }
+    if let Some(repeater) = repeater {
+        formatted.push_str(&format!(" +{}", repeater));
+    }
+    formatted.push(close);
+
+    if let Some(end) = end
+        && end.date() != start.date()
+    {
+        let end = Timestamp {
+            start: end,
+            end: None,
+            repeater: None,
+        };
+        formatted.push_str(&format!("--{}", format_timestamp(active, end)));
+    }
+
+    formatted
+}
+
+/// parses the timestamp at the start of `s`, see [`format_timestamp`]
+///
+/// Weekday names in any language and warning periods like `-2d` are skipped.
+///
+/// # Returns
+/// - the timestamp and the rest of `s` after it
+/// - `None` if `s` does not start with a timestamp
+fn parse_timestamp(s: &str) -> Option<(Timestamp, &str)> {
+    let close = match s.chars().next()? {
+        '<' => '>',
+        '[' => ']',
+        _ => return None,
+    };
+    let (inner, mut rest) = s[1..].split_once(close)?;
+
+    let mut words = inner.split_whitespace();
+    let date = Date::parse_iso(words.next()?).ok()?;
+
+    let mut timestamp = Timestamp {
+        start: date.with_time(Time::MIN),
+        end: None,
+        repeater: None,
+    };
+    for word in words {
+        if word.starts_with(|c: char| c.is_ascii_digit()) {
+            let (start, end) = match word.split_once('-') {
+                Some((start, end)) => (start, Some(end)),
+                None => (word, None),
+            };
+            timestamp.start = date.with_time(start.parse().ok()?);
+            timestamp.end = match end {
+                Some(end) => Some(date.with_time(end.parse().ok()?)),
+                None => None,
+            };
+        } else if let Some(interval) = word.strip_prefix(['+', '.']) {
+            let interval = interval.trim_start_matches('+');
+            timestamp.repeater = Some(interval.parse().ok()?);
+        } else if !word.starts_with('-') && !word.starts_with(char::is_alphabetic) {
+            return None;
+        }
+    }
+
+    if let Some(range) = rest.strip_prefix("--")
+        && let Some((end, after)) = parse_timestamp(range)
+    {
+        timestamp.end = Some(end.start);
+        rest = after;
+    }
+
+    Some((timestamp, rest))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    fn timestamp(start: &str, end: Option<&str>, repeater: Option<&str>) -> Timestamp {
+        Timestamp {
+            start: at(start),
+            end: end.map(at),
+            repeater: repeater.map(|repeater| repeater.parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        let format = |start, end, repeater| format_timestamp(true, timestamp(start, end, repeater));
+        assert_eq!(
+            "<2025-03-01 Sat 14:00-15:00>",
+            format("2025-03-01 14:00", Some("2025-03-01 15:00"), None)
+        );
+        assert_eq!("<2025-03-08 Sat>", format("2025-03-08 00:00", None, None));
+        assert_eq!(
+            "<2025-03-03 Mon 10:00 +1w>",
+            format("2025-03-03 10:00", None, Some("1w"))
+        );
+        assert_eq!(
+            "<2025-03-01 Sat 22:00>--<2025-03-02 Sun 02:00>",
+            format("2025-03-01 22:00", Some("2025-03-02 02:00"), None)
+        );
+        assert_eq!(
+            "[2025-03-05 Wed 18:30]",
+            format_timestamp(false, timestamp("2025-03-05 18:30", None, None))
+        );
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        fn parse(s: &str) -> Option<Timestamp> {
+            parse_timestamp(s).map(|(timestamp, _)| timestamp)
+        }
+
+        for (start, end, repeater) in [
+            ("2025-03-01 14:00", Some("2025-03-01 15:00"), None),
+            ("2025-03-08 00:00", None, None),
+            ("2025-03-08 00:00", Some("2025-03-08 01:00"), Some("1m")),
+            ("2025-03-01 22:00", Some("2025-03-02 02:00"), Some("2d")),
+        ] {
+            let timestamp = timestamp(start, end, repeater);
+            for active in [true, false] {
+                let formatted = format_timestamp(active, timestamp);
+                assert_eq!(Some(timestamp), parse(&formatted));
+            }
+        }
+
+        assert_eq!(
+            Some(timestamp("2025-03-01 09:05", None, Some("1d"))),
+            parse("<2025-03-01 Sa. 9:05 .+1d -2d>")
+        );
+        assert_eq!(
+            Some(timestamp("2025-03-01 00:00", None, Some("1y"))),
+            parse("<2025-03-01 ++1y>")
+        );
+        assert_eq!(
+            Some((timestamp("2025-03-01 00:00", None, None), " DEADLINE: <…>")),
+            parse_timestamp("[2025-03-01 Sat] DEADLINE: <…>")
+        );
+
+        assert_eq!(None, parse("2025-03-01"));
+        assert_eq!(None, parse("<2025-03-01 Sat"));
+        assert_eq!(None, parse("<2025-02-30 Sun>"));
+        assert_eq!(None, parse("<2025-03-01 Sat 25:00>"));
+        assert_eq!(None, parse("<2025-03-01 Sat +1x>"));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::entry::Entry;
+use crate::time::DateTime;
+
+use super::{Query, SearchHit};
+
+/// where a [`Store`](super::Store) keeps its entries
+///
+/// Date times passed to and returned from a backend are UTC, the store converts them from and
+/// to its zone. Methods which fail leave the entries unchanged.
+pub trait Backend: std::fmt::Debug {
+    /// returns the entry with the given id
+    ///
+    /// # Errors
+    /// - if there is no entry with the given id
+    fn get_entry_by_id(&self, id: u64) -> anyhow::Result<Entry>;
+
+    /// every entry ordered by id
+    fn get_all_entries(&self) -> anyhow::Result<Vec<Entry>>;
+
+    /// returns all entries matching `query` in the order and window given by `query`, like
+    /// [`Query::apply`]
+    fn query_entries(&self, query: &Query) -> anyhow::Result<Vec<Entry>>;
+
+    /// returns the entries matching both `text` and `query`, best matches first unless `query`
+    /// gives a sort order
+    ///
+    /// every whitespace separated word of `text` has to occur in either title or body. How
+    /// matches are ranked is up to the backend, so the order of entries matching about equally
+    /// well can differ between backends.
+    ///
+    /// # Errors
+    /// - if `text` contains no words
+    fn search_entries(&self, text: &str, query: &Query) -> anyhow::Result<Vec<SearchHit>>;
+
+    fn get_max_entry_id(&self) -> anyhow::Result<Option<u64>>;
+
+    /// # Errors
+    /// - if an entry with the id of `entry` exists
+    /// - if a tag of `entry` is invalid
+    fn add_entry(&self, entry: &Entry) -> anyhow::Result<()>;
+
+    /// overwrites every field of the entry with the same id as `entry`
+    ///
+    /// # Errors
+    /// - if there is no entry with the id of `entry`
+    fn update_entry(&self, entry: &Entry) -> anyhow::Result<()>;
+
+    /// marks the entry with the given id as closed at `at`
+    ///
+    /// # Errors
+    /// - if there is no entry with the given id
+    fn close_entry(&self, id: u64, at: DateTime) -> anyhow::Result<()>;
+
+    /// marks the entry with the given id as open again
+    ///
+    /// # Errors
+    /// - if there is no entry with the given id
+    fn reopen_entry(&self, id: u64) -> anyhow::Result<()>;
+
+    /// removes the entry with the given id together with its external ids
+    ///
+    /// # Errors
+    /// - if there is no entry with the given id
+    fn delete_entry(&self, id: u64) -> anyhow::Result<()>;
+
+    /// every tag with the number of entries it is attached to, ordered by name
+    fn get_tags(&self) -> anyhow::Result<Vec<(String, u64)>>;
+
+    /// # Errors
+    /// - if there is no tag `from` or `to` is invalid or exists already
+    fn rename_tag(&self, from: &str, to: &str) -> anyhow::Result<()>;
+
+    /// replaces the tag `from` with `into` on every entry
+    ///
+    /// # Errors
+    /// - if there is no tag `from` or `into` is invalid
+    fn merge_tags(&self, from: &str, into: &str) -> anyhow::Result<()>;
+
+    fn get_entry_id_by_uid(&self, uid: &str) -> anyhow::Result<Option<u64>>;
+
+    /// attaches an external id to an entry, taking it from the entry it was attached to
+    ///
+    /// # Errors
+    /// - if there is no entry with the given id
+    fn set_entry_uid(&self, id: u64, uid: &str) -> anyhow::Result<()>;
+
+    /// every external id with the id of its entry, ordered by entry id and external id
+    fn get_all_uids(&self) -> anyhow::Result<Vec<(String, u64)>>;
+
+    /// a random name given to the store when it is created, it tells the external ids of its
+    /// entries in exported files apart from the ones of other stores
+    fn store_uid(&self) -> anyhow::Result<String>;
+
+    /// the schema level the entries are stored at, see
+    /// [`Store::export_all`](super::Store::export_all)
+    fn migration_level(&self) -> anyhow::Result<usize>;
+
+    /// starts a group of changes which ends with [`Backend::commit`] or [`Backend::rollback`],
+    /// groups can be nested
+    fn begin(&self) -> anyhow::Result<()>;
+
+    /// keeps the changes of the innermost group
+    fn commit(&self) -> anyhow::Result<()>;
+
+    /// undoes the changes of the innermost group
+    fn rollback(&self) -> anyhow::Result<()>;
+}
+
+/// an id which is not used by any entry of `backend` yet, one more than the largest id
+pub fn new_entry_id(backend: &dyn Backend) -> anyhow::Result<u64> {
+    backend
+        .get_max_entry_id()
+        .map(|id| id.map_or(1, |id| id + 1))
+}
+
+/// runs `f` as a group of changes of `backend`, all of them are undone if it fails
+pub fn atomically<T, F>(backend: &dyn Backend, f: F) -> anyhow::Result<T>
+where
+    F: FnOnce() -> anyhow::Result<T>,
+{
+    backend.begin()?;
+    match f() {
+        Ok(value) => {
+            backend.commit()?;
+            Ok(value)
+        }
+        Err(err) => {
+            backend.rollback()?;
+            Err(err)
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use super::MigrationLike;
+
+pub struct Migration;
+
+impl MigrationLike for Migration {
+    fn up(&self, db: &rusqlite::Connection) -> anyhow::Result<()> {
+        db.execute_batch(
+            "
+            CREATE INDEX entries_prefix    ON entries (prefix);
+            CREATE INDEX entries_closed    ON entries (closed);
+            CREATE INDEX entries_scheduled ON entries (scheduled);
+            CREATE INDEX entries_deadline  ON entries (deadline);
+            ",
+        )?;
+        Ok(())
+    }
+}
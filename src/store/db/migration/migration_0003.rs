@@ -0,0 +1,38 @@
+use super::MigrationLike;
+
+pub struct Migration;
+
+impl MigrationLike for Migration {
+    fn up(&self, db: &rusqlite::Connection) -> anyhow::Result<()> {
+        db.execute_batch(
+            "
+            CREATE VIRTUAL TABLE entries_fts USING fts5 (
+                title,
+                body,
+                content = 'entries',
+                content_rowid = 'id'
+            );
+
+            CREATE TRIGGER entries_fts_insert AFTER INSERT ON entries BEGIN
+                INSERT INTO entries_fts (rowid, title, body)
+                    VALUES (new.id, new.title, new.body);
+            END;
+
+            CREATE TRIGGER entries_fts_delete AFTER DELETE ON entries BEGIN
+                INSERT INTO entries_fts (entries_fts, rowid, title, body)
+                    VALUES ('delete', old.id, old.title, old.body);
+            END;
+
+            CREATE TRIGGER entries_fts_update AFTER UPDATE OF id, title, body ON entries BEGIN
+                INSERT INTO entries_fts (entries_fts, rowid, title, body)
+                    VALUES ('delete', old.id, old.title, old.body);
+                INSERT INTO entries_fts (rowid, title, body)
+                    VALUES (new.id, new.title, new.body);
+            END;
+
+            INSERT INTO entries_fts (entries_fts) VALUES ('rebuild');
+            ",
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use super::MigrationLike;
+
+pub struct Migration;
+
+impl MigrationLike for Migration {
+    fn up(&self, db: &rusqlite::Connection) -> anyhow::Result<()> {
+        db.execute_batch(
+            "
+            CREATE TABLE tags (
+                id   INTEGER NOT NULL UNIQUE,
+                name TEXT NOT NULL UNIQUE,
+
+                PRIMARY KEY(id)
+            );
+
+            CREATE TABLE entry_tags (
+                entry_id INTEGER NOT NULL REFERENCES entries (id) ON DELETE CASCADE,
+                tag_id   INTEGER NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+
+                PRIMARY KEY(entry_id, tag_id)
+            );
+
+            CREATE INDEX entry_tags_tag ON entry_tags (tag_id);
+            ",
+        )?;
+        Ok(())
+    }
+}
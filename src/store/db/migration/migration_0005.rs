@@ -0,0 +1,10 @@
+use super::MigrationLike;
+
+pub struct Migration;
+
+impl MigrationLike for Migration {
+    fn up(&self, db: &rusqlite::Connection) -> anyhow::Result<()> {
+        db.execute("ALTER TABLE entries ADD COLUMN recurrence TEXT;", [])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+use super::MigrationLike;
+
+pub struct Migration;
+
+impl MigrationLike for Migration {
+    fn up(&self, db: &rusqlite::Connection) -> anyhow::Result<()> {
+        db.execute_batch(
+            "
+            CREATE TABLE entry_uids (
+                uid      TEXT NOT NULL UNIQUE,
+                entry_id INTEGER NOT NULL REFERENCES entries (id) ON DELETE CASCADE,
+
+                PRIMARY KEY(uid)
+            );
+
+            CREATE INDEX entry_uids_entry ON entry_uids (entry_id);
+            ",
+        )?;
+        Ok(())
+    }
+}
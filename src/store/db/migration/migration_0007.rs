@@ -0,0 +1,44 @@
+use rusqlite::Connection;
+
+use super::MigrationLike;
+use crate::store::db::Utc;
+use crate::time::{DateTime, Zone};
+
+const COLUMNS: [&str; 5] = ["opened", "closed", "scheduled", "scheduled_end", "deadline"];
+
+/// stores date times as UTC, before they were local wall clock times like `2025-03-01 14:30`
+pub struct Migration;
+
+/// rewrites every date time as UTC, taking the stored ones as wall clock times in `zone`
+///
+/// The `until` date of a recurrence stays as it is, it is a calendar day in the zone of the store
+/// like the occurrences it limits, see [`Store::close_entry`](crate::store::Store::close_entry).
+pub fn migrate(db: &Connection, zone: Zone) -> anyhow::Result<()> {
+    for column in COLUMNS {
+        let rows: Vec<(u64, String)> = db
+            .prepare(&format!(
+                "SELECT id, {0} FROM entries WHERE {0} IS NOT NULL;",
+                column
+            ))?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+
+        for (id, local) in rows {
+            let local = DateTime::parse_iso(&local)
+                .map_err(|_| anyhow::anyhow!("entry {}: invalid {} '{}'", id, column, local))?;
+            db.execute(
+                &format!("UPDATE entries SET {} = ? WHERE id = ?;", column),
+                rusqlite::params![Utc(zone.to_utc(local)), &id],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+impl MigrationLike for Migration {
+    fn up(&self, db: &Connection) -> anyhow::Result<()> {
+        // the zone of the system is the best guess for the zone the entries were written in
+        migrate(db, Zone::Local)
+    }
+}
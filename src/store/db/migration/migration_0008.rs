@@ -0,0 +1,17 @@
+use super::MigrationLike;
+
+/// gives the store a random name, see [`Backend::store_uid`](crate::store::Backend::store_uid)
+pub struct Migration;
+
+impl MigrationLike for Migration {
+    fn up(&self, db: &rusqlite::Connection) -> anyhow::Result<()> {
+        db.execute_batch(
+            "
+            CREATE TABLE store (uid TEXT NOT NULL);
+
+            INSERT INTO store VALUES (lower(hex(randomblob(8))));
+            ",
+        )?;
+        Ok(())
+    }
+}
@@ -1,16 +1,38 @@
 use rusqlite::Connection;
 
+use crate::store::atomically;
+
 mod base_migration;
 mod migration_0001;
+mod migration_0002;
+mod migration_0003;
+mod migration_0004;
+mod migration_0005;
+mod migration_0006;
+mod migration_0007;
+mod migration_0008;
 
 trait MigrationLike {
     fn up(&self, db: &Connection) -> anyhow::Result<()>;
 }
 
-const MIGRATIONS: [&dyn MigrationLike; 2] =
-    [&base_migration::Migration, &migration_0001::Migration];
+const MIGRATIONS: [&dyn MigrationLike; 9] = [
+    &base_migration::Migration,
+    &migration_0001::Migration,
+    &migration_0002::Migration,
+    &migration_0003::Migration,
+    &migration_0004::Migration,
+    &migration_0005::Migration,
+    &migration_0006::Migration,
+    &migration_0007::Migration,
+    &migration_0008::Migration,
+];
+
+/// the number of migrations a fully migrated database has run
+pub const LEVEL: usize = MIGRATIONS.len();
 
-fn get_level(db: &Connection) -> anyhow::Result<usize> {
+/// the number of migrations run on `db`, `0` for a new database
+pub fn get_level(db: &Connection) -> anyhow::Result<usize> {
     let exists: bool = db.query_one(
         "SELECT EXISTS (
             SELECT 1 FROM sqlite_master 
@@ -30,20 +52,22 @@ fn get_level(db: &Connection) -> anyhow::Result<usize> {
 }
 
 fn update_level(db: &Connection) -> anyhow::Result<()> {
-    db.execute("UPDATE migration SET level = ?;", [MIGRATIONS.len()])?;
+    db.execute("UPDATE migration SET level = ?;", [LEVEL])?;
     Ok(())
 }
 
+/// runs every migration `db` has not run yet
+///
+/// # Errors
+/// - if a migration fails, `db` is left unchanged at its old level then
 pub fn run(db: &Connection) -> anyhow::Result<()> {
     let level = get_level(db)?;
-    let migrations = &MIGRATIONS[level..];
-    for migration in migrations {
-        migration.up(db)?;
-    }
-
-    update_level(db)?;
-
-    Ok(())
+    atomically(db, || {
+        for migration in &MIGRATIONS[level..] {
+            migration.up(db)?;
+        }
+        update_level(db)
+    })
 }
 
 #[cfg(test)]
@@ -55,4 +79,131 @@ mod test {
         let db = Connection::open_in_memory().unwrap();
         run(&db).unwrap();
     }
+
+    #[test]
+    fn test_fts_indexes_existing_entries() {
+        let db = Connection::open_in_memory().unwrap();
+        for migration in &MIGRATIONS[..3] {
+            migration.up(&db).unwrap();
+        }
+        db.execute(
+            "INSERT INTO entries (id, title, body, prefix, entry_type, opened)
+                VALUES (1, 'budget', 'review the numbers', '', 'todo', '2025-03-01 12:00');",
+            [],
+        )
+        .unwrap();
+        db.execute("UPDATE migration SET level = 3;", []).unwrap();
+
+        run(&db).unwrap();
+
+        let id: u64 = db
+            .query_row(
+                "SELECT rowid FROM entries_fts WHERE entries_fts MATCH 'numbers';",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(1, id);
+    }
+
+    #[test]
+    fn test_datetimes_become_utc() {
+        let db = Connection::open_in_memory().unwrap();
+        for migration in &MIGRATIONS[..7] {
+            migration.up(&db).unwrap();
+        }
+        db.execute(
+            "INSERT INTO entries (id, title, body, prefix, entry_type, opened, deadline, recurrence)
+                VALUES (1, 'budget', '', '', 'todo', '2025-03-01 00:30', '2025-07-01 12:00',
+                    'every 1w until 2025-08-01');",
+            [],
+        )
+        .unwrap();
+
+        let berlin = "Europe/Berlin".parse().unwrap();
+        migration_0007::migrate(&db, berlin).unwrap();
+
+        let row: (String, Option<String>, String, String) = db
+            .query_row(
+                "SELECT opened, closed, deadline, recurrence FROM entries WHERE id = 1;",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            (
+                "2025-02-28T23:30Z".to_string(),
+                None,
+                "2025-07-01T10:00Z".to_string(),
+                "every 1w until 2025-08-01".to_string()
+            ),
+            row
+        );
+    }
+
+    #[test]
+    fn test_relative_datetimes_are_rejected() {
+        let db = Connection::open_in_memory().unwrap();
+        for migration in &MIGRATIONS[..7] {
+            migration.up(&db).unwrap();
+        }
+        db.execute(
+            "INSERT INTO entries (id, title, body, prefix, entry_type, opened)
+                VALUES (1, 'budget', '', '', 'todo', 'tomorrow');",
+            [],
+        )
+        .unwrap();
+
+        assert!(migration_0007::migrate(&db, crate::time::Zone::UTC).is_err());
+    }
+
+    #[test]
+    fn test_failed_run_changes_nothing() {
+        let db = Connection::open_in_memory().unwrap();
+        for migration in &MIGRATIONS[..6] {
+            migration.up(&db).unwrap();
+        }
+        db.execute("UPDATE migration SET level = 6;", []).unwrap();
+        db.execute(
+            "INSERT INTO entries (id, title, body, prefix, entry_type, opened)
+                VALUES (1, 'budget', '', '', 'todo', '2025-03-01 12:00'),
+                    (2, 'taxes', '', '', 'todo', 'tomorrow');",
+            [],
+        )
+        .unwrap();
+
+        let contents = |db: &Connection| -> (Vec<String>, Vec<String>, usize) {
+            let schema = db
+                .prepare("SELECT sql FROM sqlite_master WHERE sql IS NOT NULL ORDER BY name;")
+                .unwrap()
+                .query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            let opened = db
+                .prepare("SELECT opened FROM entries ORDER BY id;")
+                .unwrap()
+                .query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            (schema, opened, get_level(db).unwrap())
+        };
+        let before = contents(&db);
+        assert_eq!(6, before.2);
+
+        let error = run(&db).unwrap_err().to_string();
+        assert_eq!("entry 2: invalid opened 'tomorrow'", error);
+        assert_eq!(before, contents(&db));
+
+        // the same migrations run again once the row is fixed
+        assert_eq!(error, run(&db).unwrap_err().to_string());
+        db.execute(
+            "UPDATE entries SET opened = '2025-03-02 12:00' WHERE id = 2;",
+            [],
+        )
+        .unwrap();
+        run(&db).unwrap();
+        assert_eq!(LEVEL, get_level(&db).unwrap());
+    }
 }
@@ -1,25 +1,116 @@
 use std::path::Path;
 
-use rusqlite::{Connection, Row};
+use std::fmt::Display;
+use std::str::FromStr;
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, Value, ValueRef};
+use rusqlite::{Connection, Row, ToSql};
 
 use crate::entry::Entry;
+use crate::store::{Backend, Query, SearchHit, atomically};
+use crate::time::{Date, DateTime, ParseError, ParseErrorKind, Time};
 
 mod migration;
+mod query;
+mod tag;
+mod uid;
+
+pub use migration::{LEVEL as MIGRATION_LEVEL, get_level as get_migration_level};
+pub use tag::{ensure_valid_tag, get_tags, merge_tags, rename_tag};
+pub use uid::{get_all_uids, get_entry_id_by_uid, get_store_uid, set_entry_uid};
 
 #[cfg(test)]
 pub fn open_test() -> Connection {
     let db = Connection::open_in_memory().unwrap();
+    configure(&db).unwrap();
     migration::run(&db).unwrap();
     db
 }
 
 pub fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<Connection> {
     let db = Connection::open(path.as_ref())?;
+    configure(&db)?;
     migration::run(&db)?;
     Ok(db)
 }
 
-// TODO: test this
+/// per connection settings which are not persisted in the database file
+fn configure(db: &Connection) -> anyhow::Result<()> {
+    // prefix filters use `LIKE`, which has to match case sensitively like `str::starts_with`
+    db.pragma_update(None, "case_sensitive_like", true)?;
+    db.pragma_update(None, "foreign_keys", true)?;
+    Ok(())
+}
+
+/// a date time in UTC as it is stored, like `2025-03-01T13:30Z`
+///
+/// The functions of this module take and return date times in UTC. Unlike the format of
+/// [`DateTime`] the stored text can not be mistaken for a local time, and it still sorts like
+/// the instants it describes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Utc(pub DateTime);
+
+impl Display for Utc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}T{}Z", self.0.date(), self.0.time())
+    }
+}
+
+impl FromStr for Utc {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (date, time) = s
+            .strip_suffix('Z')
+            .and_then(|s| s.split_once('T'))
+            .ok_or_else(|| {
+                let expected = "a date time in UTC like 2025-03-01T13:30Z";
+                ParseError::new(s, s, ParseErrorKind::Expected(expected))
+            })?;
+
+        let date = Date::parse_iso(date).map_err(|err| err.within(s, date))?;
+        let time = Time::parse_iso(time).map_err(|err| err.within(s, time))?;
+        Ok(Self(DateTime::new(date, time)))
+    }
+}
+
+serde_with_str!(Utc);
+
+impl FromSql for Utc {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        <String as FromSql>::column_result(value)?
+            .parse()
+            .map_err(|err: ParseError| FromSqlError::Other(Box::new(err)))
+    }
+}
+
+impl ToSql for Utc {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Owned(Value::Text(self.to_string())))
+    }
+}
+
+/// the columns read by [`entry_from_row`]
+const ENTRY_COLUMNS: &str = "
+    entries.id,
+    title,
+    body,
+    prefix,
+    entry_type,
+    opened,
+    closed,
+    scheduled,
+    scheduled_end,
+    deadline,
+    recurrence,
+    (
+        SELECT group_concat(tags.name, ' ' ORDER BY tags.name)
+        FROM entry_tags JOIN tags ON tags.id = entry_tags.tag_id
+        WHERE entry_tags.entry_id = entries.id
+    )
+";
+
+/// the largest id of any entry, `None` if there are no entries
 pub fn get_max_entry_id(db: &Connection) -> anyhow::Result<Option<u64>> {
     match db.query_one(
         "SELECT id FROM entries ORDER BY id DESC LIMIT 1;",
@@ -32,11 +123,15 @@ pub fn get_max_entry_id(db: &Connection) -> anyhow::Result<Option<u64>> {
     }
 }
 
-// TODO: test this
-// TODO: doc this
+/// inserts `entry` together with its tags
+///
+/// # Errors
+/// - if an entry with the id of `entry` exists
+/// - if a tag of `entry` is invalid
 pub fn add_entry(db: &Connection, entry: &Entry) -> anyhow::Result<()> {
-    db.execute(
-        "
+    atomically(db, || {
+        db.execute(
+            "
         INSERT INTO entries (
             id,
 
@@ -52,27 +147,120 @@ pub fn add_entry(db: &Connection, entry: &Entry) -> anyhow::Result<()> {
             scheduled,
             scheduled_end,
 
-            deadline
+            deadline,
+
+            recurrence
         ) VALUES (
-            ?, ?, ?, ?, ?, ?, ?, ?, ?, ?
+            ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?
         );
         ",
-        rusqlite::params![
-            &entry.id,
-            &entry.title,
-            &entry.body,
-            &entry.prefix,
-            &entry.entry_type,
-            &entry.opened,
-            &entry.closed,
-            &entry.scheduled,
-            &entry.scheduled_end,
-            &entry.deadline,
-        ],
-    )?;
+            rusqlite::params![
+                &entry.id,
+                &entry.title,
+                &entry.body,
+                &entry.prefix,
+                &entry.entry_type,
+                &Utc(entry.opened),
+                &entry.closed.map(Utc),
+                &entry.scheduled.map(Utc),
+                &entry.scheduled_end.map(Utc),
+                &entry.deadline.map(Utc),
+                &entry.recurrence,
+            ],
+        )?;
+        tag::set_entry_tags(db, entry.id, &entry.tags)
+    })
+}
+
+fn ensure_changed(changed: usize, id: u64) -> anyhow::Result<()> {
+    if changed == 0 {
+        anyhow::bail!("no entry with id {}", id);
+    }
     Ok(())
 }
 
+/// overwrites every field of the entry with the same id as `entry`
+///
+/// # Errors
+/// - if there is no entry with the id of `entry`
+pub fn update_entry(db: &Connection, entry: &Entry) -> anyhow::Result<()> {
+    atomically(db, || {
+        let changed = db.execute(
+            "
+        UPDATE entries SET
+            title = ?,
+            body = ?,
+            prefix = ?,
+
+            entry_type = ?,
+
+            opened = ?,
+            closed = ?,
+
+            scheduled = ?,
+            scheduled_end = ?,
+
+            deadline = ?,
+
+            recurrence = ?
+        WHERE id = ?;
+        ",
+            rusqlite::params![
+                &entry.title,
+                &entry.body,
+                &entry.prefix,
+                &entry.entry_type,
+                &Utc(entry.opened),
+                &entry.closed.map(Utc),
+                &entry.scheduled.map(Utc),
+                &entry.scheduled_end.map(Utc),
+                &entry.deadline.map(Utc),
+                &entry.recurrence,
+                &entry.id,
+            ],
+        )?;
+        ensure_changed(changed, entry.id)?;
+        tag::set_entry_tags(db, entry.id, &entry.tags)
+    })
+}
+
+/// marks the entry with the given id as closed at `at`
+///
+/// # Errors
+/// - if there is no entry with the given id
+pub fn close_entry(db: &Connection, id: u64, at: DateTime) -> anyhow::Result<()> {
+    let changed = db.execute(
+        "UPDATE entries SET closed = ? WHERE id = ?;",
+        rusqlite::params![&Utc(at), &id],
+    )?;
+    ensure_changed(changed, id)
+}
+
+/// marks the entry with the given id as open again
+///
+/// # Errors
+/// - if there is no entry with the given id
+pub fn reopen_entry(db: &Connection, id: u64) -> anyhow::Result<()> {
+    let changed = db.execute("UPDATE entries SET closed = NULL WHERE id = ?;", [id])?;
+    ensure_changed(changed, id)
+}
+
+/// removes the entry with the given id
+///
+/// # Errors
+/// - if there is no entry with the given id
+pub fn delete_entry(db: &Connection, id: u64) -> anyhow::Result<()> {
+    atomically(db, || {
+        let changed = db.execute("DELETE FROM entries WHERE id = ?;", [id])?;
+        ensure_changed(changed, id)?;
+        tag::remove_unused_tags(db)
+    })
+}
+
+fn get_utc(row: &Row, index: usize) -> rusqlite::Result<Option<DateTime>> {
+    Ok(row.get::<_, Option<Utc>>(index)?.map(|at| at.0))
+}
+
 fn entry_from_row(row: &Row) -> rusqlite::Result<Entry> {
     Ok(Entry {
         id: row.get(0)?,
@@ -80,72 +268,234 @@ fn entry_from_row(row: &Row) -> rusqlite::Result<Entry> {
         body: row.get(2)?,
         prefix: row.get(3)?,
         entry_type: row.get(4)?,
-        opened: row.get(5)?,
-        closed: row.get(6)?,
-        scheduled: row.get(7)?,
-        scheduled_end: row.get(8)?,
-        deadline: row.get(9)?,
+        opened: row.get::<_, Utc>(5)?.0,
+        closed: get_utc(row, 6)?,
+        scheduled: get_utc(row, 7)?,
+        scheduled_end: get_utc(row, 8)?,
+        deadline: get_utc(row, 9)?,
+        recurrence: row.get(10)?,
+        tags: row
+            .get::<_, Option<String>>(11)?
+            .map(|tags| tags.split(' ').map(|tag| tag.into()).collect())
+            .unwrap_or_default(),
     })
 }
 
-// TODO: test this
-// TODO: doc this
+/// every entry ordered by id
 pub fn get_all_entries(db: &Connection) -> anyhow::Result<Vec<Entry>> {
-    db.prepare(
-        "
-        SELECT
-            id,
-            title,
-            body,
-            prefix,
-            entry_type,
-            opened,
-            closed,
-            scheduled,
-            scheduled_end,
-            deadline
-        FROM entries;
-        ",
-    )?
+    db.prepare(&format!(
+        "SELECT {} FROM entries ORDER BY id;",
+        ENTRY_COLUMNS
+    ))?
     .query([])?
     .and_then(entry_from_row)
     .collect::<Result<_, _>>()
     .map_err(|err| err.into())
 }
 
-// TODO: test this
-// TODO: doc this
-pub fn get_entry_by_id(db: &Connection, id: u64) -> anyhow::Result<Entry> {
-    db.query_row(
+/// returns all entries matching `query` in the order and window given by `query`
+pub fn query_entries(db: &Connection, query: &Query) -> anyhow::Result<Vec<Entry>> {
+    let filter = query::Filter::new(query);
+    let sql = format!(
         "
-        SELECT
-            id,
-            title,
-            body,
-            prefix,
-            entry_type,
-            opened,
-            closed,
-            scheduled,
-            scheduled_end,
-            deadline
-        FROM entries WHERE id = ? LIMIT 1;
+        SELECT {} FROM entries {} {} {};
+        ",
+        ENTRY_COLUMNS,
+        filter.where_clause(),
+        query::order_clause(query),
+        query::limit_clause(query),
+    );
+
+    db.prepare(&sql)?
+        .query(rusqlite::params_from_iter(filter.params()))?
+        .and_then(entry_from_row)
+        .collect::<Result<_, _>>()
+        .map_err(|err| err.into())
+}
+
+/// returns the entries matching both `text` and `query`, best matches first unless `query`
+/// gives a sort order
+///
+/// every whitespace separated word of `text` has to occur in either title or body.
+///
+/// # Errors
+/// - if `text` contains no words
+pub fn search_entries(
+    db: &Connection,
+    text: &str,
+    query: &Query,
+) -> anyhow::Result<Vec<SearchHit>> {
+    let Some(expression) = query::match_expression(text) else {
+        anyhow::bail!("nothing to search for");
+    };
+
+    let filter = query::Filter::new(query);
+    let order = match query.sort {
+        Some(_) => query::order_clause(query),
+        None => "ORDER BY hit_rank, id".into(),
+    };
+
+    let sql = format!(
+        "
+        SELECT {}, hit_snippet FROM entries JOIN (
+            SELECT
+                rowid AS hit_id,
+                rank AS hit_rank,
+                snippet(entries_fts, 1, '[', ']', '...', 12) AS hit_snippet
+            FROM entries_fts WHERE entries_fts MATCH ?
+        ) ON hit_id = entries.id {} {} {};
         ",
+        ENTRY_COLUMNS,
+        filter.where_clause(),
+        order,
+        query::limit_clause(query),
+    );
+
+    let params = std::iter::once(rusqlite::types::Value::Text(expression))
+        .chain(filter.params().iter().cloned());
+
+    db.prepare(&sql)?
+        .query(rusqlite::params_from_iter(params))?
+        .and_then(|row| -> rusqlite::Result<SearchHit> {
+            Ok(SearchHit {
+                entry: entry_from_row(row)?,
+                snippet: row.get(12)?,
+            })
+        })
+        .collect::<Result<_, _>>()
+        .map_err(|err| err.into())
+}
+
+/// returns the entry with the given id
+///
+/// # Errors
+/// - if there is no entry with the given id
+pub fn get_entry_by_id(db: &Connection, id: u64) -> anyhow::Result<Entry> {
+    db.query_row(
+        &format!(
+            "SELECT {} FROM entries WHERE id = ? LIMIT 1;",
+            ENTRY_COLUMNS
+        ),
         [id],
         entry_from_row,
     )
-    .map_err(|err| err.into())
+    .map_err(|err| match err {
+        rusqlite::Error::QueryReturnedNoRows => anyhow::anyhow!("no entry with id {}", id),
+        err => err.into(),
+    })
+}
+
+impl Backend for Connection {
+    fn get_entry_by_id(&self, id: u64) -> anyhow::Result<Entry> {
+        get_entry_by_id(self, id)
+    }
+
+    fn get_all_entries(&self) -> anyhow::Result<Vec<Entry>> {
+        get_all_entries(self)
+    }
+
+    fn query_entries(&self, query: &Query) -> anyhow::Result<Vec<Entry>> {
+        query_entries(self, query)
+    }
+
+    fn search_entries(&self, text: &str, query: &Query) -> anyhow::Result<Vec<SearchHit>> {
+        search_entries(self, text, query)
+    }
+
+    fn get_max_entry_id(&self) -> anyhow::Result<Option<u64>> {
+        get_max_entry_id(self)
+    }
+
+    fn add_entry(&self, entry: &Entry) -> anyhow::Result<()> {
+        add_entry(self, entry)
+    }
+
+    fn update_entry(&self, entry: &Entry) -> anyhow::Result<()> {
+        update_entry(self, entry)
+    }
+
+    fn close_entry(&self, id: u64, at: DateTime) -> anyhow::Result<()> {
+        close_entry(self, id, at)
+    }
+
+    fn reopen_entry(&self, id: u64) -> anyhow::Result<()> {
+        reopen_entry(self, id)
+    }
+
+    fn delete_entry(&self, id: u64) -> anyhow::Result<()> {
+        delete_entry(self, id)
+    }
+
+    fn get_tags(&self) -> anyhow::Result<Vec<(String, u64)>> {
+        get_tags(self)
+    }
+
+    fn rename_tag(&self, from: &str, to: &str) -> anyhow::Result<()> {
+        rename_tag(self, from, to)
+    }
+
+    fn merge_tags(&self, from: &str, into: &str) -> anyhow::Result<()> {
+        merge_tags(self, from, into)
+    }
+
+    fn get_entry_id_by_uid(&self, uid: &str) -> anyhow::Result<Option<u64>> {
+        get_entry_id_by_uid(self, uid)
+    }
+
+    fn set_entry_uid(&self, id: u64, uid: &str) -> anyhow::Result<()> {
+        set_entry_uid(self, id, uid)
+    }
+
+    fn get_all_uids(&self) -> anyhow::Result<Vec<(String, u64)>> {
+        get_all_uids(self)
+    }
+
+    fn store_uid(&self) -> anyhow::Result<String> {
+        get_store_uid(self)
+    }
+
+    fn migration_level(&self) -> anyhow::Result<usize> {
+        get_migration_level(self)
+    }
+
+    // groups are savepoints, unlike transactions they can be nested
+    fn begin(&self) -> anyhow::Result<()> {
+        Ok(self.execute_batch("SAVEPOINT atomically;")?)
+    }
+
+    fn commit(&self) -> anyhow::Result<()> {
+        Ok(self.execute_batch("RELEASE atomically;")?)
+    }
+
+    fn rollback(&self) -> anyhow::Result<()> {
+        Ok(self.execute_batch("ROLLBACK TO atomically; RELEASE atomically;")?)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::entry::{EntryState, EntryType};
+    use crate::store::{SortKey, SortOrder};
+    use crate::time::prelude::*;
 
     #[test]
     fn test_open() {
         open_test();
     }
 
+    #[test]
+    fn test_utc() {
+        let at: DateTime = "2025-03-01 13:30".parse().unwrap();
+        assert_eq!("2025-03-01T13:30Z", Utc(at).to_string());
+        assert_eq!(Ok(Utc(at)), "2025-03-01T13:30Z".parse());
+
+        assert!("2025-03-01 13:30".parse::<Utc>().is_err());
+        assert!("today".parse::<Utc>().is_err());
+        let err = "2025-03-01T13:60Z".parse::<Utc>().unwrap_err();
+        assert_eq!(14, err.position);
+    }
+
     #[test]
     fn test_add_get() {
         let db = open_test();
@@ -192,5 +542,384 @@ mod test {
 
         assert_eq!(expected, got);
     }
-}
 
+    #[test]
+    fn test_update() {
+        let db = open_test();
+
+        let mut entry = Entry::test_entry(1, "title a".into());
+        add_entry(&db, &entry).unwrap();
+
+        entry.title = "title b".into();
+        entry.body = "body".into();
+        entry.prefix = "work".into();
+        entry.deadline = Some(now());
+        update_entry(&db, &entry).unwrap();
+
+        let got = get_entry_by_id(&db, 1).unwrap();
+        assert_eq!(entry, got);
+    }
+
+    #[test]
+    fn test_close_reopen() {
+        let db = open_test();
+
+        add_entry(&db, &Entry::test_entry(1, "title".into())).unwrap();
+
+        let at = now();
+        close_entry(&db, 1, at).unwrap();
+
+        let got = get_entry_by_id(&db, 1).unwrap();
+        assert_eq!(Some(at), got.closed);
+        assert!(got.is_closed());
+
+        reopen_entry(&db, 1).unwrap();
+
+        let got = get_entry_by_id(&db, 1).unwrap();
+        assert_eq!(None, got.closed);
+        assert!(got.is_open());
+    }
+
+    #[test]
+    fn test_delete() {
+        let db = open_test();
+
+        add_entry(&db, &Entry::test_entry(1, "title a".into())).unwrap();
+        add_entry(&db, &Entry::test_entry(2, "title b".into())).unwrap();
+
+        delete_entry(&db, 1).unwrap();
+
+        assert!(get_entry_by_id(&db, 1).is_err());
+        assert_eq!(1, get_all_entries(&db).unwrap().len());
+    }
+
+    #[test]
+    fn test_missing_id() {
+        let db = open_test();
+
+        add_entry(&db, &Entry::test_entry(1, "title".into())).unwrap();
+
+        assert!(update_entry(&db, &Entry::test_entry(2, "title".into())).is_err());
+        assert!(close_entry(&db, 2, now()).is_err());
+        assert!(reopen_entry(&db, 2).is_err());
+        assert!(delete_entry(&db, 2).is_err());
+
+        assert_eq!(1, get_all_entries(&db).unwrap().len());
+    }
+
+    #[test]
+    fn test_query_entries() {
+        let db = open_test();
+
+        let at = |s: &str| s.parse::<DateTime>().unwrap();
+        let entry = |id: u64, prefix: &str| {
+            let mut entry = Entry::test_entry(id, format!("title {}", id));
+            entry.prefix = prefix.into();
+            entry
+        };
+
+        let mut entries = vec![
+            entry(1, "work/acme"),
+            entry(2, "work/acme/budget"),
+            entry(3, "Work/acme"),
+            entry(4, "work_acme"),
+            entry(5, "100%"),
+            entry(6, "1000"),
+            entry(7, ""),
+        ];
+        entries[0].scheduled = Some(at("2025-03-01 14:00"));
+        entries[1].deadline = Some(at("2025-03-02 00:00"));
+        entries[1].closed = Some(at("2025-02-28 12:00"));
+        entries[2].scheduled = Some(at("2025-02-28 23:59"));
+        entries[2].deadline = Some(at("2025-03-08 00:00"));
+        entries[3].deadline = Some(at("2025-03-07 23:59"));
+        entries[4].closed = Some(at("2025-03-01 00:00"));
+
+        for entry in &entries {
+            add_entry(&db, entry).unwrap();
+        }
+
+        let range = Some(DateTimeRange::new(
+            at("2025-03-01 00:00"),
+            at("2025-03-08 00:00"),
+        ));
+        let queries = [
+            Query::default(),
+            Query {
+                state: Some(EntryState::Open),
+                ..Default::default()
+            },
+            Query {
+                state: Some(EntryState::Closed),
+                ..Default::default()
+            },
+            Query {
+                prefix: Some("work/acme".into()),
+                ..Default::default()
+            },
+            Query {
+                prefix: Some("work_".into()),
+                ..Default::default()
+            },
+            Query {
+                prefix: Some("100%".into()),
+                ..Default::default()
+            },
+            Query {
+                scheduled_or_deadline: range,
+                ..Default::default()
+            },
+            Query {
+                state: Some(EntryState::Open),
+                prefix: Some("work".into()),
+                scheduled_or_deadline: range,
+                ..Default::default()
+            },
+        ];
+
+        for query in queries {
+            let got = query_entries(&db, &query).unwrap();
+            assert_eq!(query.apply(entries.clone()), got, "{:?}", query);
+        }
+    }
+
+    #[test]
+    fn test_query_entries_extended() {
+        let db = open_test();
+
+        let at = |s: &str| s.parse::<DateTime>().unwrap();
+        let entry = |id: u64, title: &str, body: &str| {
+            let mut entry = Entry::test_entry(id, title.into());
+            entry.body = body.into();
+            entry.opened = at("2025-01-01 12:00").add_days(id as i32);
+            entry
+        };
+
+        let mut entries = vec![
+            entry(1, "Budget review", ""),
+            entry(2, "call bob", "about the BUDGET"),
+            entry(3, "weekly", "100% attendance"),
+            entry(4, "taxes", ""),
+            entry(5, "dentist", ""),
+        ];
+        entries[2].entry_type = EntryType::Meeting;
+        entries[2].scheduled = Some(at("2025-03-03 10:00"));
+        entries[0].deadline = Some(at("2025-03-10 00:00"));
+        entries[3].deadline = Some(at("2025-03-05 00:00"));
+        entries[4].deadline = Some(at("2025-03-07 00:00"));
+        entries[1].closed = Some(at("2025-02-20 18:00"));
+        entries[3].closed = Some(at("2025-02-27 09:00"));
+        entries[0].tags = vec!["urgent".into(), "work".into()];
+        entries[1].tags = vec!["@waiting".into(), "work".into()];
+        entries[4].tags = vec!["urgent".into()];
+
+        for entry in &entries {
+            add_entry(&db, entry).unwrap();
+        }
+
+        let last_week = Some(DateTimeRange::week(
+            at("2025-02-24").date(),
+            Weekday::Monday,
+        ));
+        let queries = [
+            Query {
+                entry_type: Some(EntryType::Meeting),
+                ..Default::default()
+            },
+            Query {
+                entry_type: Some(EntryType::Todo),
+                ..Default::default()
+            },
+            Query {
+                text: vec!["budget".into()],
+                ..Default::default()
+            },
+            Query {
+                text: vec!["100%".into()],
+                ..Default::default()
+            },
+            Query {
+                closed: last_week,
+                ..Default::default()
+            },
+            Query {
+                opened: Some(DateTimeRange::new(
+                    at("2025-01-03 00:00"),
+                    at("2025-01-05 00:00"),
+                )),
+                ..Default::default()
+            },
+            Query {
+                has_deadline: Some(false),
+                ..Default::default()
+            },
+            Query {
+                tags: vec!["work"],
+                ..Default::default()
+            },
+            Query {
+                tags: vec!["work", "urgent"],
+                ..Default::default()
+            },
+            Query {
+                exclude_tags: vec!["urgent"],
+                ..Default::default()
+            },
+            Query {
+                tags: vec!["work"],
+                exclude_tags: vec!["@waiting"],
+                ..Default::default()
+            },
+            Query {
+                deadline: Some(DateTimeRange::before(at("2025-03-07 00:00"))),
+                ..Default::default()
+            },
+            Query {
+                scheduled: Some(DateTimeRange::starting_at(at("2025-03-03 00:00"))),
+                ..Default::default()
+            },
+            Query {
+                has_scheduled: Some(true),
+                has_deadline: Some(false),
+                ..Default::default()
+            },
+            Query {
+                sort: Some((SortKey::Deadline, SortOrder::Ascending)),
+                ..Default::default()
+            },
+            Query {
+                sort: Some((SortKey::Deadline, SortOrder::Descending)),
+                ..Default::default()
+            },
+            Query {
+                sort: Some((SortKey::Closed, SortOrder::Descending)),
+                ..Default::default()
+            },
+            Query {
+                sort: Some((SortKey::Opened, SortOrder::Descending)),
+                limit: Some(2),
+                ..Default::default()
+            },
+            Query {
+                state: Some(EntryState::Open),
+                sort: Some((SortKey::Deadline, SortOrder::Ascending)),
+                limit: Some(2),
+                offset: Some(1),
+                ..Default::default()
+            },
+            Query {
+                offset: Some(3),
+                ..Default::default()
+            },
+        ];
+
+        for query in queries {
+            let got = query_entries(&db, &query).unwrap();
+            assert_eq!(query.apply(entries.clone()), got, "{:?}", query);
+        }
+
+        let top = query_entries(
+            &db,
+            &Query {
+                state: Some(EntryState::Open),
+                sort: Some((SortKey::Deadline, SortOrder::Ascending)),
+                limit: Some(2),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let ids: Vec<u64> = top.iter().map(|entry| entry.id).collect();
+        assert_eq!(vec![5, 1], ids);
+    }
+
+    #[test]
+    fn test_query_uses_index() {
+        let db = open_test();
+
+        let plan: String = db
+            .query_row(
+                "EXPLAIN QUERY PLAN SELECT id FROM entries WHERE prefix LIKE 'work%' ESCAPE '\\';",
+                [],
+                |row| row.get(3),
+            )
+            .unwrap();
+        assert!(plan.contains("entries_prefix"), "{}", plan);
+    }
+
+    #[test]
+    fn test_search() {
+        let db = open_test();
+
+        let entry = |id: u64, title: &str, body: &str| {
+            let mut entry = Entry::test_entry(id, title.into());
+            entry.body = body.into();
+            entry
+        };
+
+        add_entry(&db, &entry(1, "groceries", "milk, eggs and bread")).unwrap();
+        add_entry(
+            &db,
+            &entry(2, "budget", "review the budget with the budget team"),
+        )
+        .unwrap();
+        add_entry(&db, &entry(3, "call bob", "ask about the budget")).unwrap();
+        add_entry(&db, &entry(4, "taxes", "")).unwrap();
+
+        let ids = |hits: &[SearchHit]| hits.iter().map(|hit| hit.entry.id).collect::<Vec<_>>();
+
+        let hits = search_entries(&db, "budget", &Query::default()).unwrap();
+        assert_eq!(vec![2, 3], ids(&hits));
+        assert!(hits[1].snippet.contains("[budget]"), "{}", hits[1].snippet);
+
+        let hits = search_entries(&db, "Budget BOB", &Query::default()).unwrap();
+        assert_eq!(vec![3], ids(&hits));
+
+        let hits = search_entries(&db, "\"bread\" -milk: OR", &Query::default()).unwrap();
+        assert!(hits.is_empty());
+
+        close_entry(&db, 3, now()).unwrap();
+        let query = Query {
+            state: Some(EntryState::Open),
+            ..Default::default()
+        };
+        let hits = search_entries(&db, "budget", &query).unwrap();
+        assert_eq!(vec![2], ids(&hits));
+
+        assert!(search_entries(&db, "  ", &Query::default()).is_err());
+    }
+
+    #[test]
+    fn test_search_follows_changes() {
+        let db = open_test();
+
+        let mut entry = Entry::test_entry(1, "groceries".into());
+        add_entry(&db, &entry).unwrap();
+        assert_eq!(
+            1,
+            search_entries(&db, "groceries", &Query::default())
+                .unwrap()
+                .len()
+        );
+
+        entry.title = "hardware store".into();
+        update_entry(&db, &entry).unwrap();
+        assert!(
+            search_entries(&db, "groceries", &Query::default())
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            1,
+            search_entries(&db, "hardware", &Query::default())
+                .unwrap()
+                .len()
+        );
+
+        delete_entry(&db, 1).unwrap();
+        assert!(
+            search_entries(&db, "hardware", &Query::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
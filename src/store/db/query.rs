@@ -0,0 +1,244 @@
+use rusqlite::types::Value;
+
+use crate::entry::EntryState;
+use crate::store::{Query, SortKey, SortOrder};
+use crate::time::DateTimeRange;
+
+use super::Utc;
+
+/// the `WHERE` clause of a [`Query`] together with the values of its parameters
+#[derive(Debug, Default)]
+pub struct Filter {
+    clauses: Vec<String>,
+    params: Vec<Value>,
+}
+
+/// selects a row if the current entry has the tag given as parameter
+const TAGGED: &str = "
+    SELECT 1 FROM entry_tags JOIN tags ON tags.id = entry_tags.tag_id
+    WHERE entry_tags.entry_id = entries.id AND tags.name = ?
+";
+
+/// escapes `\`, `%` and `_` for use in a `LIKE ... ESCAPE '\'` pattern
+fn escape_like(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl Filter {
+    pub fn new(query: &Query) -> Self {
+        let mut filter = Self::default();
+
+        match query.state {
+            Some(EntryState::Open) => filter.push("closed IS NULL", []),
+            Some(EntryState::Closed) => filter.push("closed IS NOT NULL", []),
+            None => (),
+        }
+
+        if let Some(entry_type) = query.entry_type {
+            filter.push("entry_type = ?", [Value::Text(entry_type.to_string())]);
+        }
+
+        if let Some(prefix) = &query.prefix {
+            let pattern = format!("{}%", escape_like(prefix));
+            filter.push("prefix LIKE ? ESCAPE '\\'", [Value::Text(pattern)]);
+        }
+
+        for tag in &query.tags {
+            filter.push(
+                &format!("EXISTS ({})", TAGGED),
+                [Value::Text(tag.to_string())],
+            );
+        }
+
+        for tag in &query.exclude_tags {
+            filter.push(
+                &format!("NOT EXISTS ({})", TAGGED),
+                [Value::Text(tag.to_string())],
+            );
+        }
+
+        for text in &query.text {
+            let text = Value::Text(text.to_ascii_lowercase());
+            filter.push(
+                "(instr(lower(title), ?) > 0 OR instr(lower(body), ?) > 0)",
+                [text.clone(), text],
+            );
+        }
+
+        if let Some(range) = query.opened {
+            filter.push_range("opened", range);
+        }
+
+        if let Some(range) = query.closed {
+            filter.push_range("closed", range);
+        }
+
+        if let Some(range) = query.scheduled {
+            filter.push_range("scheduled", range);
+        }
+
+        if let Some(range) = query.deadline {
+            filter.push_range("deadline", range);
+        }
+
+        if let Some(range) = query.scheduled_or_deadline {
+            let begin = Value::Text(Utc(range.begin).to_string());
+            let end = Value::Text(Utc(range.end).to_string());
+            filter.push(
+                "((scheduled >= ? AND scheduled < ?) OR (deadline >= ? AND deadline < ?))",
+                [begin.clone(), end.clone(), begin, end],
+            );
+        }
+
+        match query.has_scheduled {
+            Some(true) => filter.push("scheduled IS NOT NULL", []),
+            Some(false) => filter.push("scheduled IS NULL", []),
+            None => (),
+        }
+
+        match query.recurring {
+            Some(true) => filter.push("recurrence IS NOT NULL", []),
+            Some(false) => filter.push("recurrence IS NULL", []),
+            None => (),
+        }
+
+        match query.has_deadline {
+            Some(true) => filter.push("deadline IS NOT NULL", []),
+            Some(false) => filter.push("deadline IS NULL", []),
+            None => (),
+        }
+
+        filter
+    }
+
+    fn push_range(&mut self, column: &str, range: DateTimeRange) {
+        self.push(
+            &format!("({0} >= ? AND {0} < ?)", column),
+            [
+                Value::Text(Utc(range.begin).to_string()),
+                Value::Text(Utc(range.end).to_string()),
+            ],
+        );
+    }
+
+    fn push<P: IntoIterator<Item = Value>>(&mut self, clause: &str, params: P) {
+        self.clauses.push(clause.into());
+        self.params.extend(params);
+    }
+
+    /// the `WHERE` clause, empty if the query does not filter anything
+    pub fn where_clause(&self) -> String {
+        if self.clauses.is_empty() {
+            return "".into();
+        }
+        format!("WHERE {}", self.clauses.join(" AND "))
+    }
+
+    pub fn params(&self) -> &[Value] {
+        &self.params
+    }
+}
+
+/// the `ORDER BY` clause of a [`Query`], entries without a value for the sort key come last
+pub fn order_clause(query: &Query) -> String {
+    let (key, order) = query.sort.unwrap_or((SortKey::Id, SortOrder::Ascending));
+    let order = match order {
+        SortOrder::Ascending => "ASC",
+        SortOrder::Descending => "DESC",
+    };
+
+    let column = match key {
+        SortKey::Id => return format!("ORDER BY id {}", order),
+        SortKey::Opened => "opened",
+        SortKey::Closed => "closed",
+        SortKey::Scheduled => "scheduled",
+        SortKey::Deadline => "deadline",
+    };
+
+    format!("ORDER BY {0} IS NULL, {0} {1}, id {1}", column, order)
+}
+
+/// the `LIMIT` and `OFFSET` clause of a [`Query`]
+///
+/// SQLite takes 64 bit signed integers, larger values are saturated, which makes no difference
+/// for any table that fits on a disk.
+pub fn limit_clause(query: &Query) -> String {
+    let saturate = |value: u64| i64::try_from(value).unwrap_or(i64::MAX);
+    match (query.limit.map(saturate), query.offset.map(saturate)) {
+        (None, None) => "".into(),
+        (Some(limit), None) => format!("LIMIT {}", limit),
+        (limit, Some(offset)) => format!("LIMIT {} OFFSET {}", limit.unwrap_or(-1), offset),
+    }
+}
+
+/// turns free text into an fts5 match expression where every word has to occur
+///
+/// Words are quoted, so fts5 operators in `text` are matched literally.
+pub fn match_expression(text: &str) -> Option<String> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+
+    if words.is_empty() {
+        return None;
+    }
+
+    Some(words.join(" "))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("work/acme"), "work/acme");
+        assert_eq!(escape_like("100%"), "100\\%");
+        assert_eq!(escape_like("a_b"), "a\\_b");
+        assert_eq!(escape_like("a\\b"), "a\\\\b");
+    }
+
+    #[test]
+    fn test_empty_query() {
+        let filter = Filter::new(&Query::default());
+        assert_eq!(filter.where_clause(), "");
+        assert!(filter.params().is_empty());
+    }
+
+    #[test]
+    fn test_limit_clause() {
+        let clause = |limit, offset| {
+            limit_clause(&Query {
+                limit,
+                offset,
+                ..Default::default()
+            })
+        };
+        assert_eq!("", clause(None, None));
+        assert_eq!("LIMIT 5", clause(Some(5), None));
+        assert_eq!("LIMIT -1 OFFSET 3", clause(None, Some(3)));
+        assert_eq!(
+            format!("LIMIT {} OFFSET {}", i64::MAX, i64::MAX),
+            clause(Some(u64::MAX), Some(i64::MAX as u64 + 1))
+        );
+    }
+
+    #[test]
+    fn test_match_expression() {
+        assert_eq!(None, match_expression(""));
+        assert_eq!(None, match_expression("  \n"));
+        assert_eq!(Some("\"budget\"".into()), match_expression("budget"));
+        assert_eq!(
+            Some("\"a\" \"-b\" \"\"\"c\"\"\"".into()),
+            match_expression(" a -b \"c\" ")
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::entry::is_valid_tag;
+
+use crate::store::atomically;
+
+/// # Errors
+/// - if `tag` is empty or contains whitespace, see [`is_valid_tag`]
+pub fn ensure_valid_tag(tag: &str) -> anyhow::Result<()> {
+    if !is_valid_tag(tag) {
+        anyhow::bail!(
+            "invalid tag '{}', tags must not be empty or contain whitespace",
+            tag
+        );
+    }
+    Ok(())
+}
+
+fn get_tag_id(db: &Connection, tag: &str) -> anyhow::Result<Option<u64>> {
+    db.query_row("SELECT id FROM tags WHERE name = ?;", [tag], |row| {
+        row.get(0)
+    })
+    .optional()
+    .map_err(|err| err.into())
+}
+
+/// removes tags which are not attached to any entry anymore
+pub fn remove_unused_tags(db: &Connection) -> anyhow::Result<()> {
+    db.execute(
+        "DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM entry_tags);",
+        [],
+    )?;
+    Ok(())
+}
+
+/// replaces the tags of the entry with the given id with `tags`, sorted and without duplicates
+///
+/// # Errors
+/// - if one of the tags is not valid, see [`is_valid_tag`]
+pub fn set_entry_tags(db: &Connection, id: u64, tags: &[String]) -> anyhow::Result<()> {
+    for tag in tags {
+        ensure_valid_tag(tag)?;
+    }
+    let mut tags = tags.to_vec();
+    tags.sort();
+    tags.dedup();
+
+    db.execute("DELETE FROM entry_tags WHERE entry_id = ?;", [id])?;
+
+    for tag in &tags {
+        db.execute("INSERT OR IGNORE INTO tags (name) VALUES (?);", [tag])?;
+        db.execute(
+            "
+            INSERT OR IGNORE INTO entry_tags (entry_id, tag_id)
+                SELECT ?, id FROM tags WHERE name = ?;
+            ",
+            rusqlite::params![&id, tag],
+        )?;
+    }
+
+    remove_unused_tags(db)
+}
+
+/// returns every tag with the number of entries it is attached to, ordered by name
+pub fn get_tags(db: &Connection) -> anyhow::Result<Vec<(String, u64)>> {
+    db.prepare(
+        "
+        SELECT name, count(entry_id) FROM tags
+            JOIN entry_tags ON entry_tags.tag_id = tags.id
+            GROUP BY tags.id
+            ORDER BY name;
+        ",
+    )?
+    .query([])?
+    .mapped(|row| Ok((row.get(0)?, row.get(1)?)))
+    .collect::<Result<_, _>>()
+    .map_err(|err| err.into())
+}
+
+/// renames the tag `from` to `to` on every entry
+///
+/// # Errors
+/// - if there is no tag `from`
+/// - if the tag `to` already exists, use [`merge_tags`] instead
+/// - if `to` is not a valid tag
+pub fn rename_tag(db: &Connection, from: &str, to: &str) -> anyhow::Result<()> {
+    ensure_valid_tag(to)?;
+
+    atomically(db, || {
+        if get_tag_id(db, to)?.is_some() {
+            anyhow::bail!("tag '{}' already exists", to);
+        }
+
+        let changed = db.execute("UPDATE tags SET name = ? WHERE name = ?;", [to, from])?;
+        if changed == 0 {
+            anyhow::bail!("no tag '{}'", from);
+        }
+
+        Ok(())
+    })
+}
+
+/// replaces the tag `from` with the tag `into` on every entry, `into` is created if necessary
+///
+/// # Errors
+/// - if there is no tag `from`
+/// - if `into` is not a valid tag
+pub fn merge_tags(db: &Connection, from: &str, into: &str) -> anyhow::Result<()> {
+    ensure_valid_tag(into)?;
+
+    atomically(db, || {
+        let Some(from_id) = get_tag_id(db, from)? else {
+            anyhow::bail!("no tag '{}'", from);
+        };
+
+        if from == into {
+            return Ok(());
+        }
+
+        db.execute("INSERT OR IGNORE INTO tags (name) VALUES (?);", [into])?;
+        db.execute(
+            "
+            INSERT OR IGNORE INTO entry_tags (entry_id, tag_id)
+                SELECT entry_id, (SELECT id FROM tags WHERE name = ?)
+                FROM entry_tags WHERE tag_id = ?;
+            ",
+            rusqlite::params![into, &from_id],
+        )?;
+        db.execute("DELETE FROM entry_tags WHERE tag_id = ?;", [from_id])?;
+        db.execute("DELETE FROM tags WHERE id = ?;", [from_id])?;
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::entry::Entry;
+    use crate::store::db::{add_entry, delete_entry, get_entry_by_id, open_test, update_entry};
+
+    fn tagged(id: u64, tags: &[&str]) -> Entry {
+        let mut entry = Entry::test_entry(id, format!("title {}", id));
+        entry.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        entry
+    }
+
+    fn tags(list: &[(&str, u64)]) -> Vec<(String, u64)> {
+        list.iter().map(|(tag, n)| (tag.to_string(), *n)).collect()
+    }
+
+    #[test]
+    fn test_add_get() {
+        let db = open_test();
+
+        let entry = tagged(1, &["@waiting", "client-x", "urgent"]);
+        add_entry(&db, &entry).unwrap();
+
+        assert_eq!(entry, get_entry_by_id(&db, 1).unwrap());
+    }
+
+    #[test]
+    fn test_normalize() {
+        let db = open_test();
+
+        add_entry(&db, &tagged(1, &["urgent", "client-x", "urgent"])).unwrap();
+        assert_eq!(
+            vec!["client-x", "urgent"],
+            get_entry_by_id(&db, 1).unwrap().tags
+        );
+
+        update_entry(&db, &tagged(1, &["b", "a", "b", "a"])).unwrap();
+        assert_eq!(vec!["a", "b"], get_entry_by_id(&db, 1).unwrap().tags);
+        assert_eq!(tags(&[("a", 1), ("b", 1)]), get_tags(&db).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_tag() {
+        let db = open_test();
+
+        assert!(add_entry(&db, &tagged(1, &["two words"])).is_err());
+        assert!(add_entry(&db, &tagged(1, &[""])).is_err());
+
+        // the failed insert must not leave the entry behind
+        assert!(get_entry_by_id(&db, 1).is_err());
+    }
+
+    #[test]
+    fn test_get_tags() {
+        let db = open_test();
+
+        add_entry(&db, &tagged(1, &["a", "b"])).unwrap();
+        add_entry(&db, &tagged(2, &["b"])).unwrap();
+        let mut entry = tagged(3, &["b", "c"]);
+        add_entry(&db, &entry).unwrap();
+
+        assert_eq!(
+            tags(&[("a", 1), ("b", 3), ("c", 1)]),
+            get_tags(&db).unwrap()
+        );
+
+        entry.tags = vec!["d".into()];
+        update_entry(&db, &entry).unwrap();
+        assert_eq!(
+            tags(&[("a", 1), ("b", 2), ("d", 1)]),
+            get_tags(&db).unwrap()
+        );
+
+        delete_entry(&db, 1).unwrap();
+        assert_eq!(tags(&[("b", 1), ("d", 1)]), get_tags(&db).unwrap());
+    }
+
+    #[test]
+    fn test_rename() {
+        let db = open_test();
+
+        add_entry(&db, &tagged(1, &["a", "b"])).unwrap();
+        add_entry(&db, &tagged(2, &["b"])).unwrap();
+
+        rename_tag(&db, "b", "c").unwrap();
+        assert_eq!(tags(&[("a", 1), ("c", 2)]), get_tags(&db).unwrap());
+        assert_eq!(vec!["a", "c"], get_entry_by_id(&db, 1).unwrap().tags);
+
+        assert!(rename_tag(&db, "a", "c").is_err());
+        assert!(rename_tag(&db, "x", "y").is_err());
+        assert!(rename_tag(&db, "a", "with space").is_err());
+    }
+
+    #[test]
+    fn test_merge() {
+        let db = open_test();
+
+        add_entry(&db, &tagged(1, &["a", "b"])).unwrap();
+        add_entry(&db, &tagged(2, &["a"])).unwrap();
+        add_entry(&db, &tagged(3, &["c"])).unwrap();
+
+        merge_tags(&db, "a", "b").unwrap();
+        assert_eq!(tags(&[("b", 2), ("c", 1)]), get_tags(&db).unwrap());
+        assert_eq!(vec!["b"], get_entry_by_id(&db, 1).unwrap().tags);
+        assert_eq!(vec!["b"], get_entry_by_id(&db, 2).unwrap().tags);
+
+        merge_tags(&db, "c", "d").unwrap();
+        assert_eq!(tags(&[("b", 2), ("d", 1)]), get_tags(&db).unwrap());
+
+        assert!(merge_tags(&db, "x", "b").is_err());
+    }
+
+    #[test]
+    fn test_merge_atomic() {
+        let db = open_test();
+
+        add_entry(&db, &tagged(1, &["a"])).unwrap();
+        add_entry(&db, &tagged(2, &["b"])).unwrap();
+
+        // fails the last statement of the merge
+        db.execute_batch(
+            "
+            CREATE TEMP TRIGGER keep_tags BEFORE DELETE ON tags
+                BEGIN SELECT RAISE(ABORT, 'tags are kept'); END;
+            ",
+        )
+        .unwrap();
+
+        assert!(merge_tags(&db, "a", "b").is_err());
+        assert_eq!(tags(&[("a", 1), ("b", 1)]), get_tags(&db).unwrap());
+        assert_eq!(vec!["a"], get_entry_by_id(&db, 1).unwrap().tags);
+    }
+}
@@ -0,0 +1,88 @@
+use rusqlite::{Connection, OptionalExtension};
+
+/// the id of the entry an external `uid` is attached to
+pub fn get_entry_id_by_uid(db: &Connection, uid: &str) -> anyhow::Result<Option<u64>> {
+    db.query_row(
+        "SELECT entry_id FROM entry_uids WHERE uid = ?;",
+        [uid],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|err| err.into())
+}
+
+/// the random name of the store, see [`Backend::store_uid`](crate::store::Backend::store_uid)
+pub fn get_store_uid(db: &Connection) -> anyhow::Result<String> {
+    db.query_row("SELECT uid FROM store LIMIT 1;", [], |row| row.get(0))
+        .map_err(|err| err.into())
+}
+
+/// every external id with the id of its entry, ordered by entry and external id
+pub fn get_all_uids(db: &Connection) -> anyhow::Result<Vec<(String, u64)>> {
+    db.prepare("SELECT uid, entry_id FROM entry_uids ORDER BY entry_id, uid;")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()
+        .map_err(|err| err.into())
+}
+
+/// attaches an external `uid` to the entry with the given id, replacing its previous entry
+///
+/// # Errors
+/// - if there is no entry with the given id
+pub fn set_entry_uid(db: &Connection, id: u64, uid: &str) -> anyhow::Result<()> {
+    db.execute(
+        "INSERT OR REPLACE INTO entry_uids (uid, entry_id) VALUES (?, ?);",
+        rusqlite::params![uid, &id],
+    )
+    .map_err(|err| match err.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::ConstraintViolation) => {
+            anyhow::anyhow!("no entry with id {}", id)
+        }
+        _ => err.into(),
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::entry::Entry;
+    use crate::store::db::{add_entry, delete_entry, open_test};
+
+    #[test]
+    fn test_set_get() {
+        let db = open_test();
+        add_entry(&db, &Entry::test_entry(1, "a".into())).unwrap();
+        add_entry(&db, &Entry::test_entry(2, "b".into())).unwrap();
+
+        assert_eq!(None, get_entry_id_by_uid(&db, "x@example.com").unwrap());
+
+        set_entry_uid(&db, 1, "x@example.com").unwrap();
+        assert_eq!(Some(1), get_entry_id_by_uid(&db, "x@example.com").unwrap());
+
+        set_entry_uid(&db, 2, "x@example.com").unwrap();
+        assert_eq!(Some(2), get_entry_id_by_uid(&db, "x@example.com").unwrap());
+
+        assert!(set_entry_uid(&db, 3, "y@example.com").is_err());
+
+        set_entry_uid(&db, 1, "a@example.com").unwrap();
+        assert_eq!(
+            vec![
+                ("a@example.com".to_string(), 1),
+                ("x@example.com".to_string(), 2)
+            ],
+            get_all_uids(&db).unwrap()
+        );
+
+        delete_entry(&db, 2).unwrap();
+        assert_eq!(None, get_entry_id_by_uid(&db, "x@example.com").unwrap());
+    }
+
+    #[test]
+    fn test_store_uid() {
+        let uid = get_store_uid(&open_test()).unwrap();
+        assert_eq!(16, uid.len());
+        assert!(uid.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(uid, get_store_uid(&open_test()).unwrap());
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::entry::{Entry, EntryType, Recurrence, is_valid_tag};
+
+use super::db::Utc;
+
+/// the version of the document written by [`Store::export_all`](super::Store::export_all)
+pub const DUMP_VERSION: u32 = 1;
+
+/// how [`Store::import_all`](super::Store::import_all) treats the ids of imported entries
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ImportMode {
+    /// the store must not contain any entries, every entry keeps its id
+    Empty,
+    /// an entry replaces the entry with the same id, other entries are added with their id
+    MergeById,
+    /// an entry whose id is taken is added with a new id, other entries keep their id
+    Renumber,
+}
+
+/// the ids changed by [`Store::import_all`](super::Store::import_all) or
+/// [`Store::import_entries`](super::Store::import_entries)
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ImportReport {
+    /// ids of the new entries, including renumbered ones
+    pub added: Vec<u64>,
+    /// ids of the replaced entries
+    pub updated: Vec<u64>,
+    /// the id in the document and the new id of each renumbered entry
+    pub renumbered: Vec<(u64, u64)>,
+}
+
+/// every entry of a store, date times are UTC
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub(super) struct Dump {
+    pub version: u32,
+    /// the number of migrations run on the exported database
+    pub migration: usize,
+    pub entries: Vec<DumpEntry>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(from = "DumpEntryJson", into = "DumpEntryJson")]
+pub(super) struct DumpEntry {
+    pub entry: Entry,
+
+    /// external ids attached to the entry
+    pub uids: Vec<String>,
+}
+
+/// a [`DumpEntry`] as it is written, date times are marked as UTC like `2025-03-01T13:30Z` so they
+/// can not be mistaken for local ones, and only read in that form
+#[derive(Serialize, Deserialize)]
+struct DumpEntryJson {
+    id: u64,
+
+    title: String,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    prefix: String,
+    entry_type: EntryType,
+
+    opened: Utc,
+    closed: Option<Utc>,
+
+    scheduled: Option<Utc>,
+    scheduled_end: Option<Utc>,
+
+    deadline: Option<Utc>,
+
+    recurrence: Option<Recurrence>,
+
+    #[serde(default)]
+    tags: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    uids: Vec<String>,
+}
+
+impl From<DumpEntry> for DumpEntryJson {
+    fn from(DumpEntry { entry, uids }: DumpEntry) -> Self {
+        Self {
+            id: entry.id,
+            title: entry.title,
+            body: entry.body,
+            prefix: entry.prefix,
+            entry_type: entry.entry_type,
+            opened: Utc(entry.opened),
+            closed: entry.closed.map(Utc),
+            scheduled: entry.scheduled.map(Utc),
+            scheduled_end: entry.scheduled_end.map(Utc),
+            deadline: entry.deadline.map(Utc),
+            recurrence: entry.recurrence,
+            tags: entry.tags,
+            uids,
+        }
+    }
+}
+
+impl From<DumpEntryJson> for DumpEntry {
+    fn from(json: DumpEntryJson) -> Self {
+        let utc = |at: Utc| at.0;
+        Self {
+            entry: Entry {
+                id: json.id,
+                title: json.title,
+                body: json.body,
+                prefix: json.prefix,
+                entry_type: json.entry_type,
+                opened: json.opened.0,
+                closed: json.closed.map(utc),
+                scheduled: json.scheduled.map(utc),
+                scheduled_end: json.scheduled_end.map(utc),
+                deadline: json.deadline.map(utc),
+                recurrence: json.recurrence,
+                tags: json.tags,
+            },
+            uids: json.uids,
+        }
+    }
+}
+
+impl Dump {
+    /// parses and checks a document written by [`Dump::to_json`]
+    ///
+    /// # Errors
+    /// - if the document is not valid JSON or misses fields
+    /// - if its version is unknown or its schema is newer than `level`
+    /// - if ids or external ids are duplicated or a tag is invalid
+    pub fn from_json(json: &str, level: usize) -> anyhow::Result<Self> {
+        let mut dump: Dump = serde_json::from_str(json)
+            .map_err(|err| anyhow::anyhow!("invalid export document: {}", err))?;
+
+        if dump.version != DUMP_VERSION {
+            anyhow::bail!(
+                "unsupported export version {}, expected {}",
+                dump.version,
+                DUMP_VERSION
+            );
+        }
+
+        if dump.migration > level {
+            anyhow::bail!(
+                "the export is from a newer schema at level {}, this store is at level {}",
+                dump.migration,
+                level
+            );
+        }
+
+        let mut ids = HashSet::new();
+        let mut uids = HashSet::new();
+        for DumpEntry { entry, .. } in &mut dump.entries {
+            if !ids.insert(entry.id) {
+                anyhow::bail!("duplicate entry id {}", entry.id);
+            }
+
+            if let Some(tag) = entry.tags.iter().find(|tag| !is_valid_tag(tag)) {
+                anyhow::bail!("invalid tag '{}' on entry {}", tag, entry.id);
+            }
+            entry.tags.sort();
+            entry.tags.dedup();
+        }
+
+        for DumpEntry {
+            entry,
+            uids: entry_uids,
+        } in &dump.entries
+        {
+            for uid in entry_uids {
+                if !uids.insert(uid.as_str()) {
+                    anyhow::bail!("duplicate external id '{}' on entry {}", uid, entry.id);
+                }
+            }
+        }
+
+        Ok(dump)
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)? + "\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dump(entries: Vec<DumpEntry>) -> Dump {
+        Dump {
+            version: DUMP_VERSION,
+            migration: 3,
+            entries,
+        }
+    }
+
+    fn dump_entry(id: u64, uids: &[&str]) -> DumpEntry {
+        let mut entry = Entry::test_entry(id, format!("entry {}", id));
+        entry.opened = "2025-03-01 12:00".parse().unwrap();
+        DumpEntry {
+            entry,
+            uids: uids.iter().map(|uid| uid.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut entry = dump_entry(2, &["x@example.com"]);
+        entry.entry.tags = vec!["home".into(), "urgent".into()];
+        entry.entry.deadline = Some("2025-03-08 00:00".parse().unwrap());
+        let dump = dump(vec![dump_entry(1, &[]), entry]);
+
+        let json = dump.to_json().unwrap();
+        assert!(json.contains("\"uids\": [\n        \"x@example.com\""));
+        assert!(json.contains("\"opened\": \"2025-03-01T12:00Z\""));
+        assert!(json.contains("\"deadline\": \"2025-03-08T00:00Z\""));
+        assert_eq!(dump, Dump::from_json(&json, 3).unwrap());
+    }
+
+    #[test]
+    fn test_strict_date_times() {
+        let json = dump(vec![dump_entry(1, &[])]).to_json().unwrap();
+        assert!(Dump::from_json(&json, 3).is_ok());
+        for opened in [
+            "2025-03-01 12:00",
+            "2025-03-01T12:00",
+            "2025-03-01T12:00+01:00",
+            "tomorrow 12:00",
+            "today",
+        ] {
+            let json = json.replace("2025-03-01T12:00Z", opened);
+            assert!(Dump::from_json(&json, 3).is_err(), "{}", opened);
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = |dump: &Dump, level| {
+            Dump::from_json(&dump.to_json().unwrap(), level)
+                .unwrap_err()
+                .to_string()
+        };
+
+        let valid = dump(vec![dump_entry(1, &["a"]), dump_entry(2, &["b"])]);
+        assert!(Dump::from_json(&valid.to_json().unwrap(), 3).is_ok());
+        assert_eq!(
+            "the export is from a newer schema at level 3, this store is at level 2",
+            error(&valid, 2)
+        );
+
+        let mut dump = valid.clone();
+        dump.version = 2;
+        assert_eq!("unsupported export version 2, expected 1", error(&dump, 3));
+
+        let mut dump = valid.clone();
+        dump.entries[1].entry.id = 1;
+        assert_eq!("duplicate entry id 1", error(&dump, 3));
+
+        let mut dump = valid.clone();
+        dump.entries[1].uids = vec!["a".into()];
+        assert_eq!("duplicate external id 'a' on entry 2", error(&dump, 3));
+
+        let mut dump = valid;
+        dump.entries[0].entry.tags = vec!["two words".into()];
+        assert_eq!("invalid tag 'two words' on entry 1", error(&dump, 3));
+
+        assert!(Dump::from_json("{\"version\": 1}", 3).is_err());
+        assert!(Dump::from_json("[]", 3).is_err());
+    }
+}
@@ -0,0 +1,872 @@
+//! a directory with one Markdown file per entry
+//!
+//! The file of an entry is named after its id, like `7.md`. Its fields are kept in front-matter
+//! between two `---` lines, followed by the title as a heading and the body:
+//!
+//! ```text
+//! ---
+//! type: todo
+//! prefix: work/acme
+//! opened: 2025-03-01T08:00Z
+//! deadline: 2025-03-07T16:00Z
+//! recurrence: every 1w
+//! tags: report urgent
+//! uids: 7@koi
+//! ---
+//! # write report
+//!
+//! outline first
+//! ```
+//!
+//! Date times are UTC, written like in the database, fields without a value are left out. Files
+//! with Windows line endings are read as well but written back with `\n`.
+//!
+//! Search ranks entries by how often the searched words occur in them, ties by id. The database
+//! ranks with bm25, which also prefers short entries and rare words, so best matches can be
+//! ordered differently there.
+//! Other files in the directory are ignored, so it can live inside a git repository next to a
+//! README. The random name of the store is kept in `.koi-uid`.
+
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use crate::entry::{Entry, EntryType};
+use crate::time::DateTime;
+
+use super::db::{MIGRATION_LEVEL, Utc, ensure_valid_tag};
+use super::{Backend, Query, SearchHit, atomically};
+
+/// the file in which the random name of the store is kept, see [`Backend::store_uid`]
+const STORE_UID_FILE: &str = ".koi-uid";
+
+/// the number of words around the first match in a [`SearchHit::snippet`]
+const SNIPPET_WORDS: usize = 12;
+
+/// an entry with the external ids attached to it, as kept in one file
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Record {
+    entry: Entry,
+    uids: Vec<String>,
+}
+
+/// the content of the file of `record`
+fn to_markdown(record: &Record) -> String {
+    let entry = &record.entry;
+    let mut lines = vec!["---".to_string(), format!("type: {}", entry.entry_type)];
+
+    if !entry.prefix.is_empty() {
+        lines.push(format!("prefix: {}", entry.prefix));
+    }
+    lines.push(format!("opened: {}", Utc(entry.opened)));
+    let times = [
+        ("closed", entry.closed),
+        ("scheduled", entry.scheduled),
+        ("scheduled_end", entry.scheduled_end),
+        ("deadline", entry.deadline),
+    ];
+    for (key, at) in times {
+        if let Some(at) = at {
+            lines.push(format!("{}: {}", key, Utc(at)));
+        }
+    }
+    if let Some(recurrence) = entry.recurrence {
+        lines.push(format!("recurrence: {}", recurrence));
+    }
+    if !entry.tags.is_empty() {
+        lines.push(format!("tags: {}", entry.tags.join(" ")));
+    }
+    if !record.uids.is_empty() {
+        lines.push(format!("uids: {}", record.uids.join(" ")));
+    }
+    lines.push("---".into());
+    lines.push(format!("# {}", entry.title));
+
+    let mut content: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+    if !entry.body.is_empty() {
+        content.push_str(&format!("\n{}\n", entry.body));
+    }
+    content
+}
+
+/// reads the file of the entry with the given id, see [`to_markdown`]
+///
+/// Lines may also end with `\r\n` if the file starts with such a line, they end with `\n` in the
+/// body of the entry then.
+///
+/// # Errors
+/// - if the front-matter or the title is missing
+/// - if a field is unknown or has an invalid value
+fn from_markdown(id: u64, content: &str) -> anyhow::Result<Record> {
+    let crlf = content.starts_with("---\r\n");
+    let mut lines = content.split_inclusive('\n').map(|line| {
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        if crlf {
+            line.strip_suffix('\r').unwrap_or(line)
+        } else {
+            line
+        }
+    });
+    if lines.next() != Some("---") {
+        anyhow::bail!("missing front-matter");
+    }
+    let mut front_matter = vec![];
+    loop {
+        match lines.next() {
+            Some("---") => break,
+            Some(line) => front_matter.push(line),
+            None => anyhow::bail!("front-matter is not closed by '---'"),
+        }
+    }
+
+    let title = lines.next().unwrap_or_default();
+    let Some(title) = title.strip_prefix("# ").or((title == "#").then_some("")) else {
+        anyhow::bail!("missing title, it has to follow the front-matter like '# title'");
+    };
+    let mut body: Vec<&str> = lines.collect();
+    if body.first() == Some(&"") {
+        body.remove(0);
+    }
+    let body = body.join("\n");
+
+    let mut entry_type = EntryType::Todo;
+    let mut prefix = "";
+    let mut opened = None;
+    let mut closed = None;
+    let mut scheduled = None;
+    let mut scheduled_end = None;
+    let mut deadline = None;
+    let mut recurrence = None;
+    let mut tags: Vec<String> = vec![];
+    let mut uids: Vec<String> = vec![];
+
+    for line in front_matter {
+        let (key, raw) = line
+            .split_once(':')
+            .map(|(key, raw)| (key.trim(), raw))
+            .with_context(|| format!("invalid front-matter line '{}'", line))?;
+        let value = raw.trim();
+        let utc = || {
+            value
+                .parse::<Utc>()
+                .map(|at| Some(at.0))
+                .with_context(|| format!("invalid {} '{}'", key, value))
+        };
+        let words = || value.split_whitespace().map(String::from).collect();
+
+        match key {
+            "type" => {
+                entry_type = value
+                    .parse()
+                    .with_context(|| format!("invalid type '{}'", value))?;
+            }
+            // written verbatim after `prefix: `, so it keeps surrounding whitespace
+            "prefix" => prefix = raw.strip_prefix(' ').unwrap_or(raw),
+            "opened" => opened = utc()?,
+            "closed" => closed = utc()?,
+            "scheduled" => scheduled = utc()?,
+            "scheduled_end" => scheduled_end = utc()?,
+            "deadline" => deadline = utc()?,
+            "recurrence" => {
+                let parsed = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("invalid recurrence '{}'", value))?;
+                recurrence = Some(parsed);
+            }
+            "tags" => tags = words(),
+            "uids" => uids = words(),
+            key => anyhow::bail!("unknown front-matter key '{}'", key),
+        }
+    }
+    tags.sort();
+    tags.dedup();
+    uids.sort();
+
+    let entry = Entry {
+        id,
+        title: title.into(),
+        body,
+        prefix: prefix.into(),
+        entry_type,
+        opened: opened.context("missing opened")?,
+        closed,
+        scheduled,
+        scheduled_end,
+        deadline,
+        recurrence,
+        tags,
+    };
+    Ok(Record { entry, uids })
+}
+
+/// the lowercase latin letter `c` without its diacritic, like the full text index of the
+/// database folds `é` to `e`
+fn remove_diacritic(c: char) -> char {
+    match c {
+        'à'..='å' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+        'ď' => 'd',
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+        'ĥ' => 'h',
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' => 'i',
+        'ĵ' => 'j',
+        'ķ' => 'k',
+        'ĺ' | 'ļ' | 'ľ' => 'l',
+        'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+        'ò'..='ö' | 'ō' | 'ŏ' | 'ő' => 'o',
+        'ŕ' | 'ŗ' | 'ř' => 'r',
+        'ś' | 'ŝ' | 'ş' | 'š' => 's',
+        'ţ' | 'ť' => 't',
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+        'ŵ' => 'w',
+        'ý' | 'ÿ' | 'ŷ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        c => c,
+    }
+}
+
+/// the lowercase words of `text` without diacritics with their byte ranges, split like the
+/// full text index of the database splits them
+fn tokens(text: &str) -> Vec<(usize, usize, String)> {
+    let mut tokens = vec![];
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(i),
+            (Some(begin), false) => {
+                let word = text[begin..i].to_lowercase();
+                tokens.push((begin, i, word.chars().map(remove_diacritic).collect()));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// the indices of the tokens of `text` which are part of an occurrence of one of `phrases`
+fn matches(text: &[(usize, usize, String)], phrases: &[Vec<String>]) -> Vec<usize> {
+    let mut matched = vec![];
+    for phrase in phrases.iter().filter(|phrase| !phrase.is_empty()) {
+        for start in 0..text.len() {
+            let window = text[start..].iter().take(phrase.len());
+            if window.len() == phrase.len() && window.zip(phrase).all(|(a, b)| a.2 == *b) {
+                matched.extend(start..start + phrase.len());
+            }
+        }
+    }
+    matched.sort();
+    matched.dedup();
+    matched
+}
+
+/// an excerpt of `body` around its first match with matched words enclosed in `[` and `]`
+fn snippet(body: &str, tokens: &[(usize, usize, String)], matched: &[usize]) -> String {
+    if tokens.is_empty() {
+        return body.into();
+    }
+
+    let first = matched.first().copied().unwrap_or(0);
+    let start = first.min(tokens.len().saturating_sub(SNIPPET_WORDS));
+    let end = tokens.len().min(start + SNIPPET_WORDS);
+
+    let mut snippet = String::new();
+    let mut at = 0;
+    if start > 0 {
+        snippet.push_str("...");
+        at = tokens[start].0;
+    }
+    for (i, (begin, end, _)) in tokens.iter().enumerate().take(end).skip(start) {
+        snippet.push_str(&body[at..*begin]);
+        if matched.binary_search(&i).is_ok() {
+            snippet.push_str(&format!("[{}]", &body[*begin..*end]));
+        } else {
+            snippet.push_str(&body[*begin..*end]);
+        }
+        at = *end;
+    }
+    if end < tokens.len() {
+        snippet.push_str("...");
+    } else {
+        snippet.push_str(&body[at..]);
+    }
+    snippet
+}
+
+/// a random name for a new store of files, like `3f2a9c1e0b4d5a68`
+fn new_store_uid() -> String {
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    if let Ok(since) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+        hasher.write_u128(since.as_nanos());
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+/// a directory with one Markdown file per entry like `7.md`, with the fields of the entry as
+/// front-matter followed by its title as a heading and its body
+///
+/// Changes inside [`Backend::begin`] and [`Backend::commit`] are undone by restoring the files
+/// as they were at the start.
+#[derive(Debug)]
+pub struct Files {
+    dir: PathBuf,
+    /// see [`Backend::store_uid`]
+    uid: String,
+    /// the content of every file of an entry, by id, at the start of each open group of changes
+    snapshots: RefCell<Vec<BTreeMap<u64, String>>>,
+    #[cfg(test)]
+    temporary: bool,
+}
+
+impl Files {
+    /// the directory at `path`, it is created if it does not exist
+    pub fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let dir = path.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let path = dir.join(STORE_UID_FILE);
+        let uid = match fs::read_to_string(&path) {
+            Ok(uid) => uid.trim().to_string(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let uid = new_store_uid();
+                fs::write(&path, format!("{}\n", uid))
+                    .with_context(|| format!("could not write {}", path.display()))?;
+                uid
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("could not read {}", path.display()));
+            }
+        };
+
+        Ok(Self {
+            dir,
+            uid,
+            snapshots: RefCell::new(vec![]),
+            #[cfg(test)]
+            temporary: false,
+        })
+    }
+
+    /// a new directory which is removed again when it is dropped
+    #[cfg(test)]
+    pub fn open_test() -> Self {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "koi-test-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut files = Self::open(dir).unwrap();
+        files.temporary = true;
+        files
+    }
+
+    fn path(&self, id: u64) -> PathBuf {
+        self.dir.join(format!("{}.md", id))
+    }
+
+    /// the content of the file of every entry by id
+    ///
+    /// Only files named like [`Files::path`] names them are entries, `007.md` is not one.
+    fn read_all(&self) -> anyhow::Result<BTreeMap<u64, String>> {
+        let mut files = BTreeMap::new();
+        for file in fs::read_dir(&self.dir)? {
+            let path = file?.path();
+            let id = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| {
+                    let id: u64 = name.strip_suffix(".md")?.parse().ok()?;
+                    (name == format!("{}.md", id)).then_some(id)
+                });
+            if let Some(id) = id {
+                let content = fs::read_to_string(&path)
+                    .with_context(|| format!("could not read {}", path.display()))?;
+                files.insert(id, content);
+            }
+        }
+        Ok(files)
+    }
+
+    fn parse(&self, id: u64, content: &str) -> anyhow::Result<Record> {
+        from_markdown(id, content).with_context(|| format!("in {}", self.path(id).display()))
+    }
+
+    /// every record ordered by id
+    fn records(&self) -> anyhow::Result<Vec<Record>> {
+        self.read_all()?
+            .iter()
+            .map(|(id, content)| self.parse(*id, content))
+            .collect()
+    }
+
+    /// # Errors
+    /// - if there is no entry with the given id
+    fn record(&self, id: u64) -> anyhow::Result<Record> {
+        let path = self.path(id);
+        if !path.exists() {
+            anyhow::bail!("no entry with id {}", id);
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("could not read {}", path.display()))?;
+        self.parse(id, &content)
+    }
+
+    /// # Errors
+    /// - if the title, prefix or an external id would not fit on its line of the file
+    fn write(&self, record: &Record) -> anyhow::Result<()> {
+        let entry = &record.entry;
+        let line_break = ['\n', '\r'];
+        if entry.title.contains(line_break) || entry.prefix.contains(line_break) {
+            anyhow::bail!("titles and prefixes of entries in files must not contain line breaks");
+        }
+        if let Some(uid) = record
+            .uids
+            .iter()
+            .find(|uid| uid.contains(char::is_whitespace))
+        {
+            anyhow::bail!("external id '{}' must not contain whitespace", uid);
+        }
+
+        let path = self.path(entry.id);
+        fs::write(&path, to_markdown(record))
+            .with_context(|| format!("could not write {}", path.display()))
+    }
+
+    /// changes the record with the given id with `f` and writes it back
+    fn modify(&self, id: u64, f: impl FnOnce(&mut Record)) -> anyhow::Result<()> {
+        let mut record = self.record(id)?;
+        f(&mut record);
+        self.write(&record)
+    }
+
+    /// the records carrying the tag `tag`
+    fn tagged(&self, tag: &str) -> anyhow::Result<Vec<Record>> {
+        let mut records = self.records()?;
+        records.retain(|record| record.entry.tags.iter().any(|t| t == tag));
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+impl Drop for Files {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+impl Backend for Files {
+    fn get_entry_by_id(&self, id: u64) -> anyhow::Result<Entry> {
+        Ok(self.record(id)?.entry)
+    }
+
+    fn get_all_entries(&self) -> anyhow::Result<Vec<Entry>> {
+        Ok(self
+            .records()?
+            .into_iter()
+            .map(|record| record.entry)
+            .collect())
+    }
+
+    fn query_entries(&self, query: &Query) -> anyhow::Result<Vec<Entry>> {
+        Ok(query.apply(self.get_all_entries()?))
+    }
+
+    fn search_entries(&self, text: &str, query: &Query) -> anyhow::Result<Vec<SearchHit>> {
+        let words: Vec<Vec<String>> = text
+            .split_whitespace()
+            .map(|word| tokens(word).into_iter().map(|token| token.2).collect())
+            .collect();
+        if words.is_empty() {
+            anyhow::bail!("nothing to search for");
+        }
+
+        // more occurrences make a better match, a simpler rank than the bm25 of the database
+        let mut hits = vec![];
+        for entry in self.get_all_entries()? {
+            if !query.matches(&entry) {
+                continue;
+            }
+
+            let title = tokens(&entry.title);
+            let body = tokens(&entry.body);
+            let found = words.iter().all(|word| {
+                let word = std::slice::from_ref(word);
+                !matches(&title, word).is_empty() || !matches(&body, word).is_empty()
+            });
+            if !found {
+                continue;
+            }
+
+            let in_body = matches(&body, &words);
+            let count = matches(&title, &words).len() + in_body.len();
+            let snippet = snippet(&entry.body, &body, &in_body);
+            hits.push((count, SearchHit { entry, snippet }));
+        }
+
+        if query.sort.is_some() {
+            let mut snippets: BTreeMap<u64, String> = hits
+                .iter()
+                .map(|(_, hit)| (hit.entry.id, hit.snippet.clone()))
+                .collect();
+            let entries = query.apply(hits.into_iter().map(|(_, hit)| hit.entry).collect());
+            return Ok(entries
+                .into_iter()
+                .map(|entry| SearchHit {
+                    snippet: snippets.remove(&entry.id).unwrap_or_default(),
+                    entry,
+                })
+                .collect());
+        }
+
+        hits.sort_by_key(|(count, hit)| (Reverse(*count), hit.entry.id));
+        let offset = query.offset.unwrap_or(0) as usize;
+        let limit = query
+            .limit
+            .map(|limit| limit as usize)
+            .unwrap_or(usize::MAX);
+        Ok(hits
+            .into_iter()
+            .map(|(_, hit)| hit)
+            .skip(offset)
+            .take(limit)
+            .collect())
+    }
+
+    fn get_max_entry_id(&self) -> anyhow::Result<Option<u64>> {
+        Ok(self.read_all()?.keys().next_back().copied())
+    }
+
+    fn add_entry(&self, entry: &Entry) -> anyhow::Result<()> {
+        if self.path(entry.id).exists() {
+            anyhow::bail!("an entry with id {} exists already", entry.id);
+        }
+        for tag in &entry.tags {
+            ensure_valid_tag(tag)?;
+        }
+
+        let mut entry = entry.clone();
+        entry.tags.sort();
+        entry.tags.dedup();
+        self.write(&Record {
+            entry,
+            uids: vec![],
+        })
+    }
+
+    fn update_entry(&self, entry: &Entry) -> anyhow::Result<()> {
+        let uids = self.record(entry.id)?.uids;
+        for tag in &entry.tags {
+            ensure_valid_tag(tag)?;
+        }
+
+        let mut entry = entry.clone();
+        entry.tags.sort();
+        entry.tags.dedup();
+        self.write(&Record { entry, uids })
+    }
+
+    fn close_entry(&self, id: u64, at: DateTime) -> anyhow::Result<()> {
+        self.modify(id, |record| record.entry.closed = Some(at))
+    }
+
+    fn reopen_entry(&self, id: u64) -> anyhow::Result<()> {
+        self.modify(id, |record| record.entry.closed = None)
+    }
+
+    fn delete_entry(&self, id: u64) -> anyhow::Result<()> {
+        self.record(id)?;
+        let path = self.path(id);
+        fs::remove_file(&path).with_context(|| format!("could not remove {}", path.display()))
+    }
+
+    fn get_tags(&self) -> anyhow::Result<Vec<(String, u64)>> {
+        let mut tags = BTreeMap::new();
+        for entry in self.get_all_entries()? {
+            for tag in entry.tags {
+                *tags.entry(tag).or_insert(0) += 1;
+            }
+        }
+        Ok(tags.into_iter().collect())
+    }
+
+    fn rename_tag(&self, from: &str, to: &str) -> anyhow::Result<()> {
+        ensure_valid_tag(to)?;
+        if !self.tagged(to)?.is_empty() {
+            anyhow::bail!("tag '{}' already exists", to);
+        }
+        self.merge_tags(from, to)
+    }
+
+    fn merge_tags(&self, from: &str, into: &str) -> anyhow::Result<()> {
+        ensure_valid_tag(into)?;
+        let records = self.tagged(from)?;
+        if records.is_empty() {
+            anyhow::bail!("no tag '{}'", from);
+        }
+        if from == into {
+            return Ok(());
+        }
+
+        atomically(self, || {
+            for mut record in records {
+                let tags = &mut record.entry.tags;
+                tags.retain(|tag| tag != from);
+                tags.push(into.into());
+                tags.sort();
+                tags.dedup();
+                self.write(&record)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn get_entry_id_by_uid(&self, uid: &str) -> anyhow::Result<Option<u64>> {
+        Ok(self
+            .records()?
+            .into_iter()
+            .find(|record| record.uids.iter().any(|u| u == uid))
+            .map(|record| record.entry.id))
+    }
+
+    fn set_entry_uid(&self, id: u64, uid: &str) -> anyhow::Result<()> {
+        self.record(id)?;
+        atomically(self, || {
+            if let Some(old) = self.get_entry_id_by_uid(uid)? {
+                self.modify(old, |record| record.uids.retain(|u| u != uid))?;
+            }
+            self.modify(id, |record| {
+                record.uids.push(uid.into());
+                record.uids.sort();
+            })
+        })
+    }
+
+    fn get_all_uids(&self) -> anyhow::Result<Vec<(String, u64)>> {
+        Ok(self
+            .records()?
+            .into_iter()
+            .flat_map(|record| {
+                let id = record.entry.id;
+                record.uids.into_iter().map(move |uid| (uid, id))
+            })
+            .collect())
+    }
+
+    // the files are read and written by the current version only
+    fn store_uid(&self) -> anyhow::Result<String> {
+        Ok(self.uid.clone())
+    }
+
+    fn migration_level(&self) -> anyhow::Result<usize> {
+        Ok(MIGRATION_LEVEL)
+    }
+
+    fn begin(&self) -> anyhow::Result<()> {
+        let files = self.read_all()?;
+        self.snapshots.borrow_mut().push(files);
+        Ok(())
+    }
+
+    fn commit(&self) -> anyhow::Result<()> {
+        self.snapshots.borrow_mut().pop();
+        Ok(())
+    }
+
+    fn rollback(&self) -> anyhow::Result<()> {
+        let Some(snapshot) = self.snapshots.borrow_mut().pop() else {
+            return Ok(());
+        };
+
+        for id in self.read_all()?.keys() {
+            if !snapshot.contains_key(id) {
+                fs::remove_file(self.path(*id))?;
+            }
+        }
+        for (id, content) in snapshot {
+            fs::write(self.path(id), content)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_markdown() {
+        let mut entry = Entry::test_entry(7, "write report".into());
+        entry.body = "outline first\n\n---\n# not a title".into();
+        entry.prefix = "work/acme".into();
+        entry.opened = at("2025-03-01 08:00");
+        entry.deadline = Some(at("2025-03-07 16:00"));
+        entry.recurrence = Some("every 1w".parse().unwrap());
+        entry.tags = vec!["report".into(), "urgent".into()];
+        let record = Record {
+            entry,
+            uids: vec!["7@koi".into()],
+        };
+
+        let content = to_markdown(&record);
+        let expected = "---\n\
+                        type: todo\n\
+                        prefix: work/acme\n\
+                        opened: 2025-03-01T08:00Z\n\
+                        deadline: 2025-03-07T16:00Z\n\
+                        recurrence: every 1w\n\
+                        tags: report urgent\n\
+                        uids: 7@koi\n\
+                        ---\n\
+                        # write report\n\
+                        \n\
+                        outline first\n\n---\n# not a title\n";
+        assert_eq!(expected, content);
+        assert_eq!(record, from_markdown(7, &content).unwrap());
+
+        let mut bare = Entry::test_entry(1, "".into());
+        bare.opened = at("2025-03-01 08:00");
+        let record = Record {
+            entry: bare,
+            uids: vec![],
+        };
+        let content = to_markdown(&record);
+        assert_eq!(record, from_markdown(1, &content).unwrap());
+    }
+
+    #[test]
+    fn test_markdown_crlf() {
+        let mut entry = Entry::test_entry(7, "write report ".into());
+        entry.body = "outline first\n\nthen\r\nwrite\n".into();
+        entry.prefix = " work/ acme  ".into();
+        entry.opened = at("2025-03-01 08:00");
+        entry.tags = vec!["report".into()];
+        let record = Record {
+            entry,
+            uids: vec![],
+        };
+
+        let content = to_markdown(&record);
+        assert!(content.contains("prefix:  work/ acme  \n"));
+        assert_eq!(record, from_markdown(7, &content).unwrap());
+
+        // the same file with Windows line endings, the lone '\r' was part of the body
+        let crlf = content.replace("\r\n", "\r").replace('\n', "\r\n");
+        let mut expected = record.clone();
+        expected.entry.body = "outline first\n\nthen\rwrite\n".into();
+        let parsed = from_markdown(7, &crlf).unwrap();
+        assert_eq!(expected, parsed);
+        assert_eq!(parsed, from_markdown(7, &to_markdown(&parsed)).unwrap());
+
+        let files = Files::open_test();
+        fs::write(files.path(7), &crlf).unwrap();
+        assert_eq!(expected.entry, files.get_entry_by_id(7).unwrap());
+        files.update_entry(&expected.entry).unwrap();
+        assert_eq!(
+            to_markdown(&expected),
+            fs::read_to_string(files.path(7)).unwrap()
+        );
+
+        let mut invalid = record.entry.clone();
+        invalid.prefix = "work\r".into();
+        assert!(files.update_entry(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_invalid_markdown() {
+        let error = |content: &str| format!("{:#}", from_markdown(1, content).unwrap_err());
+        assert_eq!("missing front-matter", error("# title\n"));
+        assert_eq!("missing opened", error("---\ntype: todo\n---\n# title\n"));
+        assert_eq!(
+            "unknown front-matter key 'color'",
+            error("---\ncolor: red\n---\n# title\n")
+        );
+        assert_eq!(
+            "invalid deadline 'friday': expected a date time in UTC like 2025-03-01T13:30Z, \
+             found 'friday'",
+            error("---\nopened: 2025-03-01T08:00Z\ndeadline: friday\n---\n# title\n")
+        );
+        assert!(from_markdown(1, "---\nopened: 2025-03-01T08:00Z\n---\ntitle\n").is_err());
+    }
+
+    #[test]
+    fn test_snippet() {
+        let snippet = |body: &str, words: &[&str]| {
+            let body_tokens = tokens(body);
+            let words: Vec<Vec<String>> = words.iter().map(|word| vec![word.to_string()]).collect();
+            snippet(body, &body_tokens, &matches(&body_tokens, &words))
+        };
+        assert_eq!(
+            "review the [budget] with the [Budget] team",
+            snippet("review the budget with the Budget team", &["budget"])
+        );
+        assert_eq!(
+            "...g h i j k l m [n] o p q r",
+            snippet("a b c d e f g h i j k l m n o p q r", &["n"])
+        );
+        assert_eq!(
+            "[a] b c d e f g h i j k l...",
+            snippet("a b c d e f g h i j k l m n", &["a"])
+        );
+        assert_eq!("", snippet("", &["n"]));
+    }
+
+    #[test]
+    fn test_store_uid() {
+        let files = Files::open_test();
+        let uid = files.store_uid().unwrap();
+        assert_eq!(16, uid.len());
+        assert_eq!(uid, Files::open(&files.dir).unwrap().store_uid().unwrap());
+        assert_ne!(uid, Files::open_test().store_uid().unwrap());
+    }
+
+    #[test]
+    fn test_ignores_other_files() {
+        let files = Files::open_test();
+        fs::write(files.dir.join("README.md"), "# todos\n").unwrap();
+        files.add_entry(&Entry::test_entry(3, "a".into())).unwrap();
+        // only `7.md` is the file of entry 7
+        let content = fs::read_to_string(files.dir.join("3.md")).unwrap();
+        for name in ["007.md", "+7.md", "7.MD"] {
+            fs::write(files.dir.join(name), &content).unwrap();
+        }
+
+        assert_eq!(Some(3), files.get_max_entry_id().unwrap());
+        assert_eq!(1, files.get_all_entries().unwrap().len());
+        assert!(files.dir.join("3.md").exists());
+    }
+
+    #[test]
+    fn test_rollback() {
+        let files = Files::open_test();
+        files.add_entry(&Entry::test_entry(1, "a".into())).unwrap();
+
+        let result: anyhow::Result<()> = atomically(&files, || {
+            files.add_entry(&Entry::test_entry(2, "b".into()))?;
+            files.update_entry(&Entry::test_entry(1, "changed".into()))?;
+            files.delete_entry(42)
+        });
+        assert!(result.is_err());
+
+        let entries = files.get_all_entries().unwrap();
+        assert_eq!(vec![Entry::test_entry(1, "a".into())], entries);
+    }
+}
@@ -1,72 +1,949 @@
+use std::collections::HashSet;
 use std::path::Path;
 
-use crate::entry::{Entry, EntryState};
+use crate::entry::{Entry, EntryType};
 use crate::time::prelude::*;
-use rusqlite::Connection;
 
+mod backend;
+mod dump;
+mod files;
+mod query;
+pub use backend::{Backend, atomically};
+pub use dump::{DUMP_VERSION, ImportMode, ImportReport};
+pub use files::Files;
+pub use query::{Query, SortKey, SortOrder};
+
+use dump::{Dump, DumpEntry};
+
+/// an entry found by [`Store::search`]
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub struct Query<'a> {
-    pub state: Option<EntryState>,
-    pub prefix: Option<&'a str>,
-    pub scheduled_or_deadline: Option<(DateTime, DateTime)>,
+pub struct SearchHit {
+    pub entry: Entry,
+
+    /// an excerpt of the body with matching words enclosed in `[` and `]`
+    pub snippet: String,
+}
+
+/// `entry` with each of its date times converted by `f`
+fn convert_entry(entry: Entry, f: impl Fn(DateTime) -> DateTime) -> Entry {
+    Entry {
+        opened: f(entry.opened),
+        closed: entry.closed.map(&f),
+        scheduled: entry.scheduled.map(&f),
+        scheduled_end: entry.scheduled_end.map(&f),
+        deadline: entry.deadline.map(&f),
+        ..entry
+    }
+}
+
+/// `query` with the bounds of each of its ranges converted by `f`
+fn convert_query<'a>(query: &Query<'a>, f: impl Fn(DateTime) -> DateTime) -> Query<'a> {
+    let range = |range: Option<DateTimeRange>| {
+        range.map(|range| DateTimeRange::new(f(range.begin), f(range.end)))
+    };
+    Query {
+        opened: range(query.opened),
+        closed: range(query.closed),
+        scheduled: range(query.scheduled),
+        deadline: range(query.deadline),
+        scheduled_or_deadline: range(query.scheduled_or_deadline),
+        ..query.clone()
+    }
+}
+
+/// the external id of the entry with the given id in files written from the store named
+/// `store_uid`, like `7@3f2a9c1e0b4d5a68.koi`
+///
+/// [`Store::entry_id_by_uid`] of that store finds the entry by it without storing it, other
+/// stores do not.
+pub fn entry_uid(store_uid: &str, id: u64) -> String {
+    format!("{}@{}.koi", id, store_uid)
 }
 
+/// the id of an entry from an external id written by [`entry_uid`] for the store `store_uid`
+fn parse_entry_uid(store_uid: &str, uid: &str) -> Option<u64> {
+    let (id, rest) = uid.split_once('@')?;
+    match rest.strip_suffix(".koi") {
+        Some(name) if name == store_uid => id.parse().ok(),
+        _ => None,
+    }
+}
+
+/// the entries of a database or of a directory of files, see [`Backend`]
+///
+/// Date times passed to and returned from a store are wall clock times in its zone, which is
+/// [`Zone::Local`] unless set with [`Store::set_zone`]. The backend holds them as UTC.
 #[derive(Debug)]
 pub struct Store {
-    db: Connection,
+    backend: Box<dyn Backend>,
+    zone: Zone,
 }
 
 mod db;
 
 impl Store {
+    #[cfg(test)]
+    pub fn open_test() -> Self {
+        Self::new(Box::new(db::open_test()))
+    }
+
+    /// a store in a new temporary directory of files, which is removed with the store
+    #[cfg(test)]
+    pub fn open_test_files() -> Self {
+        Self::new(Box::new(Files::open_test()))
+    }
+
+    pub fn new(backend: Box<dyn Backend>) -> Self {
+        Self {
+            backend,
+            zone: Zone::Local,
+        }
+    }
+
+    /// opens the directory of files at `path` if it is a directory or ends with a `/`, otherwise
+    /// the database file at `path`, either is created if it does not exist
     pub fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
-        let db = db::open(path)?;
-        Ok(Self { db })
+        let path = path.as_ref();
+        let dir = path
+            .as_os_str()
+            .as_encoded_bytes()
+            .last()
+            .is_some_and(|c| std::path::is_separator(*c as char));
+        if dir || path.is_dir() {
+            return Ok(Self::new(Box::new(Files::open(path)?)));
+        }
+        Ok(Self::new(Box::new(db::open(path)?)))
+    }
+
+    pub fn zone(&self) -> Zone {
+        self.zone
+    }
+
+    /// sets the zone of the date times passed to and returned from this store
+    pub fn set_zone(&mut self, zone: Zone) {
+        self.zone = zone;
+    }
+
+    fn in_utc(&self, entry: &Entry) -> Entry {
+        convert_entry(entry.clone(), |at| self.zone.to_utc(at))
+    }
+
+    fn in_zone(&self, entry: Entry) -> Entry {
+        convert_entry(entry, |at| self.zone.from_utc(at))
+    }
+
+    fn query_entries(&self, query: &Query) -> anyhow::Result<Vec<Entry>> {
+        let query = convert_query(query, |at| self.zone.to_utc(at));
+        let entries = self.backend.query_entries(&query)?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| self.in_zone(entry))
+            .collect())
     }
 
     pub fn query_by_id(&mut self, id: u64) -> anyhow::Result<Entry> {
-        db::get_entry_by_id(&self.db, id)
+        self.backend
+            .get_entry_by_id(id)
+            .map(|entry| self.in_zone(entry))
     }
 
     pub fn query(&mut self, query: Query) -> anyhow::Result<Vec<Entry>> {
-        let mut entries = db::get_all_entries(&self.db)?;
+        self.query_entries(&query)
+    }
 
-        let filter = |entry: &Entry| {
-            if let Some(state) = query.state {
-                if state != entry.state() {
-                    return false;
-                }
+    /// entries matching `query` which are scheduled or due in `range`, ordered by time
+    ///
+    /// Recurring meetings are expanded into each of their occurrences in `range`, all
+    /// occurrences keep the id of their entry. The range, sort order and limits of `query` are
+    /// ignored.
+    pub fn agenda(&mut self, range: DateTimeRange, query: Query) -> anyhow::Result<Vec<Entry>> {
+        let is_recurring_meeting =
+            |entry: &Entry| entry.entry_type == EntryType::Meeting && entry.recurrence.is_some();
+
+        let query = Query {
+            scheduled_or_deadline: None,
+            sort: None,
+            limit: None,
+            offset: None,
+            ..query
+        };
+
+        let mut entries = self.query_entries(&Query {
+            scheduled_or_deadline: Some(range),
+            ..query.clone()
+        })?;
+        entries.retain(|entry| !is_recurring_meeting(entry));
+
+        if query.entry_type != Some(EntryType::Todo) {
+            let meetings = self.query_entries(&Query {
+                entry_type: Some(EntryType::Meeting),
+                recurring: Some(true),
+                ..query
+            })?;
+
+            let in_range = |at: Option<DateTime>| at.is_some_and(|at| range.contains(at));
+            let earliest = |entry: &Entry| entry.scheduled.into_iter().chain(entry.deadline).min();
+
+            for meeting in &meetings {
+                let occurrences = meeting
+                    .occurrences()
+                    .take_while(|occurrence| earliest(occurrence).is_some_and(|at| at < range.end))
+                    .filter(|occurrence| {
+                        in_range(occurrence.scheduled) || in_range(occurrence.deadline)
+                    });
+                entries.extend(occurrences);
             }
+        }
 
-            if let Some(prefix) = query.prefix {
-                if !entry.prefix.starts_with(prefix) {
-                    return false;
+        entries.sort_by_key(|entry| (entry.scheduled.or(entry.deadline), entry.id));
+        Ok(entries)
+    }
+
+    /// full text search over title and body, restricted to entries matching `query`
+    pub fn search(&mut self, text: &str, query: Query) -> anyhow::Result<Vec<SearchHit>> {
+        let query = convert_query(&query, |at| self.zone.to_utc(at));
+        let hits = self.backend.search_entries(text, &query)?;
+        Ok(hits
+            .into_iter()
+            .map(|hit| SearchHit {
+                entry: self.in_zone(hit.entry),
+                ..hit
+            })
+            .collect())
+    }
+
+    /// every tag with the number of entries it is attached to, ordered by name
+    pub fn tags(&mut self) -> anyhow::Result<Vec<(String, u64)>> {
+        self.backend.get_tags()
+    }
+
+    pub fn rename_tag(&mut self, from: &str, to: &str) -> anyhow::Result<()> {
+        self.backend.rename_tag(from, to)
+    }
+
+    /// replaces the tag `from` with `into` on every entry
+    pub fn merge_tags(&mut self, from: &str, into: &str) -> anyhow::Result<()> {
+        self.backend.merge_tags(from, into)
+    }
+
+    /// the id of the entry an external id, like the `UID` of an iCalendar event, is attached to
+    ///
+    /// An external id written by [`entry_uid`] for this store is the id of its entry, unless it
+    /// is attached to another entry or there is no such entry anymore.
+    pub fn entry_id_by_uid(&self, uid: &str) -> anyhow::Result<Option<u64>> {
+        if let Some(id) = self.backend.get_entry_id_by_uid(uid)? {
+            return Ok(Some(id));
+        }
+
+        match parse_entry_uid(&self.backend.store_uid()?, uid) {
+            Some(id) if self.backend.get_entry_by_id(id).is_ok() => Ok(Some(id)),
+            _ => Ok(None),
+        }
+    }
+
+    /// the random name of this store, see [`entry_uid`]
+    pub fn uid(&mut self) -> anyhow::Result<String> {
+        self.backend.store_uid()
+    }
+
+    /// attaches an external id to an entry, it is removed together with the entry
+    pub fn set_entry_uid(&mut self, id: u64, uid: &str) -> anyhow::Result<()> {
+        self.backend.set_entry_uid(id, uid)
+    }
+
+    /// an id which is not used by any entry yet, `1` in an empty store
+    pub fn new_entry_id(&mut self) -> anyhow::Result<u64> {
+        backend::new_entry_id(&*self.backend)
+    }
+
+    pub fn add_entry(&mut self, entry: &Entry) -> anyhow::Result<()> {
+        self.backend.add_entry(&self.in_utc(entry))
+    }
+
+    pub fn update_entry(&mut self, entry: &Entry) -> anyhow::Result<()> {
+        self.backend.update_entry(&self.in_utc(entry))
+    }
+
+    /// closes the entry, if it is open and recurs its next occurrence is added as a new entry
+    ///
+    /// The recurrence moves to the new entry, the closed one does not recur anymore.
+    ///
+    /// # Returns
+    /// - the id of the next occurrence if one was added
+    pub fn close_entry(&mut self, id: u64, at: DateTime) -> anyhow::Result<Option<u64>> {
+        let backend = &*self.backend;
+        atomically(backend, || {
+            // occurrences follow the wall clock, so they are computed in the zone of the store
+            let entry = self.in_zone(backend.get_entry_by_id(id)?);
+            backend.close_entry(id, self.zone.to_utc(at))?;
+
+            let next = match entry.next_occurrence() {
+                Some(next) if entry.is_open() => next,
+                _ => return Ok(None),
+            };
+
+            let closed = Entry {
+                closed: Some(at),
+                recurrence: None,
+                ..entry
+            };
+            backend.update_entry(&self.in_utc(&closed))?;
+
+            let next = Entry {
+                id: backend::new_entry_id(backend)?,
+                opened: at,
+                ..next
+            };
+            backend.add_entry(&self.in_utc(&next))?;
+
+            Ok(Some(next.id))
+        })
+    }
+
+    pub fn reopen_entry(&mut self, id: u64) -> anyhow::Result<()> {
+        self.backend.reopen_entry(id)
+    }
+
+    pub fn delete_entry(&mut self, id: u64) -> anyhow::Result<()> {
+        self.backend.delete_entry(id)
+    }
+
+    /// adds entries read from a file with their external ids, like the `UID`s of iCalendar
+    /// events, an entry whose external id is found by [`Store::entry_id_by_uid`] is updated to
+    /// `update(old, new)` instead
+    ///
+    /// The ids of the new entries are ignored, nothing is imported if one of them fails.
+    ///
+    /// # Returns
+    /// - the ids of the added and of the updated entries, nothing is renumbered
+    pub fn import_entries<I, F>(&mut self, entries: I, update: F) -> anyhow::Result<ImportReport>
+    where
+        I: IntoIterator<Item = (Option<String>, Entry)>,
+        F: Fn(Entry, Entry) -> Entry,
+    {
+        let backend = &*self.backend;
+        atomically(backend, || {
+            let mut report = ImportReport::default();
+
+            for (uid, entry) in entries {
+                let existing = match &uid {
+                    Some(uid) => self.entry_id_by_uid(uid)?,
+                    None => None,
+                };
+
+                match existing {
+                    Some(id) => {
+                        let old = self.in_zone(backend.get_entry_by_id(id)?);
+                        let entry = Entry {
+                            id,
+                            ..update(old, entry)
+                        };
+                        backend.update_entry(&self.in_utc(&entry))?;
+                        report.updated.push(id);
+                    }
+                    None => {
+                        let id = backend::new_entry_id(backend)?;
+                        backend.add_entry(&self.in_utc(&Entry { id, ..entry }))?;
+                        if let Some(uid) = &uid {
+                            backend.set_entry_uid(id, uid)?;
+                        }
+                        report.added.push(id);
+                    }
                 }
             }
 
-            if let Some((begin, end)) = query.scheduled_or_deadline {
-                let check_range =
-                    |a: Option<DateTime>| a.map(|a| begin <= a && a < end).unwrap_or(false);
+            Ok(report)
+        })
+    }
+
+    /// every entry with its external ids and the schema level of the database as a versioned
+    /// JSON document, date times in it are UTC like `2025-03-01T13:30Z` so it does not depend on
+    /// the zone of the store
+    pub fn export_all(&mut self) -> anyhow::Result<String> {
+        let mut uids = self.backend.get_all_uids()?.into_iter().peekable();
+        let entries = self
+            .backend
+            .get_all_entries()?
+            .into_iter()
+            .map(|entry| {
+                let mut entry_uids = Vec::new();
+                while let Some((uid, _)) = uids.next_if(|(_, id)| *id == entry.id) {
+                    entry_uids.push(uid);
+                }
+                DumpEntry {
+                    entry,
+                    uids: entry_uids,
+                }
+            })
+            .collect();
+
+        Dump {
+            version: DUMP_VERSION,
+            migration: self.backend.migration_level()?,
+            entries,
+        }
+        .to_json()
+    }
+
+    /// adds the entries of a document written by [`Store::export_all`], `mode` decides what
+    /// happens to entries whose id is already taken
+    ///
+    /// The whole document is checked before the store is changed, nothing is imported if it
+    /// is invalid.
+    ///
+    /// # Errors
+    /// - if the document is invalid or from a newer schema, see [`DUMP_VERSION`]
+    /// - if `mode` is [`ImportMode::Empty`] and the store contains entries
+    pub fn import_all(&mut self, json: &str, mode: ImportMode) -> anyhow::Result<ImportReport> {
+        let dump = Dump::from_json(json, db::MIGRATION_LEVEL)?;
+
+        let backend = &*self.backend;
+        let existing: HashSet<u64> = backend
+            .get_all_entries()?
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+
+        if mode == ImportMode::Empty && !existing.is_empty() {
+            anyhow::bail!(
+                "the store is not empty, it contains {} entries",
+                existing.len()
+            );
+        }
+
+        // renumbered entries go after every id of the store and of the document, so they
+        // never take the id of an entry imported later
+        let mut next_id = existing
+            .iter()
+            .chain(dump.entries.iter().map(|entry| &entry.entry.id))
+            .max()
+            .map_or(1, |id| id + 1);
+
+        atomically(backend, || {
+            let mut report = ImportReport::default();
+
+            for DumpEntry { mut entry, uids } in dump.entries {
+                let taken = existing.contains(&entry.id);
+                match mode {
+                    ImportMode::MergeById if taken => {
+                        backend.update_entry(&entry)?;
+                        report.updated.push(entry.id);
+                    }
+                    ImportMode::Renumber if taken => {
+                        report.renumbered.push((entry.id, next_id));
+                        entry.id = next_id;
+                        next_id += 1;
+                        backend.add_entry(&entry)?;
+                        report.added.push(entry.id);
+                    }
+                    _ => {
+                        backend.add_entry(&entry)?;
+                        report.added.push(entry.id);
+                    }
+                }
 
-                let scheduled = check_range(entry.scheduled);
-                let deadline = check_range(entry.deadline);
-                if !(scheduled || deadline) {
-                    return false;
+                for uid in &uids {
+                    backend.set_entry_uid(entry.id, uid)?;
                 }
             }
-            true
+
+            Ok(report)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::entry::EntryState;
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_open() {
+        let dir = std::env::temp_dir().join(format!("koi-test-open-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir(&dir).unwrap();
+
+        // a trailing separator creates a directory of files
+        let notes = dir.join("notes");
+        let mut store = Store::open(format!("{}/", notes.display())).unwrap();
+        store.add_entry(&Entry::test_entry(1, "a".into())).unwrap();
+        assert!(notes.join("1.md").is_file());
+        drop(store);
+
+        // an existing directory is opened as files without it
+        let mut store = Store::open(&notes).unwrap();
+        assert_eq!(1, store.query_by_id(1).unwrap().id);
+
+        let database = dir.join("koi.db");
+        let mut store = Store::open(&database).unwrap();
+        store.add_entry(&Entry::test_entry(1, "a".into())).unwrap();
+        assert!(database.is_file());
+        drop(store);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_close_recurring() {
+        let mut store = Store::open_test();
+
+        let mut entry = Entry::test_entry(1, "water plants".into());
+        entry.deadline = Some(at("2025-03-01 00:00"));
+        entry.recurrence = Some("every 1w 2 times".parse().unwrap());
+        entry.tags = vec!["home".into()];
+        store.add_entry(&entry).unwrap();
+
+        let next = store
+            .close_entry(1, at("2025-03-01 10:00"))
+            .unwrap()
+            .unwrap();
+
+        let closed = store.query_by_id(1).unwrap();
+        assert_eq!(Some(at("2025-03-01 10:00")), closed.closed);
+        assert_eq!(None, closed.recurrence);
+
+        let next = store.query_by_id(next).unwrap();
+        assert!(next.is_open());
+        assert_eq!(at("2025-03-01 10:00"), next.opened);
+        assert_eq!(Some(at("2025-03-08 00:00")), next.deadline);
+        assert_eq!(Some("every 1w 1 times".parse().unwrap()), next.recurrence);
+        assert_eq!(entry.tags, next.tags);
+
+        // the last occurrence does not spawn another one
+        assert_eq!(
+            None,
+            store.close_entry(next.id, at("2025-03-08 10:00")).unwrap()
+        );
+
+        // closing twice does not spawn either
+        store.reopen_entry(1).unwrap();
+        assert_eq!(None, store.close_entry(1, at("2025-03-09 10:00")).unwrap());
+
+        assert!(store.close_entry(42, now()).is_err());
+    }
+
+    #[test]
+    fn test_zone() {
+        let mut store = Store::open_test();
+        store.set_zone("Europe/Berlin".parse().unwrap());
+
+        let mut entry = Entry::test_entry(1, "standup".into());
+        entry.opened = at("2025-03-01 09:00");
+        entry.scheduled = Some(at("2025-03-03 10:00"));
+        store.add_entry(&entry).unwrap();
+        assert_eq!(entry, store.query_by_id(1).unwrap());
+
+        let stored = store.backend.get_entry_by_id(1).unwrap();
+        assert_eq!(Some(at("2025-03-03 09:00")), stored.scheduled);
+
+        store.set_zone("America/New_York".parse().unwrap());
+        assert_eq!(
+            Some(at("2025-03-03 04:00")),
+            store.query_by_id(1).unwrap().scheduled
+        );
+
+        let query = |begin, end| Query {
+            scheduled: Some(DateTimeRange::new(at(begin), at(end))),
+            ..Default::default()
         };
+        let found = store.query(query("2025-03-03 04:00", "2025-03-03 05:00"));
+        assert_eq!(1, found.unwrap().len());
+        let found = store.query(query("2025-03-03 10:00", "2025-03-03 11:00"));
+        assert_eq!(0, found.unwrap().len());
+    }
 
-        entries.retain(filter);
-        Ok(entries)
+    #[test]
+    fn test_close_recurring_keeps_wall_clock() {
+        let mut store = Store::open_test();
+        store.set_zone("Europe/Berlin".parse().unwrap());
+
+        // clocks are turned forward on 2025-03-30
+        let mut entry = Entry::test_entry(1, "weekly".into());
+        entry.entry_type = EntryType::Meeting;
+        entry.scheduled = Some(at("2025-03-24 10:00"));
+        entry.recurrence = Some("every 1w".parse().unwrap());
+        store.add_entry(&entry).unwrap();
+
+        let next = store
+            .close_entry(1, at("2025-03-24 11:00"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Some(at("2025-03-31 10:00")),
+            store.query_by_id(next).unwrap().scheduled
+        );
     }
 
-    pub fn new_entry_id(&mut self) -> anyhow::Result<u64> {
-        db::get_max_entry_id(&self.db)
-            .map(|id| id.unwrap_or(1) + 1)
+    #[test]
+    fn test_agenda() {
+        let mut store = Store::open_test();
+
+        let mut weekly = Entry::test_entry(1, "weekly".into());
+        weekly.entry_type = EntryType::Meeting;
+        weekly.scheduled = Some(at("2025-02-03 10:00"));
+        weekly.scheduled_end = Some(at("2025-02-03 11:00"));
+        weekly.recurrence = Some("every 1w".parse().unwrap());
+        store.add_entry(&weekly).unwrap();
+
+        let mut todo = Entry::test_entry(2, "report".into());
+        todo.deadline = Some(at("2025-03-05 00:00"));
+        todo.recurrence = Some("every 1w".parse().unwrap());
+        store.add_entry(&todo).unwrap();
+
+        let mut outside = Entry::test_entry(3, "taxes".into());
+        outside.deadline = Some(at("2025-04-01 00:00"));
+        store.add_entry(&outside).unwrap();
+
+        let range = DateTimeRange::new(at("2025-03-01 00:00"), at("2025-03-15 00:00"));
+        let agenda = store
+            .agenda(
+                range,
+                Query {
+                    state: Some(EntryState::Open),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let got: Vec<_> = agenda
+            .iter()
+            .map(|entry| (entry.id, entry.scheduled.or(entry.deadline).unwrap()))
+            .collect();
+        let expected = vec![
+            (1, at("2025-03-03 10:00")),
+            (2, at("2025-03-05 00:00")),
+            (1, at("2025-03-10 10:00")),
+        ];
+        assert_eq!(expected, got);
+        assert_eq!(Some(at("2025-03-10 11:00")), agenda[2].scheduled_end);
+
+        let todos = store
+            .agenda(
+                range,
+                Query {
+                    entry_type: Some(EntryType::Todo),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(1, todos.len());
     }
 
-    pub fn add_entry(&mut self, entry: &Entry) -> anyhow::Result<()> {
-        db::add_entry(&self.db, entry)
+    #[test]
+    fn test_backends_agree() {
+        let stores = [Store::open_test(), Store::open_test_files()];
+        let results = stores.map(|mut store| {
+            store.set_zone("Europe/Berlin".parse().unwrap());
+            let first_id = store.new_entry_id().unwrap();
+
+            let mut report = Entry::test_entry(1, "budget report".into());
+            report.body = "review the budget with the budget team".into();
+            report.prefix = "work/acme".into();
+            report.opened = at("2025-03-01 09:00");
+            report.deadline = Some(at("2025-03-07 17:00"));
+            report.recurrence = Some("every 1w".parse().unwrap());
+            report.tags = vec!["urgent".into(), "work".into()];
+            store.add_entry(&report).unwrap();
+
+            let mut standup = Entry::test_entry(4, "standup".into());
+            standup.entry_type = EntryType::Meeting;
+            standup.prefix = "work".into();
+            standup.opened = at("2025-03-01 10:00");
+            standup.scheduled = Some(at("2025-03-03 10:00"));
+            standup.scheduled_end = Some(at("2025-03-03 10:15"));
+            standup.body = "Café meeting".into();
+            standup.tags = vec!["team".into()];
+            store.add_entry(&standup).unwrap();
+
+            let mut call = Entry::test_entry(5, "call bob".into());
+            call.body = "ask about the Budget".into();
+            call.opened = at("2025-03-02 08:00");
+            call.tags = vec!["@phone".into(), "team".into()];
+            store.add_entry(&call).unwrap();
+
+            assert!(store.add_entry(&call).is_err());
+            assert!(store.add_entry(&Entry::test_entry(9, "a".into())).is_ok());
+            store.delete_entry(9).unwrap();
+            assert!(store.delete_entry(9).is_err());
+            let missing = Entry::test_entry(9, "a".into());
+            assert!(store.update_entry(&missing).is_err());
+            assert!(store.reopen_entry(9).is_err());
+            assert!(store.set_entry_uid(9, "a").is_err());
+            let invalid = Entry {
+                tags: vec!["a b".into()],
+                ..call.clone()
+            };
+            assert!(store.update_entry(&invalid).is_err());
+
+            store.set_entry_uid(1, "report@example.com").unwrap();
+            store.set_entry_uid(4, "report@example.com").unwrap();
+            store.set_entry_uid(4, "4@koi").unwrap();
+            let next = store.close_entry(1, at("2025-03-06 18:00")).unwrap();
+            store.rename_tag("team", "crew").unwrap();
+            assert!(store.rename_tag("crew", "urgent").is_err());
+            assert!(store.rename_tag("team", "other").is_err());
+            store.merge_tags("urgent", "work").unwrap();
+            assert!(store.merge_tags("urgent", "work").is_err());
+
+            let queries = [
+                Query::default(),
+                Query {
+                    state: Some(EntryState::Open),
+                    ..Default::default()
+                },
+                Query {
+                    prefix: Some("work".into()),
+                    sort: Some((SortKey::Opened, SortOrder::Descending)),
+                    ..Default::default()
+                },
+                Query {
+                    tags: vec!["crew"],
+                    text: vec!["BOB".into()],
+                    ..Default::default()
+                },
+                Query {
+                    scheduled_or_deadline: Some(DateTimeRange::new(
+                        at("2025-03-03 00:00"),
+                        at("2025-03-10 00:00"),
+                    )),
+                    limit: Some(2),
+                    offset: Some(1),
+                    ..Default::default()
+                },
+            ];
+            let queried: Vec<Vec<Entry>> = queries
+                .iter()
+                .map(|query| store.query(query.clone()).unwrap())
+                .collect();
+
+            // best matches first where both ranks agree, the given order otherwise
+            let by_id = Query {
+                sort: Some((SortKey::Id, SortOrder::Descending)),
+                ..Default::default()
+            };
+            let searches = [
+                ("budget", Query::default()),
+                ("Budget bob", Query::default()),
+                ("standup", Query::default()),
+                ("nothing", Query::default()),
+                ("cafe", Query::default()),
+                ("CAFÉ MEETING", Query::default()),
+                (
+                    "budget",
+                    Query {
+                        offset: Some(1),
+                        ..Default::default()
+                    },
+                ),
+                ("the", by_id.clone()),
+                (
+                    "the",
+                    Query {
+                        limit: Some(1),
+                        ..by_id
+                    },
+                ),
+            ];
+            let searched: Vec<Vec<u64>> = searches
+                .into_iter()
+                .map(|(text, query)| {
+                    let hits = store.search(text, query).unwrap();
+                    hits.iter().map(|hit| hit.entry.id).collect()
+                })
+                .collect();
+            assert!(store.search(" ", Query::default()).is_err());
+
+            (
+                first_id,
+                next,
+                store.new_entry_id().unwrap(),
+                queried,
+                searched,
+                store.tags().unwrap(),
+                store.entry_id_by_uid("report@example.com").unwrap(),
+                store.export_all().unwrap(),
+            )
+        });
+
+        let [db, files] = results;
+        assert_eq!(1, db.0);
+        assert_eq!(Some(6), db.1);
+        assert_eq!(
+            vec![
+                vec![1, 6, 5],
+                vec![5],
+                vec![4],
+                vec![],
+                vec![4],
+                vec![4],
+                vec![6, 5],
+                vec![6, 5, 1],
+                vec![6]
+            ],
+            db.4
+        );
+        assert_eq!(db, files);
+    }
+
+    #[test]
+    fn test_entry_uid() {
+        for mut store in [Store::open_test(), Store::open_test_files()] {
+            store.add_entry(&Entry::test_entry(7, "a".into())).unwrap();
+            store.add_entry(&Entry::test_entry(8, "b".into())).unwrap();
+            let uid = store.uid().unwrap();
+            assert_eq!(Some(7), parse_entry_uid(&uid, &entry_uid(&uid, 7)));
+
+            assert_eq!(Some(7), store.entry_id_by_uid(&entry_uid(&uid, 7)).unwrap());
+            assert_eq!(None, store.entry_id_by_uid(&entry_uid(&uid, 9)).unwrap());
+            assert_eq!(None, store.entry_id_by_uid("7@koi").unwrap());
+            let other = Store::open_test().uid().unwrap();
+            assert_eq!(None, store.entry_id_by_uid(&entry_uid(&other, 7)).unwrap());
+
+            // attached external ids come first
+            store.set_entry_uid(8, &entry_uid(&uid, 7)).unwrap();
+            assert_eq!(Some(8), store.entry_id_by_uid(&entry_uid(&uid, 7)).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_import_entries() {
+        for mut store in [Store::open_test(), Store::open_test_files()] {
+            store.set_zone("Europe/Berlin".parse().unwrap());
+            let mut standup = Entry::test_entry(1, "standup".into());
+            standup.scheduled = Some(at("2025-03-03 10:00"));
+            store.add_entry(&standup).unwrap();
+            store.set_entry_uid(1, "standup@example.com").unwrap();
+            let keep_title = |old: Entry, new: Entry| Entry {
+                title: old.title,
+                ..new
+            };
+
+            // nothing is imported if one of the entries fails
+            let moved = Entry {
+                scheduled: Some(at("2025-03-04 10:00")),
+                ..Entry::test_entry(0, "weekly standup".into())
+            };
+            let mut invalid = Entry::test_entry(0, "invalid".into());
+            invalid.tags = vec!["not a tag".into()];
+            let before = store.export_all().unwrap();
+            let entries = [
+                (Some("standup@example.com".into()), moved.clone()),
+                (
+                    Some("report@example.com".into()),
+                    Entry::test_entry(0, "report".into()),
+                ),
+                (None, invalid),
+            ];
+            assert!(store.import_entries(entries, keep_title).is_err());
+            assert_eq!(before, store.export_all().unwrap());
+
+            let entries = [
+                (Some("standup@example.com".into()), moved.clone()),
+                (
+                    Some("report@example.com".into()),
+                    Entry::test_entry(0, "report".into()),
+                ),
+                (None, Entry::test_entry(0, "taxes".into())),
+            ];
+            let report = store.import_entries(entries, keep_title).unwrap();
+            assert_eq!(vec![2, 3], report.added);
+            assert_eq!(vec![1], report.updated);
+            assert!(report.renumbered.is_empty());
+
+            let updated = store.query_by_id(1).unwrap();
+            assert_eq!("standup", updated.title);
+            assert_eq!(Some(at("2025-03-04 10:00")), updated.scheduled);
+            assert_eq!(
+                Some(2),
+                store.entry_id_by_uid("report@example.com").unwrap()
+            );
+            assert_eq!("taxes", store.query_by_id(3).unwrap().title);
+        }
+    }
+
+    #[test]
+    fn test_export_import() {
+        let mut store = Store::open_test();
+        store.set_zone("Europe/Berlin".parse().unwrap());
+
+        let mut meeting = Entry::test_entry(1, "standup".into());
+        meeting.entry_type = EntryType::Meeting;
+        meeting.opened = at("2025-03-01 09:00");
+        meeting.scheduled = Some(at("2025-03-03 10:00"));
+        meeting.recurrence = Some("every 1w".parse().unwrap());
+        meeting.tags = vec!["team".into()];
+        store.add_entry(&meeting).unwrap();
+        store.set_entry_uid(1, "standup@example.com").unwrap();
+
+        let mut todo = Entry::test_entry(3, "taxes".into());
+        todo.body = "receipts\nforms".into();
+        todo.opened = at("2025-03-01 09:00");
+        store.add_entry(&todo).unwrap();
+
+        let json = store.export_all().unwrap();
+        assert!(json.contains("\"scheduled\": \"2025-03-03T09:00Z\""));
+
+        // into an empty store, in another zone
+        let mut copy = Store::open_test();
+        copy.set_zone("Europe/Berlin".parse().unwrap());
+        let report = copy.import_all(&json, ImportMode::Empty).unwrap();
+        assert_eq!(vec![1, 3], report.added);
+        assert_eq!(json, copy.export_all().unwrap());
+        assert_eq!(meeting, copy.query_by_id(1).unwrap());
+        assert_eq!(
+            Some(1),
+            copy.entry_id_by_uid("standup@example.com").unwrap()
+        );
+
+        assert!(copy.import_all(&json, ImportMode::Empty).is_err());
+
+        // merge by id
+        copy.update_entry(&Entry {
+            title: "weekly standup".into(),
+            ..meeting.clone()
+        })
+        .unwrap();
+        copy.delete_entry(3).unwrap();
+        let report = copy.import_all(&json, ImportMode::MergeById).unwrap();
+        assert_eq!(vec![3], report.added);
+        assert_eq!(vec![1], report.updated);
+        assert_eq!(json, copy.export_all().unwrap());
+
+        // renumber on conflict, the new ids follow every id of the store and the document
+        store.delete_entry(1).unwrap();
+        let mut other = Entry::test_entry(5, "other".into());
+        other.opened = at("2025-03-01 09:00");
+        store.add_entry(&other).unwrap();
+        let report = copy
+            .import_all(&store.export_all().unwrap(), ImportMode::Renumber)
+            .unwrap();
+        assert_eq!(vec![6, 5], report.added);
+        assert_eq!(vec![(3, 6)], report.renumbered);
+        assert_eq!("taxes", copy.query_by_id(6).unwrap().title);
+        assert_eq!(4, copy.query(Query::default()).unwrap().len());
+    }
+
+    #[test]
+    fn test_import_invalid() {
+        let mut store = Store::open_test();
+        store.add_entry(&Entry::test_entry(1, "a".into())).unwrap();
+        let json = store.export_all().unwrap();
+
+        let mut copy = Store::open_test();
+        let invalid = json.replace("\"tags\": []", "\"tags\": [\"a b\"]");
+        assert_ne!(json, invalid);
+        assert!(copy.import_all(&invalid, ImportMode::Empty).is_err());
+        assert!(copy.query(Query::default()).unwrap().is_empty());
+
+        let newer = json.replace(
+            &format!("\"migration\": {}", db::MIGRATION_LEVEL),
+            &format!("\"migration\": {}", db::MIGRATION_LEVEL + 1),
+        );
+        assert!(copy.import_all(&newer, ImportMode::Empty).is_err());
+        assert!(copy.query(Query::default()).unwrap().is_empty());
     }
 }
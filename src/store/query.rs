@@ -0,0 +1,793 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::entry::{Entry, EntryState, EntryType, is_valid_tag};
+use crate::time::{ParseError, ParseErrorKind, parse_number, prelude::*};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SortKey {
+    Id,
+    Opened,
+    Closed,
+    Scheduled,
+    Deadline,
+}
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SortKey::Id => "id",
+            SortKey::Opened => "opened",
+            SortKey::Closed => "closed",
+            SortKey::Scheduled => "scheduled",
+            SortKey::Deadline => "deadline",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "id" => Ok(Self::Id),
+            "opened" => Ok(Self::Opened),
+            "closed" => Ok(Self::Closed),
+            "scheduled" => Ok(Self::Scheduled),
+            "deadline" => Ok(Self::Deadline),
+            token => {
+                let kind = ParseErrorKind::Unknown {
+                    what: "sort key",
+                    expected: "id, opened, closed, scheduled or deadline",
+                };
+                Err(ParseError::new(s, token, kind))
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// a filter over entries
+///
+/// every field which is set has to match, see [`DateTimeRange`] for ranges.
+/// Entries are returned ordered by `sort`, entries without a value for the sort key come last.
+/// Without `sort` they are ordered by ascending id.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Query<'a> {
+    pub state: Option<EntryState>,
+    pub entry_type: Option<EntryType>,
+    pub prefix: Option<Cow<'a, str>>,
+
+    /// tags an entry has to have
+    pub tags: Vec<&'a str>,
+    /// tags an entry must not have
+    pub exclude_tags: Vec<&'a str>,
+
+    /// ascii case insensitive substrings which each occur in either title or body
+    pub text: Vec<Cow<'a, str>>,
+
+    pub opened: Option<DateTimeRange>,
+    pub closed: Option<DateTimeRange>,
+    pub scheduled: Option<DateTimeRange>,
+    pub deadline: Option<DateTimeRange>,
+    pub scheduled_or_deadline: Option<DateTimeRange>,
+
+    pub has_scheduled: Option<bool>,
+    pub has_deadline: Option<bool>,
+    pub recurring: Option<bool>,
+
+    pub sort: Option<(SortKey, SortOrder)>,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+impl Query<'_> {
+    /// checks whether `entry` is matched by the filters of this query
+    pub fn matches(&self, entry: &Entry) -> bool {
+        if let Some(state) = self.state
+            && state != entry.state()
+        {
+            return false;
+        }
+
+        if let Some(entry_type) = self.entry_type
+            && entry_type != entry.entry_type
+        {
+            return false;
+        }
+
+        if let Some(prefix) = &self.prefix
+            && !entry.prefix.starts_with(prefix.as_ref())
+        {
+            return false;
+        }
+
+        let has_tag = |tag: &&str| entry.tags.iter().any(|t| t == tag);
+        if !self.tags.iter().all(has_tag) || self.exclude_tags.iter().any(has_tag) {
+            return false;
+        }
+
+        let title = entry.title.to_ascii_lowercase();
+        let body = entry.body.to_ascii_lowercase();
+        for text in &self.text {
+            let text = text.to_ascii_lowercase();
+            if !(title.contains(&text) || body.contains(&text)) {
+                return false;
+            }
+        }
+
+        let in_range =
+            |range: DateTimeRange, a: Option<DateTime>| a.is_some_and(|a| range.contains(a));
+
+        if let Some(range) = self.opened
+            && !in_range(range, Some(entry.opened))
+        {
+            return false;
+        }
+
+        if let Some(range) = self.closed
+            && !in_range(range, entry.closed)
+        {
+            return false;
+        }
+
+        if let Some(range) = self.scheduled
+            && !in_range(range, entry.scheduled)
+        {
+            return false;
+        }
+
+        if let Some(range) = self.deadline
+            && !in_range(range, entry.deadline)
+        {
+            return false;
+        }
+
+        if let Some(range) = self.scheduled_or_deadline {
+            let scheduled = in_range(range, entry.scheduled);
+            let deadline = in_range(range, entry.deadline);
+            if !(scheduled || deadline) {
+                return false;
+            }
+        }
+
+        if let Some(has_scheduled) = self.has_scheduled
+            && has_scheduled != entry.scheduled.is_some()
+        {
+            return false;
+        }
+
+        if let Some(has_deadline) = self.has_deadline
+            && has_deadline != entry.deadline.is_some()
+        {
+            return false;
+        }
+
+        if let Some(recurring) = self.recurring
+            && recurring != entry.recurrence.is_some()
+        {
+            return false;
+        }
+
+        true
+    }
+
+    /// filters, sorts and paginates `entries` in memory the same way
+    /// [`Store::query`](super::Store::query) does
+    pub fn apply(&self, mut entries: Vec<Entry>) -> Vec<Entry> {
+        entries.retain(|entry| self.matches(entry));
+
+        let (key, order) = self.sort.unwrap_or((SortKey::Id, SortOrder::Ascending));
+        let value = |entry: &Entry| match key {
+            SortKey::Id => None,
+            SortKey::Opened => Some(entry.opened),
+            SortKey::Closed => entry.closed,
+            SortKey::Scheduled => entry.scheduled,
+            SortKey::Deadline => entry.deadline,
+        };
+
+        entries.sort_by(|a, b| {
+            let ordering = match (value(a), value(b)) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => return Ordering::Less,
+                (None, Some(_)) => return Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
+            let ordering = ordering.then(a.id.cmp(&b.id));
+            match order {
+                SortOrder::Ascending => ordering,
+                SortOrder::Descending => ordering.reverse(),
+            }
+        });
+
+        let offset = self.offset.unwrap_or(0) as usize;
+        let limit = self.limit.map(|limit| limit as usize).unwrap_or(usize::MAX);
+        entries.into_iter().skip(offset).take(limit).collect()
+    }
+}
+
+/// splits `s` at whitespace outside of double quotes, returns every token
+///
+/// Inside quotes `\` escapes the following character.
+fn tokenize(s: &str) -> Result<Vec<&str>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut begin = None;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (i, c) in s.char_indices() {
+        match begin {
+            None if c.is_whitespace() => (),
+            None => {
+                begin = Some(i);
+                quoted = c == '"';
+            }
+            Some(_) if escaped => escaped = false,
+            Some(_) if quoted && c == '\\' => escaped = true,
+            Some(_) if c == '"' => quoted = !quoted,
+            Some(b) if c.is_whitespace() && !quoted => {
+                tokens.push(&s[b..i]);
+                begin = None;
+            }
+            Some(_) => (),
+        }
+    }
+
+    if let Some(b) = begin {
+        if quoted {
+            let kind = ParseErrorKind::Expected("a closing quote");
+            return Err(ParseError::new(s, &s[b..], kind));
+        }
+        tokens.push(&s[b..]);
+    }
+
+    Ok(tokens)
+}
+
+/// strips the surrounding double quotes of `value`, a slice of `input`, and resolves the escapes
+/// `\"` and `\\` between them, quotes are not allowed anywhere else
+fn unquote<'a>(input: &str, value: &'a str) -> Result<Cow<'a, str>, ParseError> {
+    let invalid = || ParseError::new(input, value, ParseErrorKind::Invalid("quoting"));
+
+    let Some(inner) = value.strip_prefix('"') else {
+        if value.contains('"') {
+            return Err(invalid());
+        }
+        return Ok(value.into());
+    };
+
+    let mut unquoted = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if chars.as_str().is_empty() => return Ok(unquoted.into()),
+            '\\' => match chars.next() {
+                Some(c @ ('"' | '\\')) => unquoted.push(c),
+                _ => return Err(invalid()),
+            },
+            '"' => return Err(invalid()),
+            c => unquoted.push(c),
+        }
+    }
+
+    Err(invalid())
+}
+
+/// quotes `s` if it would otherwise not be read back as a single value
+fn quote(s: &str, allow_colon: bool) -> String {
+    let needs_quotes = s.is_empty()
+        || s.contains(char::is_whitespace)
+        || s.contains('"')
+        || (!allow_colon && s.contains(':'));
+
+    if needs_quotes {
+        format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        s.into()
+    }
+}
+
+fn parse_datetime(input: &str, value: &str) -> Result<DateTime, ParseError> {
+    let s = unquote(input, value)?;
+    s.parse::<DateTime>().map_err(|err| {
+        // the position within an unquoted value is off, so the whole value is pointed at
+        match s {
+            Cow::Borrowed(s) => err.within(input, s),
+            Cow::Owned(_) => ParseError::new(input, value, err.kind),
+        }
+    })
+}
+
+/// parses `<end`, `>=begin`, `begin..end` or a single day, `value` is a slice of `input`
+fn parse_range(input: &str, value: &str) -> Result<DateTimeRange, ParseError> {
+    if let Some(end) = value.strip_prefix('<') {
+        return Ok(DateTimeRange::before(parse_datetime(input, end)?));
+    }
+
+    if let Some(begin) = value.strip_prefix(">=") {
+        return Ok(DateTimeRange::starting_at(parse_datetime(input, begin)?));
+    }
+
+    if let Some((begin, end)) = value.split_once("..") {
+        let begin = match begin {
+            "" => DateTime::MIN,
+            begin => parse_datetime(input, begin)?,
+        };
+        let end = match end {
+            "" => DateTime::MAX,
+            end => parse_datetime(input, end)?,
+        };
+        if end < begin {
+            return Err(ParseError::new(input, value, ParseErrorKind::InvertedRange));
+        }
+        return Ok(DateTimeRange::new(begin, end));
+    }
+
+    Ok(DateTimeRange::day(parse_datetime(input, value)?.date()))
+}
+
+fn format_datetime(datetime: DateTime) -> String {
+    if datetime.time() == Time::MIN {
+        datetime.date().to_string()
+    } else {
+        format!("\"{}\"", datetime)
+    }
+}
+
+fn format_range(range: DateTimeRange) -> String {
+    let DateTimeRange { begin, end } = range;
+    match (begin == DateTime::MIN, end == DateTime::MAX) {
+        (true, true) => "..".into(),
+        (true, false) => format!("<{}", format_datetime(end)),
+        (false, true) => format!(">={}", format_datetime(begin)),
+        (false, false) if range == DateTimeRange::day(begin.date()) => format_datetime(begin),
+        (false, false) => format!("{}..{}", format_datetime(begin), format_datetime(end)),
+    }
+}
+
+fn parse_tag<'a>(input: &str, value: &'a str) -> Result<&'a str, ParseError> {
+    if !is_valid_tag(value) {
+        return Err(ParseError::new(
+            input,
+            value,
+            ParseErrorKind::Invalid("tag"),
+        ));
+    }
+    Ok(value)
+}
+
+/// sets the filter `key`, a slice of `input`, which may be given only once
+fn set<T>(input: &str, key: &str, field: &mut Option<T>, value: T) -> Result<(), ParseError> {
+    if field.is_some() {
+        return Err(ParseError::new(input, key, ParseErrorKind::Repeated));
+    }
+    *field = Some(value);
+    Ok(())
+}
+
+impl<'a> Query<'a> {
+    /// parses a query string like `state:open prefix:work type:meeting due:<+7d "budget"`
+    ///
+    /// A query is a list of whitespace separated `key:value` filters, values may be quoted with
+    /// `"`, inside quotes `\"` and `\\` stand for `"` and `\`. A token without a key is matched
+    /// against title and body, every such token has to match.
+    ///
+    /// | key                   | value                                         |
+    /// |-----------------------|-----------------------------------------------|
+    /// | `state`               | `open` or `closed`                            |
+    /// | `type`                | `todo` or `meeting`                           |
+    /// | `prefix`              | prefix of the entry prefix                    |
+    /// | `tag`, `-tag`         | tag the entry has or must not have            |
+    /// | `text`                | substring of title or body                    |
+    /// | `opened`, `closed`    | range                                         |
+    /// | `sched`, `due`        | range, `any` or `none`                        |
+    /// | `when`                | range of either scheduled or deadline         |
+    /// | `repeat`              | `any` or `none`                               |
+    /// | `sort`                | sort key, prefixed with `-` for descending    |
+    /// | `limit`, `offset`     | number                                        |
+    ///
+    /// A range is either a single day, `<end`, `>=begin` or `begin..end` where each bound is
+    /// anything [`DateTime`] can parse, like `today`, `+2w` or `"2025-03-01 14:00"`, and the end
+    /// must not lie before the beginning.
+    pub fn parse(s: &'a str) -> Result<Self, ParseError> {
+        let mut query = Query::default();
+
+        for token in tokenize(s)? {
+            query
+                .parse_token(token)
+                .map_err(|err| err.within(s, token))?;
+        }
+
+        Ok(query)
+    }
+
+    fn parse_token(&mut self, token: &'a str) -> Result<(), ParseError> {
+        let (key, value) = match token.split_once(':') {
+            Some((key, value)) if !key.starts_with('"') => (key, value),
+            _ => {
+                self.text.push(unquote(token, token)?);
+                return Ok(());
+            }
+        };
+
+        match key {
+            "state" => {
+                let state = value
+                    .parse()
+                    .map_err(|err: ParseError| err.within(token, value))?;
+                set(token, key, &mut self.state, state)
+            }
+            "type" => {
+                let entry_type = value
+                    .parse()
+                    .map_err(|err: ParseError| err.within(token, value))?;
+                set(token, key, &mut self.entry_type, entry_type)
+            }
+            "prefix" => set(token, key, &mut self.prefix, unquote(token, value)?),
+            "tag" => {
+                self.tags.push(parse_tag(token, value)?);
+                Ok(())
+            }
+            "-tag" => {
+                self.exclude_tags.push(parse_tag(token, value)?);
+                Ok(())
+            }
+            "text" => {
+                self.text.push(unquote(token, value)?);
+                Ok(())
+            }
+            "opened" => set(token, key, &mut self.opened, parse_range(token, value)?),
+            "closed" => set(token, key, &mut self.closed, parse_range(token, value)?),
+            "sched" | "scheduled" => match value {
+                "any" => set(token, key, &mut self.has_scheduled, true),
+                "none" => set(token, key, &mut self.has_scheduled, false),
+                _ => set(token, key, &mut self.scheduled, parse_range(token, value)?),
+            },
+            "due" | "deadline" => match value {
+                "any" => set(token, key, &mut self.has_deadline, true),
+                "none" => set(token, key, &mut self.has_deadline, false),
+                _ => set(token, key, &mut self.deadline, parse_range(token, value)?),
+            },
+            "when" => {
+                let range = parse_range(token, value)?;
+                set(token, key, &mut self.scheduled_or_deadline, range)
+            }
+            "repeat" => match value {
+                "any" => set(token, key, &mut self.recurring, true),
+                "none" => set(token, key, &mut self.recurring, false),
+                _ => {
+                    let kind = ParseErrorKind::Unknown {
+                        what: "repeat",
+                        expected: "any or none",
+                    };
+                    Err(ParseError::new(token, value, kind))
+                }
+            },
+            "sort" => {
+                let (sort_key, order) = match value.strip_prefix('-') {
+                    Some(sort_key) => (sort_key, SortOrder::Descending),
+                    None => (value.trim_start_matches('+'), SortOrder::Ascending),
+                };
+                let sort_key = sort_key
+                    .parse()
+                    .map_err(|err: ParseError| err.within(token, sort_key))?;
+                set(token, key, &mut self.sort, (sort_key, order))
+            }
+            "limit" => {
+                let limit = parse_number(token, value)?;
+                set(token, key, &mut self.limit, limit)
+            }
+            "offset" => {
+                let offset = parse_number(token, value)?;
+                set(token, key, &mut self.offset, offset)
+            }
+            _ => {
+                let kind = ParseErrorKind::Unknown {
+                    what: "filter",
+                    expected: "a key like state, tag or due",
+                };
+                Err(ParseError::new(token, key, kind))
+            }
+        }
+    }
+}
+
+/// formats the query in the canonical form read by [`Query::parse`]
+///
+/// Relative dates are written as absolute dates.
+impl Display for Query<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+
+        if let Some(state) = self.state {
+            parts.push(format!("state:{}", state));
+        }
+
+        if let Some(entry_type) = self.entry_type {
+            parts.push(format!("type:{}", entry_type));
+        }
+
+        if let Some(prefix) = &self.prefix {
+            parts.push(format!("prefix:{}", quote(prefix, true)));
+        }
+
+        for tag in &self.tags {
+            parts.push(format!("tag:{}", tag));
+        }
+
+        for tag in &self.exclude_tags {
+            parts.push(format!("-tag:{}", tag));
+        }
+
+        let ranges = [
+            ("opened", self.opened),
+            ("closed", self.closed),
+            ("sched", self.scheduled),
+            ("due", self.deadline),
+            ("when", self.scheduled_or_deadline),
+        ];
+        for (key, range) in ranges {
+            if let Some(range) = range {
+                parts.push(format!("{}:{}", key, format_range(range)));
+            }
+        }
+
+        let exists = [
+            ("sched", self.has_scheduled),
+            ("due", self.has_deadline),
+            ("repeat", self.recurring),
+        ];
+        for (key, exists) in exists {
+            if let Some(exists) = exists {
+                let value = if exists { "any" } else { "none" };
+                parts.push(format!("{}:{}", key, value));
+            }
+        }
+
+        if let Some((key, order)) = self.sort {
+            let order = match order {
+                SortOrder::Ascending => "",
+                SortOrder::Descending => "-",
+            };
+            parts.push(format!("sort:{}{}", order, key));
+        }
+
+        if let Some(limit) = self.limit {
+            parts.push(format!("limit:{}", limit));
+        }
+
+        if let Some(offset) = self.offset {
+            parts.push(format!("offset:{}", offset));
+        }
+
+        for text in &self.text {
+            parts.push(quote(text, false));
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    fn day(s: &str) -> DateTimeRange {
+        DateTimeRange::day(at(s).date())
+    }
+
+    #[test]
+    fn test_parse() {
+        let query = Query::parse(
+            r#"state:open prefix:work/acme type:meeting due:<+7d sched:today..+2w "budget""#,
+        )
+        .unwrap();
+
+        let midnight = |date: Date| date.with_time(Time::MIN);
+
+        let expected = Query {
+            state: Some(EntryState::Open),
+            entry_type: Some(EntryType::Meeting),
+            prefix: Some("work/acme".into()),
+            text: vec!["budget".into()],
+            deadline: Some(DateTimeRange::before(midnight(today().add_days(7)))),
+            scheduled: Some(DateTimeRange::new(
+                midnight(today()),
+                midnight(today().add_days(14)),
+            )),
+            ..Default::default()
+        };
+        assert_eq!(expected, query);
+    }
+
+    #[test]
+    fn test_parse_values() {
+        let parse = |s| Query::parse(s).unwrap();
+
+        assert_eq!(Query::default(), parse(""));
+        assert_eq!(Query::default(), parse("   "));
+
+        assert_eq!(Some(EntryState::Closed), parse("state:closed").state);
+        assert_eq!(
+            Some("my project"),
+            parse(r#"prefix:"my project""#).prefix.as_deref()
+        );
+        assert_eq!(vec!["a b"], parse(r#""a b""#).text);
+        assert_eq!(vec!["a:b"], parse(r#"text:a:b"#).text);
+        assert_eq!(vec!["budget", "review"], parse("budget review").text);
+        assert_eq!(vec![r#"say "hi""#], parse(r#""say \"hi\"""#).text);
+        assert_eq!(vec![r"a\b"], parse(r#"text:"a\\b""#).text);
+
+        assert_eq!(Some(day("2025-03-01")), parse("opened:2025-03-01").opened);
+        assert_eq!(
+            Some(DateTimeRange::starting_at(at("2025-03-01 14:00"))),
+            parse(r#"closed:>="2025-03-01 14:00""#).closed
+        );
+        assert_eq!(
+            Some(DateTimeRange::new(at("2025-03-01"), at("2025-03-08"))),
+            parse("when:2025-03-01..2025-03-08").scheduled_or_deadline
+        );
+        assert_eq!(
+            Some(DateTimeRange::starting_at(at("2025-03-01"))),
+            parse("due:2025-03-01..").deadline
+        );
+
+        let query = parse("tag:urgent tag:@waiting -tag:client-x");
+        assert_eq!(vec!["urgent", "@waiting"], query.tags);
+        assert_eq!(vec!["client-x"], query.exclude_tags);
+
+        assert_eq!(Some(false), parse("due:none").has_deadline);
+        assert_eq!(Some(true), parse("sched:any").has_scheduled);
+        assert_eq!(Some(false), parse("repeat:none").recurring);
+
+        assert_eq!(
+            Some((SortKey::Deadline, SortOrder::Descending)),
+            parse("sort:-deadline").sort
+        );
+        assert_eq!(
+            Some((SortKey::Opened, SortOrder::Ascending)),
+            parse("sort:opened").sort
+        );
+
+        let query = parse("limit:10 offset:20");
+        assert_eq!((Some(10), Some(20)), (query.limit, query.offset));
+    }
+
+    #[test]
+    fn test_parse_fail() {
+        let error = |s| Query::parse(s).unwrap_err();
+
+        let err = error("state:open due:2025-13-01");
+        assert_eq!(20, err.position);
+        assert_eq!("13", err.token);
+        assert_eq!(
+            "month 13 out of range in 'state:open due:2025-13-01' at position 20",
+            err.to_string()
+        );
+
+        let err = error("prefix:work color:red");
+        assert_eq!(12, err.position);
+        assert_eq!("color", err.token);
+
+        let err = error(r#"prefix:work "budget"#);
+        assert_eq!(12, err.position);
+
+        assert!(Query::parse("state:done").is_err());
+        assert!(Query::parse("type:note").is_err());
+        assert!(Query::parse("sort:title").is_err());
+        let err = error("sort:-title");
+        assert_eq!((6, "title".into()), (err.position, err.token));
+        assert!(Query::parse("limit:-1").is_err());
+        assert!(Query::parse("state:open state:closed").is_err());
+        assert!(Query::parse("text:a text:b").is_ok());
+        assert!(Query::parse(r#""a\b""#).is_err());
+        assert!(Query::parse(r#""a"b""#).is_err());
+        assert!(Query::parse(r#"prefix:a"b"#).is_err());
+        assert!(Query::parse("tag:").is_err());
+
+        let err = error("due:2025-02-01..2025-01-01");
+        assert_eq!(ParseErrorKind::InvertedRange, err.kind);
+        assert_eq!(
+            (4, "2025-02-01..2025-01-01"),
+            (err.position, err.token.as_str())
+        );
+        assert!(Query::parse("when:+1d..today").is_err());
+        assert!(Query::parse("when:today..today").is_ok());
+    }
+
+    #[test]
+    fn test_quote_round_trip() {
+        // every string of up to 4 characters which need quoting or escaping
+        let mut strings = vec![String::new()];
+        for length in 0..4 {
+            let shorter: Vec<String> = strings
+                .iter()
+                .filter(|s| s.len() == length)
+                .cloned()
+                .collect();
+            for s in shorter {
+                for c in ['a', ' ', '"', '\\', ':'] {
+                    strings.push(format!("{}{}", s, c));
+                }
+            }
+        }
+
+        for s in &strings {
+            for allow_colon in [false, true] {
+                let quoted = quote(s, allow_colon);
+                assert_eq!(
+                    Ok(s.as_str().into()),
+                    unquote(&quoted, &quoted),
+                    "{}",
+                    quoted
+                );
+            }
+
+            let query = Query {
+                prefix: Some(s.as_str().into()),
+                text: vec![s.as_str().into(), "b".into()],
+                ..Default::default()
+            };
+            let formatted = query.to_string();
+            assert_eq!(Ok(query), Query::parse(&formatted), "{}", formatted);
+        }
+    }
+
+    #[test]
+    fn test_display_parse() {
+        let queries = [
+            Query::default(),
+            Query {
+                state: Some(EntryState::Closed),
+                entry_type: Some(EntryType::Todo),
+                prefix: Some("work/acme".into()),
+                tags: vec!["urgent", "@waiting"],
+                exclude_tags: vec!["client-x"],
+                text: vec!["budget".into()],
+                ..Default::default()
+            },
+            Query {
+                prefix: Some("my project".into()),
+                text: vec!["weekly sync: notes".into()],
+                opened: Some(day("2025-03-01")),
+                closed: Some(DateTimeRange::new(
+                    at("2025-03-01 12:30"),
+                    at("2025-03-02 08:00"),
+                )),
+                scheduled: Some(DateTimeRange::before(at("2025-04-01"))),
+                deadline: Some(DateTimeRange::starting_at(at("2025-04-01"))),
+                scheduled_or_deadline: Some(DateTimeRange::ALL),
+                ..Default::default()
+            },
+            Query {
+                has_scheduled: Some(true),
+                has_deadline: Some(false),
+                recurring: Some(true),
+                sort: Some((SortKey::Closed, SortOrder::Descending)),
+                limit: Some(10),
+                offset: Some(5),
+                ..Default::default()
+            },
+        ];
+
+        for query in queries {
+            let s = query.to_string();
+            assert_eq!(query, Query::parse(&s).unwrap(), "{}", s);
+        }
+
+        let s = "state:open prefix:work due:<2025-03-08 sort:deadline budget";
+        assert_eq!(s, Query::parse(s).unwrap().to_string());
+    }
+}
@@ -1,5 +1,6 @@
 use super::prelude::*;
-use std::{cmp::Ordering, fmt::Display, str::FromStr};
+use super::{ParseError, ParseErrorKind, iso, relative};
+use std::{cmp::Ordering, fmt::Display, ops::Sub, str::FromStr};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Date {
@@ -14,33 +15,37 @@ impl AsRef<Date> for Date {
     }
 }
 
-fn resolve_date(year: u32, month: i32, day: i32) -> Date {
-    if month < 1 {
-        return resolve_date(year - 1, month + 12, day);
+pub(crate) const MINUTES_PER_DAY: i64 = 24 * 60;
+
+/// the date `day - 1` days after the first of `month` in `year`, months outside of `1..=12` carry
+/// over into the neighbouring years
+///
+/// # Returns
+/// - `None` if the date is before [`Date::MIN`] or after [`Date::MAX`]
+fn resolve_date(year: i64, month: i64, day: i64) -> Option<Date> {
+    let year = year + (month - 1).div_euclid(12);
+    let month = (month - 1).rem_euclid(12) + 1;
+    if !(Date::MIN.year() as i64..=Date::MAX.year() as i64).contains(&year) {
+        return None;
     }
-    if month > 12 {
-        return resolve_date(year + 1, month - 12, day);
-    }
-
-    let mlen = month_len(year, month as u32);
-    let prev_mlen = if month == 1 {
-        31
-    } else {
-        month_len(year, month as u32 - 1)
-    };
 
-    let mlen = mlen as i32;
-    let prev_mlen = prev_mlen as i32;
-
-    if day < 1 {
-        return resolve_date(year, month - 1, day + prev_mlen);
-    }
+    let first = Date::from_ymd(year as u32, month as u32, 1)?;
+    Date::from_day_number(first.day_number() as i64 + day - 1)
+}
 
-    if day > mlen {
-        return resolve_date(year, month + 1, day - mlen);
-    }
+/// the number of days from 0000-01-01 to the first of january of `year`
+fn days_before_year(year: u32) -> u32 {
+    let leap_years = match year {
+        0 => 0,
+        year => (year - 1) / 4 - (year - 1) / 100 + (year - 1) / 400 + 1,
+    };
+    365 * year + leap_years
+}
 
-    Date::from_ymd(year, month as u32, day as u32).unwrap()
+/// the bound dates moved out of range are clamped to, [`Date::MAX`] if `forward`, otherwise
+/// [`Date::MIN`]
+fn bound(forward: bool) -> Date {
+    if forward { Date::MAX } else { Date::MIN }
 }
 
 impl DateLike for Date {
@@ -56,28 +61,24 @@ impl DateLike for Date {
         self.day as u32
     }
 
+    /// adds `days`, clamped to [`Date::MIN`] and [`Date::MAX`], see [`Date::checked_add_days`]
     fn add_days(&self, days: i32) -> Self {
-        resolve_date(self.year(), self.month() as i32, self.day() as i32 + days)
+        self.checked_add_days(days).unwrap_or(bound(days > 0))
     }
 
     fn add_months(&self, months: i32) -> Self {
-        resolve_date(self.year(), self.month() as i32 + months, self.day() as i32)
+        self.checked_add_months(months).unwrap_or(bound(months > 0))
     }
 
     fn add_years(&self, years: i32) -> Self {
-        let year = self.year() as i32;
-        let year = year + years;
-        let year = year as u32;
-        resolve_date(year, self.month() as i32, self.day() as i32)
+        self.checked_add_years(years).unwrap_or(bound(years > 0))
     }
 
+    /// adds `duration`, hours and minutes only count in whole days, see
+    /// [`Date::checked_add_duration`]
     fn add_duration(&self, duration: Duration) -> Self {
-        match duration {
-            Duration::Day(x) => self.add_days(x),
-            Duration::Week(x) => self.add_days(x * 2),
-            Duration::Month(x) => self.add_months(x),
-            Duration::Year(x) => self.add_years(x),
-        }
+        self.checked_add_duration(duration)
+            .unwrap_or_else(|| bound(duration.is_forward()))
     }
 
     fn with_time(&self, time: Time) -> DateTime {
@@ -135,11 +136,246 @@ impl DateLike for Date {
     fn year_end(&self) -> Self {
         self.with_month(12).unwrap().with_day(31).unwrap()
     }
+
+    fn weekday(&self) -> Weekday {
+        // 0000-01-01 of the proleptic gregorian calendar is a saturday
+        Weekday::ALL[((self.day_number() + 5) % 7) as usize]
+    }
+
+    fn iso_week(&self) -> (u32, u32) {
+        let day = self.day_of_year() as i32;
+        let weekday = self.weekday().number_from_monday() as i32;
+        let week = (day - weekday + 10) / 7;
+
+        if week < 1 {
+            return match self.year().checked_sub(1) {
+                Some(year) => (year, iso_weeks_in_year(year)),
+                None => (0, 0),
+            };
+        }
+
+        if week as u32 > iso_weeks_in_year(self.year()) {
+            return (self.year() + 1, 1);
+        }
+
+        (self.year(), week as u32)
+    }
+
+    fn day_of_year(&self) -> u32 {
+        let before: u32 = (1..self.month())
+            .map(|month| month_len(self.year(), month))
+            .sum();
+        before + self.day()
+    }
+
+    fn week_begin(&self, first: Weekday) -> Self {
+        self.add_days(-(self.weekday().days_since(first) as i32))
+    }
+
+    fn week_end(&self, first: Weekday) -> Self {
+        self.week_begin(first).add_days(6)
+    }
+}
+
+/// the number of ISO weeks in `year`, `53` if it starts on a thursday or is a leap year
+/// starting on a wednesday, otherwise `52`
+fn iso_weeks_in_year(year: u32) -> u32 {
+    let first = Date::from_ymd(year, 1, 1).unwrap().weekday();
+    match first {
+        Weekday::Thursday => 53,
+        Weekday::Wednesday if is_leap_year(year) => 53,
+        _ => 52,
+    }
 }
 
 impl Date {
+    /// the earliest representable date
+    pub const MIN: Date = Date {
+        year: 0,
+        month: 1,
+        day: 1,
+    };
+
+    /// the latest representable date
+    pub const MAX: Date = Date {
+        year: 9999,
+        month: 12,
+        day: 31,
+    };
+
+    /// the date of `weekday` in the ISO week `week` of `year`, see [`DateLike::iso_week`]
+    ///
+    /// # Returns
+    /// - `None` if `year` does not have the given week or the date is after [`Date::MAX`]
+    pub fn from_iso_week(year: u32, week: u32, weekday: Weekday) -> Option<Self> {
+        // the 4th of january is always in the first week
+        let first_monday = Date::from_ymd(year, 1, 4)?.week_begin(Weekday::Monday);
+        if !(1..=iso_weeks_in_year(year)).contains(&week) {
+            return None;
+        }
+
+        let days = (week - 1) * 7 + weekday.number_from_monday() - 1;
+        first_monday.checked_add_days(days as i32)
+    }
+
+    /// the number of days since 0000-01-01
+    fn day_number(&self) -> u32 {
+        days_before_year(self.year()) + self.day_of_year() - 1
+    }
+
+    /// the date `number` days after 0000-01-01, `None` if it is after [`Date::MAX`] or `number`
+    /// is negative
+    fn from_day_number(number: i64) -> Option<Self> {
+        if !(0..=Date::MAX.day_number() as i64).contains(&number) {
+            return None;
+        }
+        let number = number as u32;
+
+        // every 400 years have 146097 days, which estimates the year closely
+        let mut year = number * 400 / 146097;
+        while days_before_year(year) > number {
+            year -= 1;
+        }
+        while days_before_year(year + 1) <= number {
+            year += 1;
+        }
+
+        let mut day = number - days_before_year(year) + 1;
+        let mut month = 1;
+        while day > month_len(year, month) {
+            day -= month_len(year, month);
+            month += 1;
+        }
+        Date::from_ymd(year, month, day)
+    }
+
+    /// adds `days`, `None` if the result is before [`Date::MIN`] or after [`Date::MAX`]
+    pub fn checked_add_days(&self, days: i32) -> Option<Self> {
+        resolve_date(
+            self.year() as i64,
+            self.month() as i64,
+            self.day() as i64 + days as i64,
+        )
+    }
+
+    /// adds `months`, days past the end of the month carry over into the next one like
+    /// 2025-01-31 plus one month is 2025-03-03
+    ///
+    /// # Returns
+    /// - `None` if the result is before [`Date::MIN`] or after [`Date::MAX`]
+    pub fn checked_add_months(&self, months: i32) -> Option<Self> {
+        resolve_date(
+            self.year() as i64,
+            self.month() as i64 + months as i64,
+            self.day() as i64,
+        )
+    }
+
+    /// adds `years`, the 29th of february carries over into march in other years
+    ///
+    /// # Returns
+    /// - `None` if the result is before [`Date::MIN`] or after [`Date::MAX`]
+    pub fn checked_add_years(&self, years: i32) -> Option<Self> {
+        resolve_date(
+            self.year() as i64 + years as i64,
+            self.month() as i64,
+            self.day() as i64,
+        )
+    }
+
+    /// adds `duration` from the largest unit to the smallest, hours and minutes only count in
+    /// whole days
+    ///
+    /// # Returns
+    /// - `None` if the result or a step on the way is before [`Date::MIN`] or after [`Date::MAX`]
+    pub fn checked_add_duration(&self, duration: Duration) -> Option<Self> {
+        let [years, months, weeks, days, ..] = duration.parts();
+        let days = days as i64 + weeks as i64 * 7 + duration.clock_minutes() / MINUTES_PER_DAY;
+        self.checked_add_years(years)?
+            .checked_add_months(months)?
+            .checked_add_days(i32::try_from(days).ok()?)
+    }
+
+    /// the number of days from this date to `other`, negative if `other` is earlier
+    pub fn days_until(&self, other: Date) -> i32 {
+        other.day_number() as i32 - self.day_number() as i32
+    }
+
+    /// the time from this date to `other` in a single unit, see [`DateTime::until`]
+    pub fn until(&self, other: Date) -> Duration {
+        self.with_time(Time::MIN).until(other.with_time(Time::MIN))
+    }
+
+    /// the time from `other` to this date in a single unit, see [`DateTime::until`]
+    pub fn since(&self, other: Date) -> Duration {
+        other.until(*self)
+    }
+
+    /// parses a date, relative dates are resolved against `today`
+    ///
+    /// Besides ISO dates, see [`Date::parse_iso`], this understands
+    /// - `today`, `yesterday` and `tomorrow`
+    /// - a duration like `+3d` or `-1w`
+    /// - a weekday like `fri`, the next one or today, `next fri`, `last fri` and `this fri`
+    /// - `in 2 weeks`, `in 3d` and `3 days ago`
+    /// - `next week`, `last month` and alike
+    /// - `end of month`, `start of next week` and alike, weeks start on monday
+    /// - `first monday of next month` up to `fifth`, and `last friday of month`
+    /// - a date followed by durations like `2025-03-01+1w` or `fri-2d`
+    ///
+    /// Besides the english words those of [`Locale::current`] are understood, see
+    /// [`Date::parse_localized`].
+    ///
+    /// ```
+    /// use koi::time::Date;
+    ///
+    /// let today = Date::from_ymd(2025, 3, 1).unwrap();
+    /// let date = Date::parse_relative("first monday of next month", today);
+    /// assert_eq!(Date::from_ymd(2025, 4, 7), date);
+    /// ```
+    pub fn parse_relative(s: &str, today: Date) -> Option<Self> {
+        Self::parse_localized(s, today, Locale::current())
+    }
+
+    /// parses a date like [`Date::parse_relative`], understanding the words of `locale` like
+    /// `morgen` or `nächsten montag` in german besides the english ones
+    pub fn parse_localized(s: &str, today: Date, locale: Locale) -> Option<Self> {
+        relative::parse_date(s, today, locale)
+    }
+
+    /// parses an ISO 8601 date, a calendar date like `2025-03-01`, a week date like `2025-W09-6`
+    /// or `2025-W09` for its monday, or an ordinal date like `2025-060`, each also without dashes
+    pub fn parse_iso(s: &str) -> Result<Self, ParseError> {
+        iso::parse_date(s, s.trim())
+    }
+
+    /// formats as ISO 8601 calendar date like `2025-03-01`, the same as [`Display`]
+    pub fn format_iso(&self) -> String {
+        self.to_string()
+    }
+
+    /// formats as ISO 8601 calendar date without dashes like `20250301`
+    pub fn format_iso_basic(&self) -> String {
+        format!("{:>04}{:>02}{:>02}", self.year(), self.month(), self.day())
+    }
+
+    /// formats as ISO 8601 week date like `2025-W09-6`, the year is the one of the week, see
+    /// [`DateLike::iso_week`]
+    pub fn format_iso_week(&self) -> String {
+        let (year, week) = self.iso_week();
+        let weekday = self.weekday().number_from_monday();
+        format!("{:>04}-W{:>02}-{}", year, week, weekday)
+    }
+
+    /// formats as ISO 8601 ordinal date like `2025-060`
+    pub fn format_iso_ordinal(&self) -> String {
+        format!("{:>04}-{:>03}", self.year(), self.day_of_year())
+    }
+
+    /// # Returns
+    /// - `None` if the date does not exist or its year is after the one of [`Date::MAX`]
     pub fn from_ymd(year: u32, month: u32, day: u32) -> Option<Self> {
-        if is_valid_date(year, month, day) {
+        if year <= Date::MAX.year() && is_valid_date(year, month, day) {
             Some(Self {
                 year: year as u16,
                 month: month as u8,
@@ -163,35 +399,66 @@ impl Display for Date {
     }
 }
 
-impl FromStr for Date {
-    type Err = ();
+/// explains why `s` is no date, see [`Date::parse_relative`]
+pub(crate) fn parse_error(s: &str, today: Date) -> ParseError {
+    let trimmed = s.trim();
+    if trimmed.is_empty() {
+        return ParseError::new(s, trimmed, ParseErrorKind::Empty);
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.trim();
+    if trimmed.starts_with(|c: char| c.is_ascii_digit())
+        && trimmed
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '-' | 'W' | 'w'))
+        && let Err(err) = iso::parse_date(s, trimmed)
+    {
+        return err;
+    }
 
-        match s {
-            "today" => return Ok(today()),
-            "yesterday" => return Ok(today().add_days(-1)),
-            "tomorrow" => return Ok(today().add_days(1)),
-            _ => (),
+    let out_of_range = || {
+        let expected = "a date between 0000-01-01 and 9999-12-31";
+        ParseError::new(s, trimmed, ParseErrorKind::Expected(expected))
+    };
+
+    if trimmed.starts_with(['+', '-']) {
+        return match trimmed.parse::<Duration>() {
+            Ok(_) => out_of_range(),
+            Err(err) => err.within(s, trimmed),
         };
+    }
 
-        if let Ok(duration) = s.parse::<Duration>() {
-            return Ok(today().add_duration(duration));
-        }
+    // a date followed by a broken duration like `fri+1x`, or one which leaves the range of dates
+    if let Some(i) = trimmed.rfind(['+', '-'])
+        && Date::parse_relative(&trimmed[..i], today).is_some()
+    {
+        return match trimmed[i..].parse::<Duration>() {
+            Ok(_) => out_of_range(),
+            Err(err) => err.within(s, &trimmed[i..]),
+        };
+    }
+
+    let expected = "a date like 2025-03-01, today, fri, +3d or next monday";
+    ParseError::new(s, trimmed, ParseErrorKind::Expected(expected))
+}
+
+impl FromStr for Date {
+    type Err = ParseError;
 
-        let mut parts = s.split("-").map(|s| s.trim());
-        let year = parts.next().ok_or(())?;
-        let month = parts.next().ok_or(())?;
-        let day = parts.next().ok_or(())?;
+    /// parses a date relative to [`today`], see [`Date::parse_relative`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let today = today();
+        Self::parse_relative(s, today).ok_or_else(|| parse_error(s, today))
+    }
+}
 
-        let year = year.parse().map_err(|_| ())?;
-        let month = month.parse().map_err(|_| ())?;
-        let day = day.parse().map_err(|_| ())?;
+serde_with_str!(Date, Date::parse_iso);
 
-        let date = Date::from_ymd(year, month, day).ok_or(())?;
+impl Sub for Date {
+    type Output = Duration;
 
-        Ok(date)
+    /// the days from `other` to this date
+    fn sub(self, other: Self) -> Self::Output {
+        Duration::Day(other.days_until(self))
     }
 }
 
@@ -209,3 +476,236 @@ impl Ord for Date {
         year.then(month).then(day)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_weekday() {
+        assert_eq!(Weekday::Saturday, date("0000-01-01").weekday());
+        assert_eq!(Weekday::Saturday, date("2000-01-01").weekday());
+        assert_eq!(Weekday::Saturday, date("2025-03-01").weekday());
+        assert_eq!(Weekday::Monday, date("2025-03-03").weekday());
+        assert_eq!(Weekday::Thursday, date("2026-01-01").weekday());
+        assert_eq!(Weekday::Friday, date("9999-12-31").weekday());
+
+        let mut day = date("2024-02-26");
+        for weekday in Weekday::ALL.iter().cycle().take(30) {
+            assert_eq!(*weekday, day.weekday());
+            day = day.next_day();
+        }
+    }
+
+    #[test]
+    fn test_day_of_year() {
+        assert_eq!(1, date("2025-01-01").day_of_year());
+        assert_eq!(60, date("2025-03-01").day_of_year());
+        assert_eq!(61, date("2024-03-01").day_of_year());
+        assert_eq!(366, date("2024-12-31").day_of_year());
+    }
+
+    #[test]
+    fn test_iso_week() {
+        assert_eq!((2025, 1), date("2024-12-30").iso_week());
+        assert_eq!((2025, 9), date("2025-03-01").iso_week());
+        assert_eq!((2020, 53), date("2020-12-31").iso_week());
+        assert_eq!((2020, 53), date("2021-01-03").iso_week());
+        assert_eq!((2021, 1), date("2021-01-04").iso_week());
+        assert_eq!((2026, 53), date("2027-01-01").iso_week());
+        assert_eq!((0, 0), date("0000-01-01").iso_week());
+    }
+
+    #[test]
+    fn test_from_iso_week() {
+        assert_eq!(
+            Some(date("2024-12-30")),
+            Date::from_iso_week(2025, 1, Weekday::Monday)
+        );
+        assert_eq!(
+            Some(date("2021-01-03")),
+            Date::from_iso_week(2020, 53, Weekday::Sunday)
+        );
+        assert_eq!(None, Date::from_iso_week(2021, 53, Weekday::Monday));
+        assert_eq!(None, Date::from_iso_week(2025, 0, Weekday::Monday));
+        assert_eq!(None, Date::from_iso_week(10000, 1, Weekday::Monday));
+
+        let mut day = date("2019-12-01");
+        while day < date("2022-02-01") {
+            let (year, week) = day.iso_week();
+            assert_eq!(Some(day), Date::from_iso_week(year, week, day.weekday()));
+            day = day.next_day();
+        }
+    }
+
+    #[test]
+    fn test_week_begin_end() {
+        let day = date("2025-03-05");
+        assert_eq!(date("2025-03-03"), day.week_begin(Weekday::Monday));
+        assert_eq!(date("2025-03-09"), day.week_end(Weekday::Monday));
+        assert_eq!(date("2025-03-02"), day.week_begin(Weekday::Sunday));
+        assert_eq!(date("2025-03-08"), day.week_end(Weekday::Sunday));
+        assert_eq!(day, day.week_begin(Weekday::Wednesday));
+        assert_eq!(date("2025-02-27"), day.week_begin(Weekday::Thursday));
+    }
+
+    #[test]
+    fn test_days_until() {
+        assert_eq!(2, date("2025-02-27").days_until(date("2025-03-01")));
+        assert_eq!(3, date("2024-02-27").days_until(date("2024-03-01")));
+        assert_eq!(-365, date("2026-01-01").days_until(date("2025-01-01")));
+        assert_eq!(0, date("2025-03-01").days_until(date("2025-03-01")));
+
+        assert_eq!(Duration::Day(2), date("2025-03-01") - date("2025-02-27"));
+        assert_eq!(Duration::Day(-2), date("2025-02-27") - date("2025-03-01"));
+
+        assert_eq!(
+            Duration::Week(3),
+            date("2025-03-01").until(date("2025-03-25"))
+        );
+        assert_eq!(
+            Duration::Day(-3),
+            date("2025-03-01").since(date("2025-03-04"))
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = |s: &str| s.parse::<Date>().unwrap_err();
+        let out_of_range = |field, value| ParseErrorKind::OutOfRange { field, value };
+
+        let err = error("2025-13-01");
+        assert_eq!((5, out_of_range("month", 13)), (err.position, err.kind));
+        assert_eq!(
+            "month 13 out of range in '2025-13-01' at position 5",
+            error("2025-13-01").to_string()
+        );
+
+        let err = error("2025-02-29");
+        assert_eq!((8, out_of_range("day", 29)), (err.position, err.kind));
+
+        let err = error(" fri+1x");
+        assert_eq!((6, ParseErrorKind::UnknownUnit), (err.position, err.kind));
+
+        let err = error("2025-W54");
+        assert_eq!((6, out_of_range("week", 54)), (err.position, err.kind));
+
+        assert_eq!(ParseErrorKind::Empty, error(" ").kind);
+        assert!(matches!(error("2025-03").kind, ParseErrorKind::Expected(_)));
+        assert!(matches!(error("someday").kind, ParseErrorKind::Expected(_)));
+    }
+
+    #[test]
+    fn test_iso() {
+        let day = date("2025-03-01");
+        assert_eq!(Ok(day), Date::parse_iso("2025-03-01"));
+        assert_eq!(Ok(day), Date::parse_iso(" 2025-W09-6 "));
+        assert_eq!(Ok(day), Date::parse_iso("2025060"));
+        assert!(Date::parse_iso("today").is_err());
+
+        assert_eq!(day, date("2025-W09-6"));
+        assert_eq!(day, date("2025-060"));
+        assert_eq!(day.add_days(7), date("2025-w09-6+1w"));
+
+        assert_eq!("2025-03-01", day.format_iso());
+        assert_eq!("20250301", day.format_iso_basic());
+        assert_eq!("2025-W09-6", day.format_iso_week());
+        assert_eq!("2025-060", day.format_iso_ordinal());
+        assert_eq!("2026-W01-3", date("2025-12-31").format_iso_week());
+
+        let mut day = date("2024-12-01");
+        for _ in 0..100 {
+            assert_eq!(Ok(day), Date::parse_iso(&day.format_iso_week()));
+            assert_eq!(Ok(day), Date::parse_iso(&day.format_iso_ordinal()));
+            assert_eq!(Ok(day), Date::parse_iso(&day.format_iso_basic()));
+            day = day.next_day();
+        }
+    }
+
+    #[test]
+    fn test_add_duration() {
+        let date = Date::from_ymd(2025, 3, 1).unwrap();
+        assert_eq!(
+            Date::from_ymd(2025, 3, 15).unwrap(),
+            date.add_duration(Duration::Week(2))
+        );
+        assert_eq!(
+            Date::from_ymd(2025, 2, 22).unwrap(),
+            date.add_duration(Duration::Week(-1))
+        );
+    }
+
+    #[test]
+    fn test_day_number() {
+        for number in (0..=Date::MAX.day_number()).step_by(97) {
+            let date = Date::from_day_number(number as i64).unwrap();
+            assert_eq!(number, date.day_number());
+        }
+        assert_eq!(
+            Some(Date::MAX),
+            Date::from_day_number(Date::MAX.day_number() as i64)
+        );
+        assert_eq!(
+            None,
+            Date::from_day_number(Date::MAX.day_number() as i64 + 1)
+        );
+        assert_eq!(None, Date::from_day_number(-1));
+    }
+
+    #[test]
+    fn test_bounds() {
+        assert_eq!(Some(Date::MAX), Date::from_ymd(9999, 12, 31));
+        assert_eq!(None, Date::from_ymd(10000, 1, 1));
+        assert_eq!(None, Date::from_ymd(65536 + 2025, 3, 1));
+        assert_eq!(None, Date::MAX.with_year(10000));
+        assert_eq!(None, Date::MIN.checked_add_days(-1));
+        assert_eq!(None, Date::MAX.checked_add_days(1));
+        assert_eq!(None, Date::MAX.checked_add_months(1));
+        assert_eq!(None, date("2025-03-01").checked_add_years(-3000));
+        assert_eq!(None, date("2025-03-01").checked_add_days(i32::MAX));
+        assert_eq!(None, date("2025-03-01").checked_add_days(i32::MIN));
+        assert_eq!(Some(date("0000-01-02")), Date::MIN.checked_add_days(1));
+        assert_eq!(Some(date("9999-12-30")), Date::MAX.checked_add_days(-1));
+        assert_eq!(
+            Some(date("2025-03-03")),
+            date("2025-01-31").checked_add_months(1)
+        );
+
+        assert_eq!(Date::MIN, Date::MIN.prev_day());
+        assert_eq!(Date::MAX, Date::MAX.next_day());
+        assert_eq!(Date::MIN, date("2025-03-01").add_years(-3000));
+        assert_eq!(
+            Date::MAX,
+            date("2025-03-01").add_duration(Duration::Day(i32::MAX))
+        );
+        assert_eq!(
+            Date::MIN,
+            date("2025-03-01").add_duration(Duration::Week(i32::MIN))
+        );
+
+        let error = |s: &str| s.parse::<Date>().unwrap_err();
+        for s in [
+            "0000-01-01-1d",
+            "9999-12-31+1d",
+            "+2147483647d",
+            "-3000y",
+            "-2147483647w",
+        ] {
+            assert!(
+                matches!(error(s).kind, ParseErrorKind::Expected(_)),
+                "{}",
+                s
+            );
+        }
+        assert_eq!(
+            "expected a date between 0000-01-01 and 9999-12-31, found '9999-12-31+1d'",
+            error("9999-12-31+1d").to_string()
+        );
+        assert_eq!(date("9999-12-31"), date("9999-12-30+1d"));
+        assert_eq!(date("0000-01-01"), date("0000-01-02-1d"));
+    }
+}
@@ -1,24 +1,8 @@
-use std::{cmp::Ordering, fmt::Display, str::FromStr};
-
-use rusqlite::{ToSql, types::FromSql};
+use std::{cmp::Ordering, fmt::Display, ops::Sub, str::FromStr};
 
+use super::date::{self, MINUTES_PER_DAY};
 use super::prelude::*;
-
-impl FromSql for DateTime {
-    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
-        <String as FromSql>::column_result(value)?
-            .parse()
-            .map_err(|_| rusqlite::types::FromSqlError::InvalidType)
-    }
-}
-
-impl ToSql for DateTime {
-    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
-        Ok(rusqlite::types::ToSqlOutput::Owned(
-            rusqlite::types::Value::Text(self.to_string()),
-        ))
-    }
-}
+use super::{ParseError, iso};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct DateTime {
@@ -46,7 +30,7 @@ impl DateLike for DateTime {
     }
 
     fn month(&self) -> u32 {
-        self.date.year()
+        self.date.month()
     }
 
     fn day(&self) -> u32 {
@@ -125,16 +109,75 @@ impl DateLike for DateTime {
         self.date().year_end().with_time(self.time())
     }
 
+    /// adds `duration`, clamped to [`DateTime::MIN`] and [`DateTime::MAX`], see
+    /// [`DateTime::checked_add_duration`]
     fn add_duration(&self, duration: Duration) -> Self {
-        self.date().add_duration(duration).with_time(self.time())
+        self.checked_add_duration(duration)
+            .unwrap_or(if duration.is_forward() {
+                DateTime::MAX
+            } else {
+                DateTime::MIN
+            })
+    }
+
+    fn weekday(&self) -> Weekday {
+        self.date.weekday()
+    }
+
+    fn iso_week(&self) -> (u32, u32) {
+        self.date.iso_week()
+    }
+
+    fn day_of_year(&self) -> u32 {
+        self.date.day_of_year()
+    }
+
+    fn week_begin(&self, first: Weekday) -> Self {
+        self.date().week_begin(first).with_time(self.time())
+    }
+
+    fn week_end(&self, first: Weekday) -> Self {
+        self.date().week_end(first).with_time(self.time())
     }
 }
 
 impl DateTime {
+    /// the earliest representable date time, useful as an open lower bound
+    pub const MIN: DateTime = DateTime {
+        date: Date::MIN,
+        time: Time::MIN,
+    };
+
+    /// the latest representable date time, useful as an open upper bound
+    pub const MAX: DateTime = DateTime {
+        date: Date::MAX,
+        time: Time::MAX,
+    };
+
     pub fn new(date: Date, time: Time) -> Self {
         Self { date, time }
     }
 
+    /// adds `duration`, hours and minutes carry over into days after the calendar units are added
+    ///
+    /// # Returns
+    /// - `None` if the result or a step on the way is before [`DateTime::MIN`] or after
+    ///   [`DateTime::MAX`]
+    pub fn checked_add_duration(&self, duration: Duration) -> Option<Self> {
+        let [years, months, weeks, days, ..] = duration.parts();
+        let date = self
+            .date()
+            .checked_add_years(years)?
+            .checked_add_months(months)?
+            .checked_add_days(i32::try_from(weeks as i64 * 7 + days as i64).ok()?)?;
+
+        let minutes = (self.hour() * 60 + self.minute()) as i64 + duration.clock_minutes();
+        let days = i32::try_from(minutes.div_euclid(MINUTES_PER_DAY)).ok()?;
+        let minutes = minutes.rem_euclid(MINUTES_PER_DAY) as u32;
+        let time = Time::from_hm(minutes / 60, minutes % 60).expect("minutes are within a day");
+        Some(date.checked_add_days(days)?.with_time(time))
+    }
+
     pub fn date(&self) -> Date {
         self.date
     }
@@ -142,6 +185,129 @@ impl DateTime {
     pub fn time(&self) -> Time {
         self.time
     }
+
+    /// the number of minutes from this date time to `other`, negative if `other` is earlier
+    pub fn minutes_between(&self, other: DateTime) -> i64 {
+        let minutes = |datetime: &DateTime| (datetime.hour() * 60 + datetime.minute()) as i64;
+        self.date().days_until(other.date()) as i64 * MINUTES_PER_DAY + minutes(&other)
+            - minutes(self)
+    }
+
+    /// the number of whole months from this date time to `other`, which must not be earlier
+    fn months_until(&self, other: DateTime) -> i32 {
+        let months = |datetime: &DateTime| (datetime.year() * 12 + datetime.month()) as i32;
+        let months = months(&other) - months(self);
+        if self.add_months(months) > other {
+            months - 1
+        } else {
+            months
+        }
+    }
+
+    /// the time from this date time to `other` in a single unit, rounded towards zero, negative if
+    /// `other` is earlier
+    ///
+    /// The unit is the largest one that fits, except that days are used up to two weeks and weeks
+    /// up to a month, like `45min`, `3h`, `10d`, `2w`, `5m` or `2y`.
+    pub fn until(&self, other: DateTime) -> Duration {
+        if other < *self {
+            return other.until(*self) * -1;
+        }
+
+        let minutes = self.minutes_between(other);
+        let days = (minutes / MINUTES_PER_DAY) as i32;
+        let months = self.months_until(other);
+        if minutes < 60 {
+            Duration::Minute(minutes as i32)
+        } else if minutes < MINUTES_PER_DAY {
+            Duration::Hour(minutes as i32 / 60)
+        } else if days < 14 {
+            Duration::Day(days)
+        } else if months < 1 {
+            Duration::Week(days / 7)
+        } else if months < 12 {
+            Duration::Month(months)
+        } else {
+            Duration::Year(months / 12)
+        }
+    }
+
+    /// the time from `other` to this date time in a single unit, see [`DateTime::until`]
+    pub fn since(&self, other: DateTime) -> Duration {
+        other.until(*self)
+    }
+
+    /// parses `now` or a date optionally followed by a time like `fri 14:00` or `fri 2:30 pm`,
+    /// relative dates are resolved against `now`, see [`Date::parse_relative`]
+    ///
+    /// Without a time the date time is at the start of the day, unless it is a duration with hours
+    /// or minutes like `+2h` which is added to `now`.
+    pub fn parse_relative(s: &str, now: DateTime) -> Option<Self> {
+        Self::parse_localized(s, now, Locale::current())
+    }
+
+    /// parses a date time like [`DateTime::parse_relative`], understanding the words of `locale`
+    /// like `morgen um 14:00` in german besides the english ones, see [`Date::parse_localized`]
+    pub fn parse_localized(s: &str, now: DateTime, locale: Locale) -> Option<Self> {
+        let s = s.trim();
+        if locale.translate(vec![&s.to_lowercase()]) == ["now"] {
+            return Some(now);
+        }
+
+        if let Ok(duration) = s.parse::<Duration>()
+            && duration.clock_minutes() != 0
+        {
+            return now.checked_add_duration(duration);
+        }
+
+        if let Some((date, time)) = split_time(s) {
+            return Some(Date::parse_localized(date, now.date(), locale)?.with_time(time));
+        }
+
+        Some(Date::parse_localized(s, now.date(), locale)?.with_time(Time::MIN))
+    }
+
+    /// parses an ISO 8601 or RFC 3339 date time like `2025-03-01T14:30:00+02:00`, see
+    /// [`ZonedDateTime::parse_iso`]
+    ///
+    /// Date times with an offset are converted to the wall clock time in [`Zone::Local`].
+    pub fn parse_iso(s: &str) -> Result<Self, ParseError> {
+        Ok(ZonedDateTime::parse_iso(s)?.in_zone(Zone::Local))
+    }
+
+    /// formats as ISO 8601 date time without offset like `2025-03-01T14:30:00`
+    pub fn format_iso(&self) -> String {
+        format!("{}T{}", self.date().format_iso(), self.time().format_iso())
+    }
+
+    /// formats as RFC 3339 date time with the offset of [`Zone::Local`] like
+    /// `2025-03-01T14:30:00+01:00`
+    pub fn format_rfc3339(&self) -> String {
+        ZonedDateTime::new(*self, Zone::Local).format_rfc3339()
+    }
+
+    pub(crate) fn to_chrono(self) -> chrono::NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(self.year() as i32, self.month(), self.day())
+            .and_then(|date| date.and_hms_opt(self.hour(), self.minute(), 0))
+            .expect("every date time is a valid chrono date time")
+    }
+
+    /// converts a chrono date time, dropping seconds
+    ///
+    /// # Returns
+    /// - `None` if the date time is outside of [`DateTime::MIN`] and [`DateTime::MAX`]
+    pub(crate) fn from_chrono(datetime: chrono::NaiveDateTime) -> Option<Self> {
+        use chrono::{Datelike, Timelike};
+
+        let year: u32 = datetime.year().try_into().ok()?;
+        if year > Date::MAX.year() {
+            return None;
+        }
+
+        let date = Date::from_ymd(year, datetime.month(), datetime.day())?;
+        let time = Time::from_hm(datetime.hour(), datetime.minute())?;
+        Some(date.with_time(time))
+    }
 }
 
 impl AsRef<Time> for DateTime {
@@ -162,20 +328,73 @@ impl Display for DateTime {
     }
 }
 
+/// splits a trailing time like `14:00` or `2:30 pm` off `s`
+fn split_time(s: &str) -> Option<(&str, Time)> {
+    let (rest, last) = s.rsplit_once(char::is_whitespace)?;
+    if let Ok(time) = last.parse() {
+        return Some((rest, time));
+    }
+
+    // a time with a separate `am` or `pm`
+    let (date, _) = rest.trim_end().rsplit_once(char::is_whitespace)?;
+    let time = s[date.len()..].parse().ok()?;
+    Some((date, time))
+}
+
+/// explains why `s` is no date time, see [`DateTime::parse_relative`]
+fn parse_error(s: &str, now: DateTime) -> ParseError {
+    let trimmed = s.trim();
+
+    if iso::is_datetime_like(trimmed)
+        && let Err(err) = iso::parse_datetime(s)
+    {
+        return err;
+    }
+
+    if let Some((date, time)) = trimmed.rsplit_once(char::is_whitespace)
+        && time.contains(':')
+    {
+        if let Err(err) = time.parse::<Time>() {
+            return err.within(s, time);
+        }
+        return date::parse_error(date, now.date()).within(s, date);
+    }
+
+    date::parse_error(trimmed, now.date()).within(s, trimmed)
+}
+
 impl FromStr for DateTime {
-    type Err = ();
+    type Err = ParseError;
 
+    /// parses a date time relative to [`now`], see [`DateTime::parse_relative`], or an ISO date
+    /// time, see [`DateTime::parse_iso`]
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.trim();
-        if s == "now" {
-            return Ok(now());
+        let now = now();
+        if let Some(datetime) = Self::parse_relative(s, now) {
+            return Ok(datetime);
         }
+        Self::parse_iso(s).map_err(|_| parse_error(s, now))
+    }
+}
 
-        let mut parts = s.split_whitespace();
-        let date = parts.next().ok_or(())?.parse()?;
-        let time = parts.next().unwrap_or("00:00").parse()?;
-
-        Ok(DateTime::new(date, time))
+serde_with_str!(DateTime, DateTime::parse_iso);
+
+impl Sub for DateTime {
+    type Output = Duration;
+
+    /// the exact time from `other` to this date time in days, hours and minutes
+    fn sub(self, other: Self) -> Self::Output {
+        let minutes = other.minutes_between(self);
+        let days = minutes / MINUTES_PER_DAY;
+        let minutes = minutes % MINUTES_PER_DAY;
+        Duration::from_parts([
+            0,
+            0,
+            0,
+            days as i32,
+            minutes as i32 / 60,
+            minutes as i32 % 60,
+        ])
     }
 }
 
@@ -192,3 +411,174 @@ impl Ord for DateTime {
         date.then(time)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::time::ParseErrorKind;
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_minutes_between() {
+        let from = at("2025-03-01 14:00");
+        assert_eq!(90, from.minutes_between(at("2025-03-01 15:30")));
+        assert_eq!(-90, at("2025-03-01 15:30").minutes_between(from));
+        assert_eq!(
+            10,
+            at("2025-12-31 23:55").minutes_between(at("2026-01-01 00:05"))
+        );
+        assert_eq!(
+            29 * 24 * 60,
+            at("2024-02-01 00:00").minutes_between(at("2024-03-01 00:00"))
+        );
+    }
+
+    #[test]
+    fn test_until_since() {
+        let from = at("2025-03-01 14:00");
+        let until = |s: &str| from.until(at(s));
+
+        assert_eq!(Duration::Minute(0), until("2025-03-01 14:00"));
+        assert_eq!(Duration::Minute(59), until("2025-03-01 14:59"));
+        assert_eq!(Duration::Hour(23), until("2025-03-02 13:59"));
+        assert_eq!(Duration::Day(1), until("2025-03-02 14:00"));
+        assert_eq!(Duration::Day(13), until("2025-03-15 13:59"));
+        assert_eq!(Duration::Week(2), until("2025-03-15 14:00"));
+        assert_eq!(Duration::Week(4), until("2025-04-01 13:59"));
+        assert_eq!(Duration::Month(1), until("2025-04-01 14:00"));
+        assert_eq!(Duration::Month(11), until("2026-02-28 14:00"));
+        assert_eq!(Duration::Year(2), until("2027-03-01 14:00"));
+
+        assert_eq!(Duration::Hour(-3), until("2025-03-01 11:00"));
+        assert_eq!(Duration::Week(-2), until("2025-02-14 14:00"));
+        assert_eq!(Duration::Week(-2), from.since(at("2025-03-15 14:00")));
+        assert_eq!(Duration::Month(5), from.since(at("2024-10-01 00:00")));
+    }
+
+    #[test]
+    fn test_sub() {
+        let from = at("2025-03-01 14:00");
+        for to in [
+            "2025-03-01 15:30",
+            "2025-03-03 09:15",
+            "2024-12-24 18:00",
+            "2025-03-01 14:00",
+        ] {
+            let to = at(to);
+            assert_eq!(to, from.add_duration(to - from));
+        }
+
+        assert_eq!(Ok(at("2025-03-03 09:15") - from), "1d19h15min".parse());
+        assert_eq!(Ok(from - at("2025-03-03 09:15")), "-1d19h15min".parse());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = |s: &str| s.parse::<DateTime>().unwrap_err();
+
+        let err = error("2025-03-01 25:00");
+        assert_eq!(
+            "hour 25 out of range in '2025-03-01 25:00' at position 11",
+            err.to_string()
+        );
+
+        let err = error("2025-13-01 14:00");
+        assert_eq!((5, "13".into()), (err.position, err.token));
+
+        let err = error("tomorrow 14:6x");
+        assert_eq!(
+            (12, ParseErrorKind::InvalidNumber),
+            (err.position, err.kind)
+        );
+    }
+
+    fn error_of(s: &str) -> ParseError {
+        s.parse::<DateTime>().unwrap_err()
+    }
+
+    #[test]
+    fn test_iso() {
+        let datetime = at("2025-03-01 14:30");
+        assert_eq!(Ok(datetime), DateTime::parse_iso("2025-03-01T14:30:00"));
+        assert_eq!(datetime, at("2025-03-01T14:30:15"));
+        assert_eq!(datetime, at("2025-03-01 14:30:15"));
+        assert_eq!(datetime, at("2025-W09-6T14:30"));
+        assert_eq!(
+            Zone::UTC.convert(datetime, Zone::Local),
+            at("2025-03-01T14:30:00Z")
+        );
+        assert_eq!(
+            Zone::Offset(120).convert(datetime, Zone::Local),
+            at("2025-03-01 14:30+02:00")
+        );
+
+        assert_eq!("2025-03-01T14:30:00", datetime.format_iso());
+        assert_eq!(
+            Ok(datetime),
+            DateTime::parse_iso(&datetime.format_rfc3339())
+        );
+
+        let err = error_of("2025-03-01T25:00");
+        assert_eq!(
+            "hour 25 out of range in '2025-03-01T25:00' at position 11",
+            err.to_string()
+        );
+        let err = error_of("2025-03-01T14:30+02:70");
+        assert_eq!((20, "70".into()), (err.position, err.token));
+    }
+
+    #[test]
+    fn test_serde() {
+        let datetime = at("2025-03-01 14:30");
+        assert_eq!(
+            r#""2025-03-01 14:30""#,
+            serde_json::to_string(&datetime).unwrap()
+        );
+        assert_eq!(
+            datetime,
+            serde_json::from_str::<DateTime>(r#""2025-03-01 14:30""#).unwrap()
+        );
+        assert_eq!(
+            r#"["2025-03-01","14:30","1w2d"]"#,
+            serde_json::to_string(&(
+                datetime.date(),
+                datetime.time(),
+                Duration::from_parts([0, 0, 1, 2, 0, 0])
+            ))
+            .unwrap()
+        );
+        assert_eq!(
+            Duration::Hour(3),
+            serde_json::from_str::<Duration>(r#""3h""#).unwrap()
+        );
+        assert_eq!(
+            Time::from_hm(9, 5).unwrap(),
+            serde_json::from_str::<Time>(r#""09:05""#).unwrap()
+        );
+
+        let err = serde_json::from_str::<Date>(r#""2025-13-01""#).unwrap_err();
+        assert!(err.to_string().starts_with("month 13 out of range"));
+        assert!(serde_json::from_str::<DateTime>("14").is_err());
+
+        // relative and localized text is for the command line only
+        assert!(serde_json::from_str::<DateTime>(r#""tomorrow 14:00""#).is_err());
+        assert!(serde_json::from_str::<DateTime>(r#""+2h""#).is_err());
+        assert!(serde_json::from_str::<Date>(r#""today""#).is_err());
+        assert!(serde_json::from_str::<Date>(r#""fri+1w""#).is_err());
+        assert!(serde_json::from_str::<Time>(r#""2pm""#).is_err());
+        assert_eq!(
+            Date::from_ymd(2025, 3, 1).unwrap(),
+            serde_json::from_str::<Date>(r#""2025-03-01""#).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_date_parts() {
+        let date = Date::from_ymd(2025, 3, 1).unwrap();
+        let at = DateTime::new(date, Time::from_hm(9, 30).unwrap());
+        assert_eq!((2025, 3, 1), (at.year(), at.month(), at.day()));
+    }
+}
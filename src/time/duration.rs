@@ -1,4 +1,7 @@
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, ops::Mul, str::FromStr};
+
+use super::error::parse_number;
+use super::{ParseError, ParseErrorKind};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Duration {
@@ -6,44 +9,223 @@ pub enum Duration {
     Week(i32),
     Month(i32),
     Year(i32),
+    Hour(i32),
+    Minute(i32),
+    /// several units at once like `1w2d` or `1h30min`
+    ///
+    /// Durations with a single unit are never compound, parsing and multiplying take care of
+    /// that.
+    Compound {
+        years: i32,
+        months: i32,
+        weeks: i32,
+        days: i32,
+        hours: i32,
+        minutes: i32,
+    },
+}
+
+/// the units of [`Duration::parts`] as they are written
+const UNITS: [&str; 6] = ["y", "m", "w", "d", "h", "min"];
+
+impl Duration {
+    /// the years, months, weeks, days, hours and minutes of this duration
+    pub(crate) fn parts(&self) -> [i32; 6] {
+        match *self {
+            Duration::Year(x) => [x, 0, 0, 0, 0, 0],
+            Duration::Month(x) => [0, x, 0, 0, 0, 0],
+            Duration::Week(x) => [0, 0, x, 0, 0, 0],
+            Duration::Day(x) => [0, 0, 0, x, 0, 0],
+            Duration::Hour(x) => [0, 0, 0, 0, x, 0],
+            Duration::Minute(x) => [0, 0, 0, 0, 0, x],
+            Duration::Compound {
+                years,
+                months,
+                weeks,
+                days,
+                hours,
+                minutes,
+            } => [years, months, weeks, days, hours, minutes],
+        }
+    }
+
+    /// the duration with the given parts, see [`Duration::parts`]
+    ///
+    /// # Returns
+    /// - a duration with a single unit if at most one part is not zero, `0d` if none is
+    /// - [`Duration::Compound`] otherwise
+    pub(crate) fn from_parts(parts: [i32; 6]) -> Self {
+        let mut units = parts.iter().enumerate().filter(|(_, x)| **x != 0);
+        match (units.next(), units.next()) {
+            (None, _) => Duration::Day(0),
+            (Some((unit, &x)), None) => match unit {
+                0 => Duration::Year(x),
+                1 => Duration::Month(x),
+                2 => Duration::Week(x),
+                3 => Duration::Day(x),
+                4 => Duration::Hour(x),
+                _ => Duration::Minute(x),
+            },
+            _ => {
+                let [years, months, weeks, days, hours, minutes] = parts;
+                Duration::Compound {
+                    years,
+                    months,
+                    weeks,
+                    days,
+                    hours,
+                    minutes,
+                }
+            }
+        }
+    }
+
+    /// whether this duration goes forward, no part is negative and at least one is positive
+    pub fn is_positive(&self) -> bool {
+        let parts = self.parts();
+        parts.iter().all(|x| *x >= 0) && parts.iter().any(|x| *x > 0)
+    }
+
+    /// whether the largest unit of this duration goes forward, which decides the direction for
+    /// durations like `1y-2d`
+    pub(crate) fn is_forward(&self) -> bool {
+        self.parts()
+            .into_iter()
+            .find(|x| *x != 0)
+            .is_some_and(|x| x > 0)
+    }
+
+    /// the number of minutes in the hours and minutes of this duration
+    pub(crate) fn clock_minutes(&self) -> i64 {
+        let [.., hours, minutes] = self.parts();
+        hours as i64 * 60 + minutes as i64
+    }
 }
 
 impl Display for Duration {
+    /// writes units from the largest to the smallest like `1w2d` or `-1h30min`
+    ///
+    /// A leading `-` negates every part, parts with another sign than the first one have their
+    /// own sign like `1w-2d`.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Duration::Day(x) => write!(f, "{}d", x),
-            Duration::Week(x) => write!(f, "{}w", x),
-            Duration::Month(x) => write!(f, "{}m", x),
-            Duration::Year(x) => write!(f, "{}y", x),
+        let parts: Vec<(i32, &str)> = self
+            .parts()
+            .into_iter()
+            .zip(UNITS)
+            .filter(|(x, _)| *x != 0)
+            .collect();
+
+        let Some(&(first, _)) = parts.first() else {
+            return write!(f, "0d");
+        };
+
+        if first < 0 {
+            write!(f, "-")?;
+        }
+
+        for (i, (x, unit)) in parts.into_iter().enumerate() {
+            if i > 0 && (x < 0) != (first < 0) {
+                write!(f, "{}", if x < 0 { '-' } else { '+' })?;
+            }
+            write!(f, "{}{}", x.unsigned_abs(), unit)?;
         }
+        Ok(())
+    }
+}
+
+impl Mul<i32> for Duration {
+    type Output = Duration;
+
+    /// multiplies every part, saturating at the bounds of `i32`
+    fn mul(self, factor: i32) -> Self::Output {
+        Duration::from_parts(self.parts().map(|x| x.saturating_mul(factor)))
+    }
+}
+
+/// the index of `unit` in [`UNITS`], `m` means minutes after a unit smaller than a month
+fn parse_unit(unit: &str, previous: Option<usize>) -> Option<usize> {
+    match unit {
+        "m" if previous.is_some_and(|previous| previous > 1) => Some(5),
+        unit => UNITS.iter().position(|u| *u == unit),
     }
 }
 
 impl FromStr for Duration {
-    type Err = ();
+    type Err = ParseError;
 
+    /// parses a number with a unit like `7d` or `-1w`, or several like `1w2d` or `1h30m`
+    ///
+    /// The units are `y`, `m`, `w`, `d`, `h` and `min`, each at most once and from the largest to
+    /// the smallest. `m` is minutes if it follows a unit smaller than a month and months
+    /// otherwise. A leading sign applies to every part without its own sign.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.trim();
-
-        if s.is_empty() {
-            return Err(());
+        let trimmed = s.trim();
+        if trimmed.is_empty() {
+            return Err(ParseError::new(s, trimmed, ParseErrorKind::Empty));
         }
 
-        let num = s[..s.len()-1].trim().parse().map_err(|_| ())?;
+        let (sign, mut rest) = match trimmed.chars().next() {
+            Some('-') => (-1, &trimmed[1..]),
+            Some('+') => (1, &trimmed[1..]),
+            _ => (1, trimmed),
+        };
+
+        let mut parts = [0; 6];
+        let mut previous = None;
+        loop {
+            let (part_sign, part) = match rest.chars().next() {
+                Some('-') if previous.is_some() => (-1, &rest[1..]),
+                Some('+') if previous.is_some() => (1, &rest[1..]),
+                _ => (sign, rest),
+            };
+
+            let (digits, after) = part.split_at(
+                part.find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(part.len()),
+            );
+            let (unit, next) = after.split_at(
+                after
+                    .find(|c: char| !c.is_ascii_alphabetic())
+                    .unwrap_or(after.len()),
+            );
+
+            let x: i32 = parse_number(s, digits)?;
+            if unit.is_empty() {
+                let token = after
+                    .chars()
+                    .next()
+                    .map_or(digits, |c| &after[..c.len_utf8()]);
+                let kind = if after.is_empty() {
+                    ParseErrorKind::Expected("a unit like d or h after the number")
+                } else {
+                    ParseErrorKind::UnknownUnit
+                };
+                return Err(ParseError::new(s, token, kind));
+            }
 
-        match s.chars().next_back() {
-            Some('d') => Ok(Duration::Day(num)),
-            Some('w') => Ok(Duration::Week(num)),
-            Some('m') => Ok(Duration::Month(num)),
-            Some('y') => Ok(Duration::Year(num)),
-            _ => Err(())
+            let index = parse_unit(unit, previous)
+                .ok_or_else(|| ParseError::new(s, unit, ParseErrorKind::UnknownUnit))?;
+            if previous.is_some_and(|previous| previous >= index) {
+                return Err(ParseError::new(s, unit, ParseErrorKind::MisplacedUnit));
+            }
+
+            parts[index] = x * part_sign;
+            previous = Some(index);
+            rest = next;
+
+            if rest.is_empty() {
+                return Ok(Duration::from_parts(parts));
+            }
         }
     }
 }
 
+serde_with_str!(Duration);
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::time::prelude::*;
 
     #[test]
     fn test_parsing() {
@@ -65,6 +247,13 @@ mod test {
         assert_eq!(parse("-1y"), Duration::Year(-1));
     }
 
+    #[test]
+    fn test_mul() {
+        assert_eq!(Duration::Day(6), Duration::Day(2) * 3);
+        assert_eq!(Duration::Week(-2), Duration::Week(1) * -2);
+        assert_eq!(Duration::Year(4), Duration::Year(2) * 2);
+    }
+
     #[test]
     fn test_display_parse() {
         let expected = Duration::Day(1);
@@ -99,4 +288,131 @@ mod test {
         let got = expected.to_string().parse().unwrap();
         assert_eq!(expected, got);
     }
+
+    #[test]
+    fn test_parsing_compound() {
+        let parse = |s: &str| s.parse::<Duration>();
+        assert_eq!(Ok(Duration::Hour(2)), parse("2h"));
+        assert_eq!(Ok(Duration::Minute(90)), parse("90min"));
+        assert_eq!(Ok(Duration::Month(30)), parse("30m"));
+        assert_eq!(Ok(Duration::Week(1)), parse("0y1w"));
+
+        let compound = |[years, months, weeks, days, hours, minutes]: [i32; 6]| {
+            Ok(Duration::Compound {
+                years,
+                months,
+                weeks,
+                days,
+                hours,
+                minutes,
+            })
+        };
+        assert_eq!(compound([0, 0, 1, 2, 0, 0]), parse("1w2d"));
+        assert_eq!(compound([0, 0, 0, 0, 1, 30]), parse("1h30m"));
+        assert_eq!(compound([0, 0, 0, 1, 0, 30]), parse("1d30m"));
+        assert_eq!(compound([1, 6, 0, 0, 0, 0]), parse("1y6m"));
+        assert_eq!(compound([0, 0, -1, -2, 0, 0]), parse("-1w2d"));
+        assert_eq!(compound([0, 0, 1, -2, 0, 0]), parse("1w-2d"));
+        assert_eq!(compound([0, 0, -1, 2, 0, 0]), parse("-1w+2d"));
+
+        for s in [
+            "",
+            "1",
+            "d",
+            "1x",
+            "1d1w",
+            "1d1d",
+            "1h30",
+            "1w 2d",
+            "--1d",
+            "1m30min30m",
+        ] {
+            assert!(parse(s).is_err(), "{}", s);
+        }
+
+        let err = parse("1w2x").unwrap_err();
+        assert_eq!(
+            (3, ParseErrorKind::UnknownUnit, "x".into()),
+            (err.position, err.kind, err.token)
+        );
+        assert_eq!(
+            "unknown unit 'x' in duration, expected y/m/w/d/h/min in '1w2x' at position 3",
+            parse("1w2x").unwrap_err().to_string()
+        );
+        assert_eq!(
+            ParseErrorKind::MisplacedUnit,
+            parse("1d1w").unwrap_err().kind
+        );
+        assert_eq!(
+            ParseErrorKind::Expected("a number"),
+            parse("--1d").unwrap_err().kind
+        );
+    }
+
+    #[test]
+    fn test_display_parse_compound() {
+        for s in [
+            "2h",
+            "-30min",
+            "1w2d",
+            "1h30min",
+            "-1y6m",
+            "1w-2d",
+            "-1w+2d",
+            "1y2m3w4d5h6min",
+        ] {
+            let duration: Duration = s.parse().unwrap();
+            assert_eq!(s, duration.to_string());
+            assert_eq!(Ok(duration), duration.to_string().parse());
+        }
+        assert_eq!("1h30min", "1h30m".parse::<Duration>().unwrap().to_string());
+        assert_eq!("0d", "0h".parse::<Duration>().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_mul_compound() {
+        let duration: Duration = "1h30m".parse().unwrap();
+        assert_eq!(Ok(duration * 2), "2h60min".parse());
+        assert_eq!(Ok(duration * -1), "-1h30min".parse());
+    }
+
+    #[test]
+    fn test_add_duration() {
+        let at = |s: &str| s.parse::<DateTime>().unwrap();
+        let add = |s: &str, duration: &str| at(s).add_duration(duration.parse().unwrap());
+
+        assert_eq!(at("2025-03-01 15:30"), add("2025-03-01 14:00", "1h30m"));
+        assert_eq!(at("2025-03-02 01:15"), add("2025-03-01 23:45", "90min"));
+        assert_eq!(at("2025-02-28 23:00"), add("2025-03-01 01:00", "-2h"));
+        assert_eq!(at("2026-01-01 00:10"), add("2025-12-31 23:50", "20min"));
+        assert_eq!(at("2024-03-01 00:30"), add("2024-02-28 23:30", "1d1h"));
+        assert_eq!(at("2025-03-11 10:00"), add("2025-03-01 09:00", "1w2d25h"));
+        assert_eq!(at("2025-04-30 12:00"), add("2025-03-30 12:00", "1m"));
+        assert_eq!(at("2025-01-01 00:00"), add("2025-03-01 00:00", "-2m"));
+
+        let date = |s: &str| s.parse::<Date>().unwrap();
+        assert_eq!(
+            date("2025-03-02"),
+            date("2025-03-01").add_duration("36h".parse().unwrap())
+        );
+        assert_eq!(
+            date("2025-03-01"),
+            date("2025-03-01").add_duration("-2h".parse().unwrap())
+        );
+
+        let checked =
+            |s: &str, duration: &str| at(s).checked_add_duration(duration.parse().unwrap());
+        assert_eq!(Some(DateTime::MAX), checked("9999-12-31 23:00", "59min"));
+        assert_eq!(None, checked("9999-12-31 23:00", "1h"));
+        assert_eq!(None, checked("0000-01-01 00:00", "-1min"));
+        assert_eq!(None, checked("2025-03-01 00:00", "2147483647h"));
+        assert_eq!(DateTime::MAX, add("9999-12-31 23:00", "1y"));
+        assert_eq!(DateTime::MIN, add("2025-03-01 12:00", "-3000y"));
+    }
+
+    #[test]
+    fn test_mul_saturates() {
+        assert_eq!(Duration::Day(i32::MAX), Duration::Day(i32::MAX) * 2);
+        assert_eq!(Duration::Week(i32::MAX), Duration::Week(i32::MIN) * -1);
+    }
 }
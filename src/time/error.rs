@@ -0,0 +1,181 @@
+use std::{fmt::Display, str::FromStr};
+
+/// why a value could not be parsed, see [`ParseError`]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseErrorKind {
+    /// there is nothing but whitespace
+    Empty,
+    /// the token is not what was expected there, like `a time like 14:30`
+    Expected(&'static str),
+    /// the token is not a number
+    InvalidNumber,
+    /// the number is outside of the range of its field, like month `13`
+    OutOfRange { field: &'static str, value: u64 },
+    /// the token is no unit of a duration
+    UnknownUnit,
+    /// the unit follows a smaller or the same unit in a compound duration
+    MisplacedUnit,
+    /// the token is no known word, like an entry type other than `todo` or `meeting`
+    Unknown {
+        what: &'static str,
+        expected: &'static str,
+    },
+    /// the token is no valid value of its kind, like a tag containing `,`
+    Invalid(&'static str),
+    /// the key is given more than once, like two states in a query
+    Repeated,
+    /// the end of the range lies before its beginning
+    InvertedRange,
+}
+
+/// an error in a value like a date, pointing at the offending part of the input
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    /// the whole input
+    pub input: String,
+    /// byte offset of `token` in `input`
+    pub position: usize,
+    /// the offending part of `input`
+    pub token: String,
+    pub kind: ParseErrorKind,
+}
+
+/// the byte offset of `part` in `s`, `0` if `part` is not a slice of `s`
+fn offset(s: &str, part: &str) -> usize {
+    let begin = s.as_ptr() as usize;
+    let part_begin = part.as_ptr() as usize;
+    if begin <= part_begin && part_begin + part.len() <= begin + s.len() {
+        part_begin - begin
+    } else {
+        0
+    }
+}
+
+impl ParseError {
+    /// an error about `token`, its position is found if it is a slice of `input`
+    pub fn new(input: &str, token: &str, kind: ParseErrorKind) -> Self {
+        Self {
+            input: input.into(),
+            position: offset(input, token),
+            token: token.into(),
+            kind,
+        }
+    }
+
+    /// this error about `part` moved into `input`, which `part` is a slice of
+    pub(crate) fn within(self, input: &str, part: &str) -> Self {
+        Self {
+            position: self.position + offset(input, part),
+            input: input.into(),
+            ..self
+        }
+    }
+
+    /// what is wrong without where it is, like `month 13 out of range`
+    pub fn reason(&self) -> String {
+        let token = &self.token;
+        match &self.kind {
+            ParseErrorKind::Empty => "empty input".into(),
+            ParseErrorKind::Expected(expected) => {
+                format!("expected {}, found '{}'", expected, token)
+            }
+            ParseErrorKind::InvalidNumber => format!("invalid number '{}'", token),
+            ParseErrorKind::OutOfRange { field, value } => {
+                format!("{} {} out of range", field, value)
+            }
+            ParseErrorKind::UnknownUnit => format!(
+                "unknown unit '{}' in duration, expected y/m/w/d/h/min",
+                token
+            ),
+            ParseErrorKind::MisplacedUnit => format!(
+                "unit '{}' out of order in duration, units go from y down to min at most once",
+                token
+            ),
+            ParseErrorKind::Unknown { what, expected } => {
+                format!("unknown {} '{}', expected {}", what, token, expected)
+            }
+            ParseErrorKind::Invalid(what) => format!("invalid {} '{}'", what, token),
+            ParseErrorKind::Repeated => format!("'{}' given more than once", token),
+            ParseErrorKind::InvertedRange => format!("range '{}' ends before it begins", token),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.kind == ParseErrorKind::Empty || self.token == self.input.trim() {
+            return write!(f, "{}", self.reason());
+        }
+        write!(
+            f,
+            "{} in '{}' at position {}",
+            self.reason(),
+            self.input,
+            self.position
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// parses `token`, a slice of `input`, as a number
+pub(crate) fn parse_number<T: FromStr>(input: &str, token: &str) -> Result<T, ParseError> {
+    if token.is_empty() {
+        return Err(ParseError::new(
+            input,
+            token,
+            ParseErrorKind::Expected("a number"),
+        ));
+    }
+    token
+        .parse()
+        .map_err(|_| ParseError::new(input, token, ParseErrorKind::InvalidNumber))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let input = "2025-13-01";
+        let err = ParseError::new(
+            input,
+            &input[5..7],
+            ParseErrorKind::OutOfRange {
+                field: "month",
+                value: 13,
+            },
+        );
+        assert_eq!(5, err.position);
+        assert_eq!(
+            "month 13 out of range in '2025-13-01' at position 5",
+            err.to_string()
+        );
+
+        let outer = "due 2025-13-01";
+        let part = &outer[4..];
+        let err = ParseError::new(part, &part[5..7], err.kind).within(outer, part);
+        assert_eq!(9, err.position);
+        assert_eq!(outer, err.input);
+
+        let err = ParseError::new(input, "elsewhere", ParseErrorKind::InvalidNumber);
+        assert_eq!(0, err.position);
+    }
+
+    #[test]
+    fn test_display() {
+        let err = ParseError::new("  ", "", ParseErrorKind::Empty);
+        assert_eq!("empty input", err.to_string());
+
+        let kind = ParseErrorKind::Unknown {
+            what: "entry type",
+            expected: "todo or meeting",
+        };
+        let err = ParseError::new("note", "note", kind);
+        assert_eq!(
+            "unknown entry type 'note', expected todo or meeting",
+            err.to_string()
+        );
+    }
+}
@@ -0,0 +1,428 @@
+//! strftime like format strings such as `%a %d %b` or `%d.%m.%Y %H:%M`
+
+use std::{fmt::Display, str::FromStr};
+
+use super::prelude::*;
+use super::{Locale, ParseError, ParseErrorKind};
+
+/// how a number is filled up to its width
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Pad {
+    Zero,
+    Space,
+    None,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Field {
+    Year,
+    ShortYear,
+    Month,
+    MonthName,
+    ShortMonthName,
+    Day,
+    DayOfYear,
+    WeekdayName,
+    ShortWeekdayName,
+    WeekdayFromMonday,
+    WeekdayFromSunday,
+    IsoYear,
+    IsoWeek,
+    Hour,
+    Hour12,
+    Minute,
+    Second,
+    AmPm,
+    LowerAmPm,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Item {
+    Literal(String),
+    Field(Field, Option<Pad>),
+}
+
+/// a parsed format string, see [`Format::from_str`] for the specifiers
+///
+/// Names of months and weekdays are english unless another locale is set with
+/// [`Format::with_locale`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Format {
+    source: String,
+    items: Vec<Item>,
+    locale: Locale,
+}
+
+/// the field of a specifier like `Y`, or the specifiers it stands for like `%Y-%m-%d` for `F`
+enum Specifier {
+    Field(Field),
+    Literal(&'static str),
+    Composite(&'static str),
+}
+
+fn specifier(c: char) -> Option<Specifier> {
+    let specifier = match c {
+        'Y' => Specifier::Field(Field::Year),
+        'y' => Specifier::Field(Field::ShortYear),
+        'm' => Specifier::Field(Field::Month),
+        'B' => Specifier::Field(Field::MonthName),
+        'b' | 'h' => Specifier::Field(Field::ShortMonthName),
+        'd' => Specifier::Field(Field::Day),
+        'e' => Specifier::Composite("%_d"),
+        'j' => Specifier::Field(Field::DayOfYear),
+        'A' => Specifier::Field(Field::WeekdayName),
+        'a' => Specifier::Field(Field::ShortWeekdayName),
+        'u' => Specifier::Field(Field::WeekdayFromMonday),
+        'w' => Specifier::Field(Field::WeekdayFromSunday),
+        'G' => Specifier::Field(Field::IsoYear),
+        'V' => Specifier::Field(Field::IsoWeek),
+        'H' => Specifier::Field(Field::Hour),
+        'k' => Specifier::Composite("%_H"),
+        'I' => Specifier::Field(Field::Hour12),
+        'l' => Specifier::Composite("%_I"),
+        'M' => Specifier::Field(Field::Minute),
+        'S' => Specifier::Field(Field::Second),
+        'p' => Specifier::Field(Field::AmPm),
+        'P' => Specifier::Field(Field::LowerAmPm),
+        'F' => Specifier::Composite("%Y-%m-%d"),
+        'D' => Specifier::Composite("%m/%d/%y"),
+        'R' => Specifier::Composite("%H:%M"),
+        'T' => Specifier::Composite("%H:%M:%S"),
+        '%' => Specifier::Literal("%"),
+        'n' => Specifier::Literal("\n"),
+        't' => Specifier::Literal("\t"),
+        _ => return None,
+    };
+    Some(specifier)
+}
+
+/// parses the items of `s`, unknown specifiers are an error if `strict`, otherwise they are kept
+/// as they are
+fn parse_items(s: &str, strict: bool) -> Result<Vec<Item>, ParseError> {
+    let mut items = Vec::new();
+    let mut literal = String::new();
+    let mut rest = s;
+
+    while let Some(i) = rest.find('%') {
+        literal.push_str(&rest[..i]);
+        let spec = &rest[i..];
+
+        let mut chars = spec[1..].chars();
+        let (pad, c) = match chars.next() {
+            Some('-') => (Some(Pad::None), chars.next()),
+            Some('_') => (Some(Pad::Space), chars.next()),
+            Some('0') => (Some(Pad::Zero), chars.next()),
+            c => (None, c),
+        };
+        let len = 1 + pad.map_or(0, |_| 1) + c.map_or(0, char::len_utf8);
+        let token = &spec[..len];
+        rest = &spec[len..];
+
+        match c.and_then(specifier) {
+            Some(Specifier::Field(field)) => {
+                if !literal.is_empty() {
+                    items.push(Item::Literal(std::mem::take(&mut literal)));
+                }
+                items.push(Item::Field(field, pad));
+            }
+            Some(Specifier::Literal(s)) => literal.push_str(s),
+            Some(Specifier::Composite(composite)) => {
+                let mut composite = parse_items(composite, true).expect("composites are valid");
+                if let (Some(pad), [Item::Field(_, field_pad), ..]) = (pad, &mut composite[..]) {
+                    *field_pad = Some(pad);
+                }
+                if !literal.is_empty() {
+                    items.push(Item::Literal(std::mem::take(&mut literal)));
+                }
+                items.extend(composite);
+            }
+            None if strict => {
+                let kind = match c {
+                    Some(_) => ParseErrorKind::Unknown {
+                        what: "format specifier",
+                        expected: "one like %Y, %m, %d, %a, %b, %H, %I, %M or %p",
+                    },
+                    None => ParseErrorKind::Expected("a specifier after %"),
+                };
+                return Err(ParseError::new(s, token, kind));
+            }
+            None => literal.push_str(token),
+        }
+    }
+
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        items.push(Item::Literal(literal));
+    }
+    Ok(items)
+}
+
+fn write_number(out: &mut String, value: u32, width: usize, pad: Pad) {
+    let formatted = match pad {
+        Pad::Zero => format!("{:>0width$}", value),
+        Pad::Space => format!("{:>width$}", value),
+        Pad::None => value.to_string(),
+    };
+    out.push_str(&formatted);
+}
+
+fn write_field(
+    out: &mut String,
+    field: Field,
+    pad: Option<Pad>,
+    locale: Locale,
+    date: Option<Date>,
+    time: Option<Time>,
+) {
+    let number =
+        |out: &mut String, value, width| write_number(out, value, width, pad.unwrap_or(Pad::Zero));
+
+    if let Some(date) = date {
+        match field {
+            Field::Year => return number(out, date.year(), 4),
+            Field::ShortYear => return number(out, date.year() % 100, 2),
+            Field::Month => return number(out, date.month(), 2),
+            Field::MonthName => return out.push_str(locale.month_name(date.month())),
+            Field::ShortMonthName => return out.push_str(locale.short_month_name(date.month())),
+            Field::Day => return number(out, date.day(), 2),
+            Field::DayOfYear => return number(out, date.day_of_year(), 3),
+            Field::WeekdayName => return out.push_str(locale.weekday_name(date.weekday())),
+            Field::ShortWeekdayName => {
+                return out.push_str(locale.short_weekday_name(date.weekday()));
+            }
+            Field::WeekdayFromMonday => return number(out, date.weekday().number_from_monday(), 1),
+            Field::WeekdayFromSunday => {
+                return number(out, date.weekday().number_from_monday() % 7, 1);
+            }
+            Field::IsoYear => return number(out, date.iso_week().0, 4),
+            Field::IsoWeek => return number(out, date.iso_week().1, 2),
+            _ => (),
+        }
+    }
+
+    if let Some(time) = time {
+        let hour12 = match time.hour() % 12 {
+            0 => 12,
+            hour => hour,
+        };
+        match field {
+            Field::Hour => number(out, time.hour(), 2),
+            Field::Hour12 => number(out, hour12, 2),
+            Field::Minute => number(out, time.minute(), 2),
+            Field::Second => number(out, 0, 2),
+            Field::AmPm => out.push_str(if time.hour() < 12 { "AM" } else { "PM" }),
+            Field::LowerAmPm => out.push_str(if time.hour() < 12 { "am" } else { "pm" }),
+            _ => (),
+        }
+    }
+}
+
+impl Format {
+    /// parses `s` leniently, unknown specifiers are kept as they are like `strftime` does
+    fn lenient(s: &str) -> Self {
+        let items = parse_items(s, false).expect("lenient parsing does not fail");
+        Self {
+            source: s.into(),
+            items,
+            locale: Locale::English,
+        }
+    }
+
+    /// this format with the names of months and weekdays in `locale`
+    pub fn with_locale(self, locale: Locale) -> Self {
+        Self { locale, ..self }
+    }
+
+    /// formats `date` and `time`, specifiers of a missing part like `%H` of a date are left out
+    fn write(&self, date: Option<Date>, time: Option<Time>) -> String {
+        let mut out = String::new();
+        for item in &self.items {
+            match item {
+                Item::Literal(literal) => out.push_str(literal),
+                Item::Field(field, pad) => {
+                    write_field(&mut out, *field, *pad, self.locale, date, time)
+                }
+            }
+        }
+        out
+    }
+
+    pub fn format_date(&self, date: Date) -> String {
+        self.write(Some(date), None)
+    }
+
+    pub fn format_time(&self, time: Time) -> String {
+        self.write(None, Some(time))
+    }
+
+    pub fn format_datetime(&self, datetime: DateTime) -> String {
+        self.write(Some(datetime.date()), Some(datetime.time()))
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl FromStr for Format {
+    type Err = ParseError;
+
+    /// parses a format string, text is kept as it is and specifiers are replaced by
+    /// - `%Y` the year like `2025`, `%y` the last two digits of it like `25`
+    /// - `%m` the month like `03`, `%B` its name like `March`, `%b` or `%h` short like `Mar`
+    /// - `%d` the day like `01`, `%e` padded with a space like ` 1`, `%j` the day of the year
+    /// - `%A` the weekday like `Saturday`, `%a` short like `Sat`, `%u` its number from `1` for
+    ///   monday, `%w` its number from `0` for sunday
+    /// - `%G` the year of the ISO week and `%V` the ISO week, see [`DateLike::iso_week`]
+    /// - `%H` the hour like `14`, `%k` padded with a space, `%I` the hour on a 12-hour clock like
+    ///   `02`, `%l` padded with a space, `%p` `AM` or `PM` and `%P` `am` or `pm`
+    /// - `%M` the minute and `%S` the second, which is always `00`
+    /// - `%F` for `%Y-%m-%d`, `%D` for `%m/%d/%y`, `%R` for `%H:%M` and `%T` for `%H:%M:%S`
+    /// - `%%`, `%n` and `%t` for `%`, a newline and a tab
+    ///
+    /// A `-` after the `%` like in `%-d` drops the padding of a number, `_` pads it with spaces and
+    /// `0` with zeros.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            source: s.into(),
+            items: parse_items(s, true)?,
+            locale: Locale::English,
+        })
+    }
+}
+
+impl Date {
+    /// formats with a format string like `%a %d %b`, see [`Format::from_str`]
+    ///
+    /// Unknown specifiers are kept as they are and time specifiers like `%H` are left out.
+    ///
+    /// ```
+    /// use koi::time::Date;
+    ///
+    /// let date = Date::from_ymd(2025, 3, 3).unwrap();
+    /// assert_eq!("Mon 03 Mar", date.format("%a %d %b"));
+    /// assert_eq!("03.03.2025", date.format("%d.%m.%Y"));
+    /// ```
+    pub fn format(&self, format: &str) -> String {
+        self.format_localized(format, Locale::English)
+    }
+
+    /// formats like [`Date::format`] with the names of months and weekdays in `locale`
+    pub fn format_localized(&self, format: &str, locale: Locale) -> String {
+        Format::lenient(format)
+            .with_locale(locale)
+            .format_date(*self)
+    }
+}
+
+impl Time {
+    /// formats with a format string like `%I:%M %p`, see [`Format::from_str`]
+    ///
+    /// Unknown specifiers are kept as they are and date specifiers like `%d` are left out.
+    pub fn format(&self, format: &str) -> String {
+        Format::lenient(format).format_time(*self)
+    }
+}
+
+impl DateTime {
+    /// formats with a format string like `%a %d %b %H:%M`, see [`Format::from_str`]
+    ///
+    /// Unknown specifiers are kept as they are.
+    pub fn format(&self, format: &str) -> String {
+        self.format_localized(format, Locale::English)
+    }
+
+    /// formats like [`DateTime::format`] with the names of months and weekdays in `locale`
+    pub fn format_localized(&self, format: &str, locale: Locale) -> String {
+        Format::lenient(format)
+            .with_locale(locale)
+            .format_datetime(*self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_format_date() {
+        let date = at("2025-03-01 00:00").date();
+        assert_eq!("Sat 01 Mar", date.format("%a %d %b"));
+        assert_eq!("Saturday, 1 March 2025", date.format("%A, %-d %B %Y"));
+        assert_eq!("01.03.25", date.format("%d.%m.%y"));
+        assert_eq!(" 1|060|6|6|2025-W09", date.format("%e|%j|%u|%w|%G-W%V"));
+        assert_eq!("2025-03-01 03/01/25", date.format("%F %D"));
+        assert_eq!("100%\t", date.format("100%%%t"));
+        assert_eq!("0", at("2025-03-02 00:00").date().format("%w"));
+        assert_eq!("on  at ", date.format("on %H at %M"));
+    }
+
+    #[test]
+    fn test_format_time() {
+        let time = |s: &str| at(&format!("2025-03-01 {}", s)).time();
+        assert_eq!("02:30 PM", time("14:30").format("%I:%M %p"));
+        assert_eq!("2:30pm", time("14:30").format("%-I:%M%P"));
+        assert_eq!("12:05 AM", time("00:05").format("%I:%M %p"));
+        assert_eq!("12 PM", time("12:00").format("%l %p"));
+        assert_eq!(" 9:05:00", time("09:05").format("%k:%M:%S"));
+        assert_eq!("09:05:00", time("09:05").format("%T"));
+        assert_eq!("09:05", time("09:05").format("%R%d"));
+        assert_eq!("9h", time("09:05").format("%-Hh%Y"));
+    }
+
+    #[test]
+    fn test_format_datetime() {
+        let datetime = at("2025-03-03 09:05");
+        assert_eq!("Mon 03 Mar 09:05", datetime.format("%a %d %b %R"));
+        assert_eq!("3.3.2025 9:05", datetime.format("%-d.%-m.%Y %-H:%M"));
+        assert_eq!(" 3|03|09| 9", datetime.format("%_d|%0e|%I|%_l"));
+        assert_eq!("%q %", datetime.format("%q %"));
+    }
+
+    #[test]
+    fn test_format_localized() {
+        let datetime = at("2025-03-03 09:05");
+        assert_eq!(
+            "Mo, 3. März 2025 09:05",
+            datetime.format_localized("%a, %-d. %B %Y %R", Locale::German)
+        );
+        assert_eq!(
+            "Montag 03 Mär",
+            datetime.date().format_localized("%A %d %b", Locale::German)
+        );
+        let format: Format = "%A".parse().unwrap();
+        assert_eq!(
+            "Sonntag",
+            format
+                .with_locale(Locale::German)
+                .format_date(at("2025-03-02 00:00").date())
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let format: Format = "%d.%m.%Y".parse().unwrap();
+        assert_eq!("%d.%m.%Y", format.to_string());
+        assert_eq!(
+            "01.03.2025",
+            format.format_date(at("2025-03-01 00:00").date())
+        );
+
+        let err = "%d %q".parse::<Format>().unwrap_err();
+        assert_eq!((3, "%q".into()), (err.position, err.token));
+        assert!(matches!(err.kind, ParseErrorKind::Unknown { .. }));
+
+        let err = "%H:%".parse::<Format>().unwrap_err();
+        assert_eq!(
+            (3, ParseErrorKind::Expected("a specifier after %")),
+            (err.position, err.kind)
+        );
+        assert!("%-".parse::<Format>().is_err());
+    }
+}
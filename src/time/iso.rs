@@ -0,0 +1,381 @@
+//! ISO 8601 and RFC 3339 dates and times like `2025-03-01T14:30:00+02:00`, `2025-W09-6` or
+//! `2025-060`
+//!
+//! Both the extended format with separators and the basic format without them are understood.
+//! Times have a precision of minutes, so seconds and fractions of them are checked but dropped.
+
+use super::error::parse_number;
+use super::prelude::*;
+use super::{ParseError, ParseErrorKind};
+
+const EXPECTED_DATE: &str = "an ISO date like 2025-03-01, 2025-W09-6 or 2025-060";
+const EXPECTED_TIME: &str = "an ISO time like 14:30 or 14:30:00";
+const EXPECTED_OFFSET: &str = "an offset like Z or +02:00";
+const EXPECTED_DATETIME: &str = "an ISO date time like 2025-03-01T14:30:00Z";
+
+fn expected(s: &str, token: &str, expected: &'static str) -> ParseError {
+    ParseError::new(s, token, ParseErrorKind::Expected(expected))
+}
+
+fn out_of_range(s: &str, token: &str, field: &'static str, value: u32) -> ParseError {
+    let value = value as u64;
+    ParseError::new(s, token, ParseErrorKind::OutOfRange { field, value })
+}
+
+/// parses `token`, a slice of `s`, which has to be `len` digits
+fn digits(s: &str, token: &str, len: usize, what: &'static str) -> Result<u32, ParseError> {
+    if token.len() != len {
+        return Err(expected(s, token, what));
+    }
+    if !token.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::new(s, token, ParseErrorKind::InvalidNumber));
+    }
+    parse_number(s, token)
+}
+
+/// parses `date`, a slice of `s`, as `YYYY-MM-DD`, `YYYY-Www-D`, `YYYY-Www` or `YYYY-DDD`, or
+/// the same without dashes
+pub(crate) fn parse_date(s: &str, date: &str) -> Result<Date, ParseError> {
+    if date.is_empty() {
+        return Err(ParseError::new(s, date, ParseErrorKind::Empty));
+    }
+    if !date.is_ascii() || date.len() < 4 {
+        return Err(expected(s, date, EXPECTED_DATE));
+    }
+
+    let (year_token, rest) = date.split_at(4);
+    let year = digits(s, year_token, 4, EXPECTED_DATE)?;
+    let (extended, rest) = match rest.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+
+    if let Some(week) = rest.strip_prefix(['W', 'w']) {
+        return parse_week_date(s, year, week, extended);
+    }
+
+    let (month, day) = match (extended, rest.len()) {
+        (_, 3) => return parse_ordinal_date(s, year, rest),
+        (true, 5) if rest.as_bytes()[2] == b'-' => (&rest[..2], &rest[3..]),
+        (false, 4) => rest.split_at(2),
+        _ => return Err(expected(s, date, EXPECTED_DATE)),
+    };
+
+    let month_value = digits(s, month, 2, EXPECTED_DATE)?;
+    if !(1..=12).contains(&month_value) {
+        return Err(out_of_range(s, month, "month", month_value));
+    }
+    let day_value = digits(s, day, 2, EXPECTED_DATE)?;
+    Date::from_ymd(year, month_value, day_value)
+        .ok_or_else(|| out_of_range(s, day, "day", day_value))
+}
+
+/// parses the `ww-D`, `wwD` or `ww` after the `W` of a week date
+fn parse_week_date(s: &str, year: u32, week: &str, extended: bool) -> Result<Date, ParseError> {
+    let (week, weekday) = match (extended, week.len()) {
+        (_, 2) => (week, None),
+        (true, 4) if week.as_bytes()[2] == b'-' => (&week[..2], Some(&week[3..])),
+        (false, 3) => (&week[..2], Some(&week[2..])),
+        _ => return Err(expected(s, week, EXPECTED_DATE)),
+    };
+
+    let weekday = match weekday {
+        Some(token) => {
+            let number = digits(s, token, 1, EXPECTED_DATE)?;
+            Weekday::from_number_from_monday(number)
+                .ok_or_else(|| out_of_range(s, token, "weekday", number))?
+        }
+        None => Weekday::Monday,
+    };
+
+    let week_value = digits(s, week, 2, EXPECTED_DATE)?;
+    Date::from_iso_week(year, week_value, weekday)
+        .ok_or_else(|| out_of_range(s, week, "week", week_value))
+}
+
+/// parses the `DDD` of an ordinal date
+fn parse_ordinal_date(s: &str, year: u32, day: &str) -> Result<Date, ParseError> {
+    let day_value = digits(s, day, 3, EXPECTED_DATE)?;
+    if !(1..=year_len(year)).contains(&day_value) {
+        return Err(out_of_range(s, day, "day of year", day_value));
+    }
+    let first = Date::from_ymd(year, 1, 1).expect("every year has a first of january");
+    Ok(first.add_days(day_value as i32 - 1))
+}
+
+/// checks `second`, a slice of `s`, is a second like `05`, `60` on leap seconds, or one with a
+/// fraction like `05.250`
+pub(crate) fn parse_second(s: &str, second: &str) -> Result<(), ParseError> {
+    let (second, fraction) = match second.find(['.', ',']) {
+        Some(i) => (&second[..i], Some(&second[i + 1..])),
+        None => (second, None),
+    };
+
+    let value = digits(s, second, 2, EXPECTED_TIME)?;
+    if value > 60 {
+        return Err(out_of_range(s, second, "second", value));
+    }
+
+    if let Some(fraction) = fraction
+        && (fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return Err(expected(s, fraction, "a fraction of a second like 250"));
+    }
+    Ok(())
+}
+
+/// parses `time`, a slice of `s`, as `HH:MM`, `HH:MM:SS` or `HH:MM:SS.fff`, or the same without
+/// colons
+pub(crate) fn parse_time(s: &str, time: &str) -> Result<Time, ParseError> {
+    if time.is_empty() {
+        return Err(ParseError::new(s, time, ParseErrorKind::Empty));
+    }
+    if !time.is_ascii() || time.len() < 4 {
+        return Err(expected(s, time, EXPECTED_TIME));
+    }
+
+    let (hour, rest) = time.split_at(2);
+    let (minute, second) = match rest.strip_prefix(':') {
+        Some(rest) if rest.len() == 2 => (rest, None),
+        Some(rest) if rest.len() > 3 && rest.as_bytes()[2] == b':' => {
+            (&rest[..2], Some(&rest[3..]))
+        }
+        None if rest.len() == 2 => (rest, None),
+        None if rest.len() >= 4 && rest.as_bytes()[2].is_ascii_digit() => {
+            (&rest[..2], Some(&rest[2..]))
+        }
+        _ => return Err(expected(s, time, EXPECTED_TIME)),
+    };
+
+    let hour_value = digits(s, hour, 2, EXPECTED_TIME)?;
+    if hour_value > 23 {
+        return Err(out_of_range(s, hour, "hour", hour_value));
+    }
+    let minute_value = digits(s, minute, 2, EXPECTED_TIME)?;
+    if minute_value > 59 {
+        return Err(out_of_range(s, minute, "minute", minute_value));
+    }
+    if let Some(second) = second {
+        parse_second(s, second)?;
+    }
+
+    Ok(Time::from_hm(hour_value, minute_value).expect("hour and minute are in range"))
+}
+
+/// parses `offset`, a slice of `s`, as `Z`, `±HH:MM`, `±HHMM` or `±HH`
+fn parse_offset(s: &str, offset: &str) -> Result<Zone, ParseError> {
+    if offset.eq_ignore_ascii_case("z") {
+        return Ok(Zone::UTC);
+    }
+
+    let (sign, rest) = match offset.strip_prefix('+') {
+        Some(rest) => (1, rest),
+        None => (-1, offset.strip_prefix('-').unwrap_or(offset)),
+    };
+    if !offset.starts_with(['+', '-']) || !rest.is_ascii() || rest.len() < 2 {
+        return Err(expected(s, offset, EXPECTED_OFFSET));
+    }
+
+    let (hour, minute) = rest.split_at(2);
+    let minute = minute.strip_prefix(':').unwrap_or(minute);
+    let hour_value = digits(s, hour, 2, EXPECTED_OFFSET)?;
+    if hour_value > 23 {
+        return Err(out_of_range(s, hour, "offset hour", hour_value));
+    }
+    let minute_value = match minute {
+        "" => 0,
+        minute => digits(s, minute, 2, EXPECTED_OFFSET)?,
+    };
+    if minute_value > 59 {
+        return Err(out_of_range(s, minute, "offset minute", minute_value));
+    }
+
+    Ok(Zone::Offset(sign * (hour_value * 60 + minute_value) as i32))
+}
+
+/// parses a date and a time separated by `T` or a space, optionally followed by an offset, see
+/// [`ZonedDateTime::parse_iso`]
+pub(crate) fn parse_datetime(s: &str) -> Result<ZonedDateTime, ParseError> {
+    let trimmed = s.trim();
+    if trimmed.is_empty() {
+        return Err(ParseError::new(s, trimmed, ParseErrorKind::Empty));
+    }
+
+    let (date, rest) = trimmed
+        .split_once(['T', 't', ' '])
+        .ok_or_else(|| expected(s, trimmed, EXPECTED_DATETIME))?;
+    let (time, offset) = match rest.find(['Z', 'z', '+', '-']) {
+        Some(i) => rest.split_at(i),
+        None => (rest, ""),
+    };
+
+    let date = parse_date(s, date)?;
+    let time = parse_time(s, time)?;
+    let zone = match offset {
+        "" => Zone::Local,
+        offset => parse_offset(s, offset)?,
+    };
+    Ok(ZonedDateTime::new(date.with_time(time), zone))
+}
+
+/// whether `s` is meant as an ISO date time rather than a relative one, which decides whose error
+/// explains why it could not be parsed
+///
+/// This is a date of digits, dashes and week letters followed by a `T`, or by a time with an
+/// offset.
+pub(crate) fn is_datetime_like(s: &str) -> bool {
+    let s = s.trim();
+    let Some((date, rest)) = s.split_once(['T', 't', ' ']) else {
+        return false;
+    };
+
+    let is_date = date.starts_with(|c: char| c.is_ascii_digit())
+        && date
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '-' | 'W' | 'w'));
+    let has_offset = !rest.contains(char::is_whitespace)
+        && rest.contains(':')
+        && rest.contains(['Z', 'z', '+', '-']);
+    is_date && (!s.contains(char::is_whitespace) || has_offset)
+}
+
+/// formats an offset east of UTC in minutes like `+02:00`, `Z` for UTC itself
+pub(crate) fn format_offset(minutes: i32) -> String {
+    if minutes == 0 {
+        return "Z".into();
+    }
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let minutes = minutes.unsigned_abs();
+    format!("{}{:>02}:{:>02}", sign, minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        Date::from_ymd(
+            s[..4].parse().unwrap(),
+            s[5..7].parse().unwrap(),
+            s[8..].parse().unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let parse = |s| parse_date(s, s);
+        assert_eq!(Ok(date("2025-03-01")), parse("2025-03-01"));
+        assert_eq!(Ok(date("2025-03-01")), parse("20250301"));
+        assert_eq!(Ok(date("2025-03-01")), parse("2025-W09-6"));
+        assert_eq!(Ok(date("2025-03-01")), parse("2025w096"));
+        assert_eq!(Ok(date("2025-02-24")), parse("2025-W09"));
+        assert_eq!(Ok(date("2024-12-30")), parse("2025-W01-1"));
+        assert_eq!(Ok(date("2025-03-01")), parse("2025-060"));
+        assert_eq!(Ok(date("2024-02-29")), parse("2024060"));
+        assert_eq!(Ok(date("2024-12-31")), parse("2024-366"));
+
+        let kind = |s| parse(s).unwrap_err().kind;
+        let out_of_range = |field, value| ParseErrorKind::OutOfRange { field, value };
+        assert_eq!(out_of_range("day of year", 366), kind("2025-366"));
+        assert_eq!(out_of_range("week", 53), kind("2025-W53-1"));
+        assert_eq!(out_of_range("weekday", 8), kind("2025-W09-8"));
+        assert_eq!(out_of_range("month", 13), kind("20251301"));
+        assert_eq!(out_of_range("day", 30), kind("2025-02-30"));
+        assert_eq!(ParseErrorKind::InvalidNumber, kind("2025-0x-01"));
+        assert_eq!(ParseErrorKind::Expected(EXPECTED_DATE), kind("2025-03"));
+        assert_eq!(ParseErrorKind::Expected(EXPECTED_DATE), kind("2025-0301"));
+        assert_eq!(ParseErrorKind::InvalidNumber, kind("25-03-01"));
+        assert_eq!(ParseErrorKind::Expected(EXPECTED_DATE), kind("2025-03-ü1"));
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let parse = |s| parse_time(s, s);
+        let time = |hour, minute| Time::from_hm(hour, minute).unwrap();
+        assert_eq!(Ok(time(14, 30)), parse("14:30"));
+        assert_eq!(Ok(time(14, 30)), parse("14:30:59"));
+        assert_eq!(Ok(time(14, 30)), parse("14:30:59.999"));
+        assert_eq!(Ok(time(14, 30)), parse("14:30:05,5"));
+        assert_eq!(Ok(time(23, 59)), parse("23:59:60"));
+        assert_eq!(Ok(time(14, 30)), parse("1430"));
+        assert_eq!(Ok(time(14, 30)), parse("143000"));
+
+        let err = parse("14:30:61").unwrap_err();
+        assert_eq!(
+            (
+                6,
+                ParseErrorKind::OutOfRange {
+                    field: "second",
+                    value: 61
+                }
+            ),
+            (err.position, err.kind)
+        );
+        assert!(parse("24:00").is_err());
+        assert!(parse("14:30:").is_err());
+        assert!(parse("14:30:00.").is_err());
+        assert!(parse("14:3").is_err());
+        assert!(parse("14").is_err());
+    }
+
+    #[test]
+    fn test_parse_datetime() {
+        let at = |s: &str, zone| {
+            Ok(ZonedDateTime::new(
+                date(&s[..10]).with_time(s[11..].parse().unwrap()),
+                zone,
+            ))
+        };
+        assert_eq!(
+            at("2025-03-01 14:30", Zone::UTC),
+            parse_datetime("2025-03-01T14:30:00Z")
+        );
+        assert_eq!(
+            at("2025-03-01 14:30", Zone::UTC),
+            parse_datetime("2025-03-01t14:30z")
+        );
+        assert_eq!(
+            at("2025-03-01 14:30", Zone::Offset(120)),
+            parse_datetime("2025-03-01T14:30:00+02:00")
+        );
+        assert_eq!(
+            at("2025-03-01 14:30", Zone::Offset(-330)),
+            parse_datetime("2025-03-01 14:30:00.5-0530")
+        );
+        assert_eq!(
+            at("2025-03-01 14:30", Zone::Offset(-60)),
+            parse_datetime("20250301T1430-01")
+        );
+        assert_eq!(
+            at("2025-03-01 14:30", Zone::Local),
+            parse_datetime("2025-060T14:30")
+        );
+
+        let err = parse_datetime("2025-03-01T14:30+24:00").unwrap_err();
+        assert_eq!(
+            (
+                17,
+                ParseErrorKind::OutOfRange {
+                    field: "offset hour",
+                    value: 24
+                }
+            ),
+            (err.position, err.kind)
+        );
+        assert!(parse_datetime("2025-03-01T14:30+2").is_err());
+        assert!(parse_datetime("2025-03-01T14:30Europe/Berlin").is_err());
+        assert!(parse_datetime("2025-03-01").is_err());
+    }
+
+    #[test]
+    fn test_is_datetime_like() {
+        assert!(is_datetime_like("2025-03-01T14:30"));
+        assert!(is_datetime_like("2025-W09-6T14:30:00Z"));
+        assert!(is_datetime_like("2025-03-01 14:30+02:00"));
+        assert!(!is_datetime_like("2025-03-01 14:30"));
+        assert!(!is_datetime_like("2025-03-01"));
+        assert!(!is_datetime_like("tomorrow 14:30"));
+        assert!(!is_datetime_like("today"));
+    }
+}
@@ -0,0 +1,346 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use super::prelude::*;
+use super::{ParseError, ParseErrorKind};
+
+/// the language of month and weekday names and of relative dates like `next monday`
+///
+/// English words are understood in every locale, so `tomorrow` works in German as well.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Locale {
+    #[default]
+    English,
+    German,
+}
+
+const ENGLISH_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const ENGLISH_WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+const GERMAN_MONTHS: [&str; 12] = [
+    "Januar",
+    "Februar",
+    "März",
+    "April",
+    "Mai",
+    "Juni",
+    "Juli",
+    "August",
+    "September",
+    "Oktober",
+    "November",
+    "Dezember",
+];
+
+const GERMAN_SHORT_MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+];
+
+const GERMAN_WEEKDAYS: [&str; 7] = [
+    "Montag",
+    "Dienstag",
+    "Mittwoch",
+    "Donnerstag",
+    "Freitag",
+    "Samstag",
+    "Sonntag",
+];
+
+const GERMAN_SHORT_WEEKDAYS: [&str; 7] = ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"];
+
+/// german words of relative dates and the english words they stand for, an empty word is dropped
+const GERMAN_WORDS: &[(&str, &str)] = &[
+    ("jetzt", "now"),
+    ("heute", "today"),
+    ("gestern", "yesterday"),
+    ("morgen", "tomorrow"),
+    ("übermorgen", "+2d"),
+    ("uebermorgen", "+2d"),
+    ("vorgestern", "-2d"),
+    ("montag", "monday"),
+    ("dienstag", "tuesday"),
+    ("mittwoch", "wednesday"),
+    ("donnerstag", "thursday"),
+    ("freitag", "friday"),
+    ("samstag", "saturday"),
+    ("sonnabend", "saturday"),
+    ("sonntag", "sunday"),
+    ("mo", "monday"),
+    ("di", "tuesday"),
+    ("mi", "wednesday"),
+    ("do", "thursday"),
+    ("fr", "friday"),
+    ("sa", "saturday"),
+    ("so", "sunday"),
+    ("nächste", "next"),
+    ("nächsten", "next"),
+    ("nächster", "next"),
+    ("nächstes", "next"),
+    ("naechste", "next"),
+    ("naechsten", "next"),
+    ("naechster", "next"),
+    ("naechstes", "next"),
+    ("kommende", "next"),
+    ("kommenden", "next"),
+    ("kommender", "next"),
+    ("kommendes", "next"),
+    ("letzte", "last"),
+    ("letzten", "last"),
+    ("letzter", "last"),
+    ("letztes", "last"),
+    ("vergangene", "last"),
+    ("vergangenen", "last"),
+    ("vergangener", "last"),
+    ("vergangenes", "last"),
+    ("diese", "this"),
+    ("diesen", "this"),
+    ("dieser", "this"),
+    ("dieses", "this"),
+    ("tag", "day"),
+    ("tage", "days"),
+    ("tagen", "days"),
+    ("tages", "day"),
+    ("woche", "week"),
+    ("wochen", "weeks"),
+    ("monat", "month"),
+    ("monate", "months"),
+    ("monaten", "months"),
+    ("monats", "month"),
+    ("jahr", "year"),
+    ("jahre", "years"),
+    ("jahren", "years"),
+    ("jahres", "year"),
+    ("ein", "a"),
+    ("eine", "a"),
+    ("einen", "a"),
+    ("einem", "a"),
+    ("einer", "a"),
+    ("ende", "end"),
+    ("anfang", "start"),
+    ("beginn", "start"),
+    ("des", "of"),
+    ("der", "of"),
+    ("im", "of"),
+    ("erste", "first"),
+    ("ersten", "first"),
+    ("erster", "first"),
+    ("zweite", "second"),
+    ("zweiten", "second"),
+    ("zweiter", "second"),
+    ("dritte", "third"),
+    ("dritten", "third"),
+    ("dritter", "third"),
+    ("vierte", "fourth"),
+    ("vierten", "fourth"),
+    ("vierter", "fourth"),
+    ("fünfte", "fifth"),
+    ("fünften", "fifth"),
+    ("fünfter", "fifth"),
+    ("fuenfte", "fifth"),
+    ("fuenften", "fifth"),
+    ("fuenfter", "fifth"),
+    ("um", ""),
+];
+
+/// the locale set with [`Locale::set_current`], `0` if none is set
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+impl Locale {
+    /// the locale of the environment from `LC_ALL`, `LC_TIME` or `LANG`, whichever is set
+    /// first, english if none of them is german
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// the locale used to parse relative dates without an explicit locale, the one set with
+    /// [`Locale::set_current`] or otherwise [`Locale::from_env`]
+    pub fn current() -> Self {
+        match CURRENT.load(Ordering::Relaxed) {
+            1 => Locale::English,
+            2 => Locale::German,
+            _ => Self::from_env(),
+        }
+    }
+
+    /// sets the locale returned by [`Locale::current`] for the whole process
+    pub fn set_current(locale: Locale) {
+        CURRENT.store(locale as u8 + 1, Ordering::Relaxed);
+    }
+
+    /// the name of `month`, which starts at `1` for january, like `March` or `März`
+    pub fn month_name(&self, month: u32) -> &'static str {
+        let i = month as usize - 1;
+        match self {
+            Locale::English => ENGLISH_MONTHS[i],
+            Locale::German => GERMAN_MONTHS[i],
+        }
+    }
+
+    /// the abbreviated name of `month` like `Mar` or `Mär`
+    pub fn short_month_name(&self, month: u32) -> &'static str {
+        let i = month as usize - 1;
+        match self {
+            Locale::English => &ENGLISH_MONTHS[i][..3],
+            Locale::German => GERMAN_SHORT_MONTHS[i],
+        }
+    }
+
+    /// the name of `weekday` like `Monday` or `Montag`
+    pub fn weekday_name(&self, weekday: Weekday) -> &'static str {
+        let i = weekday as usize;
+        match self {
+            Locale::English => ENGLISH_WEEKDAYS[i],
+            Locale::German => GERMAN_WEEKDAYS[i],
+        }
+    }
+
+    /// the abbreviated name of `weekday` like `Mon` or `Mo`
+    pub fn short_weekday_name(&self, weekday: Weekday) -> &'static str {
+        let i = weekday as usize;
+        match self {
+            Locale::English => &ENGLISH_WEEKDAYS[i][..3],
+            Locale::German => GERMAN_SHORT_WEEKDAYS[i],
+        }
+    }
+
+    /// translates the lowercase `words` of a relative date into english, see
+    /// [`Date::parse_localized`]
+    pub(crate) fn translate<'a>(&self, words: Vec<&'a str>) -> Vec<&'a str> {
+        if *self == Locale::English {
+            return words;
+        }
+
+        let mut words: Vec<&str> = words
+            .into_iter()
+            .map(|word| {
+                GERMAN_WORDS
+                    .iter()
+                    .find(|(german, _)| *german == word)
+                    .map_or(word, |(_, english)| *english)
+            })
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        // `vor 3 tagen` is `3 days ago`
+        if let ["vor", amount, unit] = words[..] {
+            words = vec![amount, unit, "ago"];
+        }
+
+        // `ende nächsten monats` is `end of next month`
+        if let ["end" | "start", next, ..] = words[..]
+            && next != "of"
+        {
+            words.insert(1, "of");
+        }
+
+        words
+    }
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Locale::English => write!(f, "en"),
+            Locale::German => write!(f, "de"),
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = ParseError;
+
+    /// parses `en` or `de`, also as part of a locale name like `de_DE.UTF-8`, or `english` or
+    /// `german`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let language = trimmed
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        match language.as_str() {
+            "en" | "english" | "c" | "posix" => Ok(Locale::English),
+            "de" | "german" | "deutsch" => Ok(Locale::German),
+            _ => {
+                let kind = ParseErrorKind::Unknown {
+                    what: "locale",
+                    expected: "en or de",
+                };
+                Err(ParseError::new(s, trimmed, kind))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Ok(Locale::German), "de".parse());
+        assert_eq!(Ok(Locale::German), "de_DE.UTF-8".parse());
+        assert_eq!(Ok(Locale::English), "en_GB".parse());
+        assert_eq!(Ok(Locale::English), "C.UTF-8".parse());
+        assert!("fr_FR".parse::<Locale>().is_err());
+
+        for locale in [Locale::English, Locale::German] {
+            assert_eq!(Ok(locale), locale.to_string().parse());
+        }
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!("March", Locale::English.month_name(3));
+        assert_eq!("Mär", Locale::German.short_month_name(3));
+        assert_eq!("Dec", Locale::English.short_month_name(12));
+        assert_eq!("Sonntag", Locale::German.weekday_name(Weekday::Sunday));
+        assert_eq!("Mo", Locale::German.short_weekday_name(Weekday::Monday));
+        assert_eq!("Thu", Locale::English.short_weekday_name(Weekday::Thursday));
+    }
+
+    #[test]
+    fn test_translate() {
+        fn translate(s: &str) -> Vec<&str> {
+            Locale::German.translate(s.split_whitespace().collect())
+        }
+        assert_eq!(vec!["next", "monday"], translate("nächsten montag"));
+        assert_eq!(vec!["3", "days", "ago"], translate("vor 3 tagen"));
+        assert_eq!(
+            vec!["end", "of", "next", "month"],
+            translate("ende nächsten monats")
+        );
+        assert_eq!(vec!["end", "of", "month"], translate("ende des monats"));
+        assert_eq!(vec!["tomorrow"], translate("morgen um"));
+        assert_eq!(vec!["next", "week"], translate("next week"));
+    }
+}
@@ -13,6 +13,29 @@ pub use datetime::DateTime;
 mod duration;
 pub use duration::Duration;
 
+mod error;
+pub(crate) use error::parse_number;
+pub use error::{ParseError, ParseErrorKind};
+
+mod format;
+pub use format::Format;
+
+mod range;
+pub use range::{DateRange, DateTimeRange, Days};
+
+mod iso;
+
+mod locale;
+pub use locale::Locale;
+
+mod relative;
+
+mod weekday;
+pub use weekday::Weekday;
+
+mod zone;
+pub use zone::{Zone, ZonedDateTime};
+
 pub mod prelude {
     pub use super::Duration;
 
@@ -50,6 +73,25 @@ pub mod prelude {
 
         fn year_begin(&self) -> Self;
         fn year_end(&self) -> Self;
+
+        fn weekday(&self) -> Weekday;
+
+        /// the ISO 8601 week, weeks start on monday and the first week of a year is the one
+        /// containing its first thursday
+        ///
+        /// # Returns
+        /// - the year the week belongs to, which differs from the calendar year for some days
+        ///   around new year, and the number of the week starting at `1`
+        /// - week `0` of year `0` for the days before the first week of year `0`
+        fn iso_week(&self) -> (u32, u32);
+
+        /// the number of the day in its year, starting at `1` for the first of january
+        fn day_of_year(&self) -> u32;
+
+        /// the first day of the week, weeks start on `first`
+        fn week_begin(&self, first: Weekday) -> Self;
+        /// the last day of the week, weeks start on `first`
+        fn week_end(&self, first: Weekday) -> Self;
     }
 
     pub trait TimeLike {
@@ -63,5 +105,10 @@ pub mod prelude {
 
     pub use super::Date;
     pub use super::DateTime;
+    pub use super::Locale;
     pub use super::Time;
+    pub use super::Weekday;
+    pub use super::Zone;
+    pub use super::ZonedDateTime;
+    pub use super::{DateRange, DateTimeRange};
 }
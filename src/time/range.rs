@@ -0,0 +1,420 @@
+use std::fmt::Display;
+
+use super::prelude::*;
+
+/// a half open range of days `[begin, end)`
+///
+/// Ranges with `end` not after `begin` are empty.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DateRange {
+    pub begin: Date,
+    pub end: Date,
+}
+
+impl DateRange {
+    pub fn new(begin: Date, end: Date) -> Self {
+        Self { begin, end }
+    }
+
+    /// the single day `date`
+    pub fn day(date: Date) -> Self {
+        Self::new(date, date.next_day())
+    }
+
+    /// the week around `date` starting on `first`
+    pub fn week(date: Date, first: Weekday) -> Self {
+        Self::new(date.week_begin(first), date.week_end(first).next_day())
+    }
+
+    /// the month around `date`
+    pub fn month(date: Date) -> Self {
+        Self::new(date.month_begin(), date.month_end().next_day())
+    }
+
+    /// the year around `date`
+    pub fn year(date: Date) -> Self {
+        Self::new(date.year_begin(), date.year_end().next_day())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.begin
+    }
+
+    /// the number of days in this range
+    pub fn len(&self) -> u32 {
+        self.begin.days_until(self.end).max(0) as u32
+    }
+
+    pub fn contains(&self, date: Date) -> bool {
+        self.begin <= date && date < self.end
+    }
+
+    /// whether this and `other` have at least one day in common
+    pub fn overlaps(&self, other: &DateRange) -> bool {
+        self.intersect(other).is_some()
+    }
+
+    /// the days in both this range and `other`, `None` if there are none
+    pub fn intersect(&self, other: &DateRange) -> Option<DateRange> {
+        let range = Self::new(self.begin.max(other.begin), self.end.min(other.end));
+        (!range.is_empty()).then_some(range)
+    }
+
+    /// the days in either this range or `other`
+    ///
+    /// # Returns
+    /// - `None` if the ranges neither overlap nor touch, their union is no range
+    pub fn union(&self, other: &DateRange) -> Option<DateRange> {
+        let union = self.to_datetime_range().union(&other.to_datetime_range())?;
+        Some(Self::new(union.begin.date(), union.end.date()))
+    }
+
+    /// an iterator over the days in this range, from the first to the last
+    pub fn days(&self) -> Days {
+        Days {
+            next: self.begin,
+            end: self.end,
+        }
+    }
+
+    /// the date times from the start of the first day to the start of the day after the last one
+    pub fn to_datetime_range(&self) -> DateTimeRange {
+        DateTimeRange::new(
+            self.begin.with_time(Time::MIN),
+            self.end.with_time(Time::MIN),
+        )
+    }
+}
+
+impl IntoIterator for DateRange {
+    type Item = Date;
+    type IntoIter = Days;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.days()
+    }
+}
+
+impl Display for DateRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.begin, self.end)
+    }
+}
+
+/// the days of a [`DateRange`], see [`DateRange::days`]
+#[derive(Debug, Clone)]
+pub struct Days {
+    next: Date,
+    end: Date,
+}
+
+impl Iterator for Days {
+    type Item = Date;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.end {
+            return None;
+        }
+        let day = self.next;
+        self.next = day.next_day();
+        Some(day)
+    }
+}
+
+/// a half open range of date times `[begin, end)`
+///
+/// Open ended ranges use [`DateTime::MIN`] and [`DateTime::MAX`] as bounds. Ranges with `end` not
+/// after `begin` are empty.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DateTimeRange {
+    pub begin: DateTime,
+    pub end: DateTime,
+}
+
+impl DateTimeRange {
+    /// every date time
+    pub const ALL: DateTimeRange = DateTimeRange {
+        begin: DateTime::MIN,
+        end: DateTime::MAX,
+    };
+
+    pub fn new(begin: DateTime, end: DateTime) -> Self {
+        Self { begin, end }
+    }
+
+    /// every date time from `begin` on
+    pub fn starting_at(begin: DateTime) -> Self {
+        Self::new(begin, DateTime::MAX)
+    }
+
+    /// every date time before `end`
+    pub fn before(end: DateTime) -> Self {
+        Self::new(DateTime::MIN, end)
+    }
+
+    /// the day `date` from midnight to midnight
+    pub fn day(date: Date) -> Self {
+        DateRange::day(date).to_datetime_range()
+    }
+
+    /// the week around `date` starting on `first`, see [`DateRange::week`]
+    pub fn week(date: Date, first: Weekday) -> Self {
+        DateRange::week(date, first).to_datetime_range()
+    }
+
+    /// the month around `date`
+    pub fn month(date: Date) -> Self {
+        DateRange::month(date).to_datetime_range()
+    }
+
+    /// the year around `date`
+    pub fn year(date: Date) -> Self {
+        DateRange::year(date).to_datetime_range()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.begin
+    }
+
+    pub fn contains(&self, at: DateTime) -> bool {
+        self.begin <= at && at < self.end
+    }
+
+    /// whether this and `other` have at least one date time in common
+    pub fn overlaps(&self, other: &DateTimeRange) -> bool {
+        self.intersect(other).is_some()
+    }
+
+    /// the date times in both this range and `other`, `None` if there are none
+    pub fn intersect(&self, other: &DateTimeRange) -> Option<DateTimeRange> {
+        let range = Self::new(self.begin.max(other.begin), self.end.min(other.end));
+        (!range.is_empty()).then_some(range)
+    }
+
+    /// the date times in either this range or `other`
+    ///
+    /// Empty ranges are ignored.
+    ///
+    /// # Returns
+    /// - `None` if the ranges neither overlap nor touch, their union is no range
+    pub fn union(&self, other: &DateTimeRange) -> Option<DateTimeRange> {
+        if other.is_empty() {
+            return Some(*self);
+        }
+        if self.is_empty() {
+            return Some(*other);
+        }
+        if self.end < other.begin || other.end < self.begin {
+            return None;
+        }
+        Some(Self::new(
+            self.begin.min(other.begin),
+            self.end.max(other.end),
+        ))
+    }
+
+    /// the days this range touches, from the day of `begin` up to the day of the last minute
+    /// before `end`
+    pub fn dates(&self) -> DateRange {
+        if self.is_empty() {
+            return DateRange::new(self.begin.date(), self.begin.date());
+        }
+
+        let end = if self.end.time() == Time::MIN {
+            self.end.date()
+        } else {
+            self.end.date().next_day()
+        };
+        DateRange::new(self.begin.date(), end)
+    }
+
+    /// this range cut at every midnight, the parts in order without empty ones
+    pub fn split_by_day(&self) -> impl Iterator<Item = DateTimeRange> + use<> {
+        let range = *self;
+        self.dates()
+            .days()
+            .filter_map(move |day| range.intersect(&DateTimeRange::day(day)))
+    }
+}
+
+impl Display for DateTimeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.begin, self.end)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    fn range(begin: &str, end: &str) -> DateTimeRange {
+        DateTimeRange::new(at(begin), at(end))
+    }
+
+    #[test]
+    fn test_constructors() {
+        let day = date("2025-03-05");
+        assert_eq!(
+            DateRange::new(date("2025-03-05"), date("2025-03-06")),
+            DateRange::day(day)
+        );
+        assert_eq!(
+            DateRange::new(date("2025-03-03"), date("2025-03-10")),
+            DateRange::week(day, Weekday::Monday)
+        );
+        assert_eq!(
+            DateRange::new(date("2025-03-02"), date("2025-03-09")),
+            DateRange::week(day, Weekday::Sunday)
+        );
+        assert_eq!(
+            DateRange::new(date("2025-03-01"), date("2025-04-01")),
+            DateRange::month(day)
+        );
+        assert_eq!(
+            DateRange::new(date("2025-01-01"), date("2026-01-01")),
+            DateRange::year(day)
+        );
+
+        assert_eq!(range("2025-03-05", "2025-03-06"), DateTimeRange::day(day));
+        assert_eq!(
+            range("2025-12-01", "2026-01-01"),
+            DateTimeRange::month(date("2025-12-31"))
+        );
+        assert_eq!(31, DateRange::month(day).len());
+    }
+
+    #[test]
+    fn test_contains() {
+        let day = DateTimeRange::day(date("2025-03-05"));
+        assert!(day.contains(at("2025-03-05 00:00")));
+        assert!(day.contains(at("2025-03-05 23:59")));
+        assert!(!day.contains(at("2025-03-06 00:00")));
+        assert!(!day.contains(at("2025-03-04 23:59")));
+
+        let week = DateRange::week(date("2025-03-05"), Weekday::Monday);
+        assert!(week.contains(date("2025-03-09")));
+        assert!(!week.contains(date("2025-03-10")));
+
+        assert!(DateTimeRange::ALL.contains(DateTime::MIN));
+        assert!(DateTimeRange::before(at("2025-03-01")).contains(at("2025-02-28 23:59")));
+        assert!(DateTimeRange::starting_at(at("2025-03-01")).contains(at("2025-03-01")));
+    }
+
+    #[test]
+    fn test_overlaps_intersect() {
+        let a = range("2025-03-01 10:00", "2025-03-01 12:00");
+        let b = range("2025-03-01 11:00", "2025-03-01 13:00");
+        let c = range("2025-03-01 12:00", "2025-03-01 14:00");
+
+        assert!(a.overlaps(&b));
+        assert!(!a.overlaps(&c));
+        assert!(!a.overlaps(&range("2025-03-01 11:00", "2025-03-01 11:00")));
+
+        assert_eq!(
+            Some(range("2025-03-01 11:00", "2025-03-01 12:00")),
+            a.intersect(&b)
+        );
+        assert_eq!(None, a.intersect(&c));
+
+        let march = DateRange::month(date("2025-03-01"));
+        let week = DateRange::week(date("2025-03-31"), Weekday::Monday);
+        assert!(march.overlaps(&week));
+        assert_eq!(
+            Some(DateRange::day(date("2025-03-31"))),
+            march.intersect(&week)
+        );
+        assert!(!march.overlaps(&DateRange::month(date("2025-04-01"))));
+    }
+
+    #[test]
+    fn test_union() {
+        let a = range("2025-03-01 10:00", "2025-03-01 12:00");
+        let b = range("2025-03-01 11:00", "2025-03-01 13:00");
+        let c = range("2025-03-01 12:00", "2025-03-01 14:00");
+        let d = range("2025-03-01 15:00", "2025-03-01 16:00");
+
+        assert_eq!(
+            Some(range("2025-03-01 10:00", "2025-03-01 13:00")),
+            a.union(&b)
+        );
+        assert_eq!(
+            Some(range("2025-03-01 10:00", "2025-03-01 14:00")),
+            a.union(&c)
+        );
+        assert_eq!(None, a.union(&d));
+        assert_eq!(Some(a), a.union(&range("2025-03-02", "2025-03-01")));
+
+        let march = DateRange::month(date("2025-03-01"));
+        let april = DateRange::month(date("2025-04-01"));
+        assert_eq!(
+            Some(DateRange::new(date("2025-03-01"), date("2025-05-01"))),
+            march.union(&april)
+        );
+        assert_eq!(None, march.union(&DateRange::month(date("2025-05-01"))));
+    }
+
+    #[test]
+    fn test_days() {
+        let days: Vec<Date> = DateRange::new(date("2025-02-27"), date("2025-03-02"))
+            .days()
+            .collect();
+        assert_eq!(
+            vec![date("2025-02-27"), date("2025-02-28"), date("2025-03-01")],
+            days
+        );
+        assert_eq!(
+            0,
+            DateRange::day(date("2025-03-01"))
+                .into_iter()
+                .skip(1)
+                .count()
+        );
+        assert_eq!(
+            0,
+            DateRange::new(date("2025-03-02"), date("2025-03-01"))
+                .days()
+                .count()
+        );
+
+        assert_eq!(
+            DateRange::new(date("2025-03-01"), date("2025-03-03")),
+            range("2025-03-01 22:00", "2025-03-02 01:00").dates()
+        );
+        assert_eq!(
+            DateRange::day(date("2025-03-01")),
+            range("2025-03-01 22:00", "2025-03-02 00:00").dates()
+        );
+        assert!(
+            range("2025-03-01 22:00", "2025-03-01 22:00")
+                .dates()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_split_by_day() {
+        let parts: Vec<_> = range("2025-03-01 22:00", "2025-03-03 01:00")
+            .split_by_day()
+            .collect();
+        assert_eq!(
+            vec![
+                range("2025-03-01 22:00", "2025-03-02 00:00"),
+                range("2025-03-02 00:00", "2025-03-03 00:00"),
+                range("2025-03-03 00:00", "2025-03-03 01:00"),
+            ],
+            parts
+        );
+
+        let day = DateTimeRange::day(date("2025-03-01"));
+        assert_eq!(vec![day], day.split_by_day().collect::<Vec<_>>());
+        assert_eq!(0, range("2025-03-02", "2025-03-01").split_by_day().count());
+    }
+}
@@ -0,0 +1,308 @@
+//! relative dates like `next monday`, `in 2 weeks` or `first friday of next month`
+//!
+//! Weeks start on monday here, for other first days see [`DateLike::week_begin`].
+
+use super::iso;
+use super::prelude::*;
+
+/// parses `day(s)`, `week(s)`, `month(s)` or `year(s)`, or their first letter
+fn parse_unit(unit: &str) -> Option<fn(i32) -> Duration> {
+    let unit = unit.strip_suffix('s').unwrap_or(unit);
+    match unit {
+        "d" | "day" => Some(Duration::Day),
+        "w" | "week" => Some(Duration::Week),
+        "m" | "month" => Some(Duration::Month),
+        "y" | "year" => Some(Duration::Year),
+        _ => None,
+    }
+}
+
+/// parses an amount and a unit like `2 weeks` or `a month`
+fn parse_amount(amount: &str, unit: &str) -> Option<Duration> {
+    let amount = match amount {
+        "a" | "an" | "one" => 1,
+        amount => amount.parse().ok().filter(|amount| *amount >= 0)?,
+    };
+    parse_unit(unit).map(|unit| unit(amount))
+}
+
+/// parses `first` up to `fifth`, `1st` up to `5th` and `last` as `-1`
+fn parse_ordinal(ordinal: &str) -> Option<i32> {
+    let n = match ordinal {
+        "first" | "1st" => 1,
+        "second" | "2nd" => 2,
+        "third" | "3rd" => 3,
+        "fourth" | "4th" => 4,
+        "fifth" | "5th" => 5,
+        "last" => -1,
+        _ => return None,
+    };
+    Some(n)
+}
+
+/// the number of days from `from` forward to the next `to`, from `1` up to `7`
+fn days_between(from: Weekday, to: Weekday) -> i32 {
+    match to.days_since(from) {
+        0 => 7,
+        days => days as i32,
+    }
+}
+
+/// the first and last day of a period like `week`, `next month` or `last year`
+fn parse_period(period: &[&str], today: Date) -> Option<(Date, Date)> {
+    let (shift, unit) = match period {
+        [unit] | ["this", unit] => (0, unit),
+        ["next", unit] => (1, unit),
+        ["last", unit] => (-1, unit),
+        _ => return None,
+    };
+
+    let day = today.checked_add_duration(parse_unit(unit)?(shift))?;
+    let period = match parse_unit(unit)?(1) {
+        Duration::Day(_) => (day, day),
+        Duration::Week(_) => (
+            day.week_begin(Weekday::Monday),
+            day.week_end(Weekday::Monday),
+        ),
+        Duration::Month(_) => (day.month_begin(), day.month_end()),
+        Duration::Year(_) => (day.year_begin(), day.year_end()),
+        _ => return None,
+    };
+    Some(period)
+}
+
+/// the `n`th `weekday` in the month of `day`, counting from the end if `n` is negative
+fn nth_weekday_of_month(n: i32, weekday: Weekday, day: Date) -> Option<Date> {
+    if n < 0 {
+        let end = day.month_end();
+        let last = end.add_days(-(end.weekday().days_since(weekday) as i32));
+        return last
+            .checked_add_days((n + 1) * 7)
+            .filter(|date| date.month() == day.month());
+    }
+
+    let begin = day.month_begin();
+    let first = begin.checked_add_days(weekday.days_since(begin.weekday()) as i32)?;
+    first
+        .checked_add_days((n - 1) * 7)
+        .filter(|date| date.month() == day.month())
+}
+
+/// parses a single word like `today`, `fri`, `+3d`, an ISO date like `2025-03-01` or `2025-W09-6`,
+/// or `2025-03-01+1w`
+fn parse_word(word: &str, today: Date) -> Option<Date> {
+    match word {
+        "today" => return Some(today),
+        "yesterday" => return today.checked_add_days(-1),
+        "tomorrow" => return today.checked_add_days(1),
+        _ => (),
+    }
+
+    // the next one, which is today on that weekday
+    if let Ok(weekday) = word.parse::<Weekday>() {
+        return today.checked_add_days(weekday.days_since(today.weekday()) as i32);
+    }
+
+    if let Ok(duration) = word.parse::<Duration>() {
+        return today.checked_add_duration(duration);
+    }
+
+    if let Ok(date) = iso::parse_date(word, word) {
+        return Some(date);
+    }
+
+    // a date followed by a duration, like `2025-03-01+1w` or `fri-2d`
+    word.char_indices()
+        .filter(|(i, c)| *i > 0 && (*c == '+' || *c == '-'))
+        .find_map(|(i, _)| {
+            let date = parse_word(&word[..i], today)?;
+            let duration: Duration = word[i..].parse().ok()?;
+            date.checked_add_duration(duration)
+        })
+}
+
+/// the date `s` describes as seen on `today`, see [`Date::parse_localized`]
+pub fn parse_date(s: &str, today: Date, locale: Locale) -> Option<Date> {
+    let s = s.trim().to_lowercase();
+    let words: Vec<&str> = s.split_whitespace().filter(|word| *word != "the").collect();
+    let words = locale.translate(words);
+
+    match words.as_slice() {
+        [word] => parse_word(word, today),
+
+        ["in", duration] => today.checked_add_duration(duration.parse().ok()?),
+        ["in", amount, unit] => today.checked_add_duration(parse_amount(amount, unit)?),
+        [amount, unit, "ago"] => today.checked_add_duration(parse_amount(amount, unit)? * -1),
+
+        ["next", word] => match word.parse::<Weekday>() {
+            Ok(weekday) => today.checked_add_days(days_between(today.weekday(), weekday)),
+            Err(()) => today.checked_add_duration(parse_unit(word)?(1)),
+        },
+        ["last", word] => match word.parse::<Weekday>() {
+            Ok(weekday) => today.checked_add_days(-days_between(weekday, today.weekday())),
+            Err(()) => today.checked_add_duration(parse_unit(word)?(-1)),
+        },
+        ["this", weekday] => {
+            let begin = today.week_begin(Weekday::Monday);
+            let weekday: Weekday = weekday.parse().ok()?;
+            begin.checked_add_days(weekday.days_since(Weekday::Monday) as i32)
+        }
+
+        ["end", "of", period @ ..] => parse_period(period, today).map(|(_, end)| end),
+        ["start" | "beginning", "of", period @ ..] => {
+            parse_period(period, today).map(|(begin, _)| begin)
+        }
+
+        [ordinal, weekday, "of", period @ ..] => {
+            if parse_unit(period.last()?)?(1) != Duration::Month(1) {
+                return None;
+            }
+            let (begin, _) = parse_period(period, today)?;
+            nth_weekday_of_month(parse_ordinal(ordinal)?, weekday.parse().ok()?, begin)
+        }
+
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        iso::parse_date(s, s).unwrap()
+    }
+
+    /// resolves against saturday 2025-03-01
+    fn parse(s: &str) -> Option<Date> {
+        parse_date(s, date("2025-03-01"), Locale::English)
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(Some(date("2025-03-01")), parse("today"));
+        assert_eq!(Some(date("2025-02-28")), parse("yesterday"));
+        assert_eq!(Some(date("2025-03-04")), parse("+3d"));
+        assert_eq!(Some(date("2025-04-15")), parse("2025-04-15"));
+
+        assert_eq!(Some(date("2025-03-07")), parse("fri"));
+        assert_eq!(Some(date("2025-03-01")), parse("Saturday"));
+        assert_eq!(Some(date("2025-03-03")), parse("mon"));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(Some(date("2025-03-08")), parse("2025-03-01+1w"));
+        assert_eq!(Some(date("2025-02-22")), parse("2025-03-01-1w"));
+        assert_eq!(Some(date("2025-03-10")), parse("today+1w+2d"));
+        assert_eq!(Some(date("2025-03-05")), parse("fri-2d"));
+        assert_eq!(None, parse("2025-03-01+1x"));
+    }
+
+    #[test]
+    fn test_in_ago() {
+        assert_eq!(Some(date("2025-03-15")), parse("in 2 weeks"));
+        assert_eq!(Some(date("2025-03-15")), parse("in 2w"));
+        assert_eq!(Some(date("2025-04-01")), parse("in a month"));
+        assert_eq!(Some(date("2025-02-26")), parse("3 days ago"));
+        assert_eq!(None, parse("in -2 weeks"));
+        assert_eq!(None, parse("in 2 fortnights"));
+    }
+
+    #[test]
+    fn test_next_last() {
+        assert_eq!(Some(date("2025-03-03")), parse("next monday"));
+        assert_eq!(Some(date("2025-03-08")), parse("next sat"));
+        assert_eq!(Some(date("2025-02-22")), parse("last sat"));
+        assert_eq!(Some(date("2025-02-28")), parse("last friday"));
+        assert_eq!(Some(date("2025-02-24")), parse("this monday"));
+        assert_eq!(Some(date("2025-03-08")), parse("next week"));
+        assert_eq!(Some(date("2025-02-01")), parse("last month"));
+    }
+
+    #[test]
+    fn test_periods() {
+        assert_eq!(Some(date("2025-03-31")), parse("end of month"));
+        assert_eq!(Some(date("2025-03-31")), parse("end of the month"));
+        assert_eq!(Some(date("2025-04-30")), parse("end of next month"));
+        assert_eq!(Some(date("2025-03-02")), parse("end of week"));
+        assert_eq!(Some(date("2025-03-03")), parse("start of next week"));
+        assert_eq!(Some(date("2025-01-01")), parse("beginning of year"));
+        assert_eq!(Some(date("2024-12-31")), parse("end of last year"));
+        assert_eq!(None, parse("end of time"));
+    }
+
+    #[test]
+    fn test_nth_weekday() {
+        assert_eq!(
+            Some(date("2025-04-07")),
+            parse("first monday of next month")
+        );
+        assert_eq!(Some(date("2025-03-03")), parse("1st mon of month"));
+        assert_eq!(Some(date("2025-03-28")), parse("last friday of this month"));
+        assert_eq!(Some(date("2025-03-31")), parse("fifth monday of month"));
+        assert_eq!(None, parse("fifth tuesday of month"));
+        assert_eq!(Some(date("2025-02-25")), parse("last tue of last month"));
+        assert_eq!(None, parse("first monday of next week"));
+        assert_eq!(None, parse("first day of month"));
+    }
+
+    #[test]
+    fn test_fail() {
+        assert_eq!(None, parse(""));
+        assert_eq!(None, parse("someday"));
+        assert_eq!(None, parse("next"));
+        assert_eq!(None, parse("2025-02-30"));
+        assert_eq!(None, parse("2025-03-01-01"));
+        assert_eq!(None, parse("näxt mön"));
+    }
+
+    #[test]
+    fn test_german() {
+        let parse = |s| parse_date(s, date("2025-03-01"), Locale::German);
+        assert_eq!(Some(date("2025-03-01")), parse("heute"));
+        assert_eq!(Some(date("2025-03-02")), parse("Morgen"));
+        assert_eq!(Some(date("2025-03-03")), parse("übermorgen"));
+        assert_eq!(Some(date("2025-03-03")), parse("nächsten Montag"));
+        assert_eq!(Some(date("2025-03-07")), parse("fr"));
+        assert_eq!(Some(date("2025-02-28")), parse("letzten freitag"));
+        assert_eq!(Some(date("2025-03-15")), parse("in 2 wochen"));
+        assert_eq!(Some(date("2025-04-01")), parse("in einem monat"));
+        assert_eq!(Some(date("2025-02-26")), parse("vor 3 tagen"));
+        assert_eq!(Some(date("2025-03-31")), parse("ende des monats"));
+        assert_eq!(Some(date("2025-04-30")), parse("ende nächsten monats"));
+        assert_eq!(Some(date("2025-03-03")), parse("anfang nächster woche"));
+        assert_eq!(
+            Some(date("2025-04-07")),
+            parse("erster montag im nächsten monat")
+        );
+        assert_eq!(Some(date("2025-03-02")), parse("tomorrow"));
+
+        let english = |s| parse_date(s, date("2025-03-01"), Locale::English);
+        assert_eq!(None, english("morgen"));
+        assert_eq!(None, english("nächsten montag"));
+    }
+
+    #[test]
+    fn test_datetime() {
+        let now = date("2025-03-01").with_time(Time::from_hm(9, 30).unwrap());
+        let parse = |s| DateTime::parse_relative(s, now);
+        let at = |date_s, h, m| Some(date(date_s).with_time(Time::from_hm(h, m).unwrap()));
+
+        assert_eq!(Some(now), parse("now"));
+        assert_eq!(at("2025-03-03", 14, 0), parse("mon 14:00"));
+        assert_eq!(at("2025-03-03", 8, 15), parse("next monday 08:15"));
+        assert_eq!(at("2025-03-31", 0, 0), parse("end of month"));
+        assert_eq!(at("2025-03-08", 12, 0), parse("2025-03-01+1w 12:00"));
+        assert_eq!(at("2025-03-03", 14, 30), parse("mon 2:30pm"));
+        assert_eq!(at("2025-03-03", 14, 30), parse("mon 2:30 PM"));
+        assert_eq!(at("2025-03-02", 9, 0), parse("tomorrow 9am"));
+        assert!(parse("mon 14:00 extra").is_none());
+        assert!(parse("14:00").is_none());
+
+        let parse = |s| DateTime::parse_localized(s, now, Locale::German);
+        assert_eq!(Some(now), parse("jetzt"));
+        assert_eq!(at("2025-03-02", 14, 0), parse("morgen um 14:00"));
+        assert_eq!(at("2025-03-03", 8, 15), parse("nächsten Montag 08:15"));
+    }
+}
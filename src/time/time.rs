@@ -1,4 +1,6 @@
+use super::error::parse_number;
 use super::prelude::*;
+use super::{ParseError, ParseErrorKind, iso};
 use std::cmp::Ordering;
 use std::fmt::Display;
 use std::str::FromStr;
@@ -58,6 +60,15 @@ fn is_valid_time(hour: u32, minute: u32) -> bool {
 }
 
 impl Time {
+    /// midnight, the first minute of a day
+    pub const MIN: Time = Time { hour: 0, minute: 0 };
+
+    /// the last minute of a day
+    pub const MAX: Time = Time {
+        hour: 23,
+        minute: 59,
+    };
+
     /// creates a new valid time
     ///
     /// # Returns
@@ -82,6 +93,17 @@ impl Time {
         let minute = minute as u8;
         Some(Self { hour, minute })
     }
+
+    /// parses an ISO 8601 time like `14:30`, `14:30:00`, `14:30:00.250` or `143000`, seconds are
+    /// dropped
+    pub fn parse_iso(s: &str) -> Result<Self, ParseError> {
+        iso::parse_time(s, s.trim())
+    }
+
+    /// formats as ISO 8601 time with seconds like `14:30:00`
+    pub fn format_iso(&self) -> String {
+        format!("{}:00", self)
+    }
 }
 
 impl Display for Time {
@@ -90,17 +112,79 @@ impl Display for Time {
     }
 }
 
+/// splits a trailing `am` or `pm` like in `2:30pm` off `s`
+///
+/// # Returns
+/// - the rest of `s` and whether it is `pm`
+/// - `None` if there is neither `am` nor `pm`
+fn split_meridiem(s: &str) -> Option<(&str, bool)> {
+    let lower = s.to_ascii_lowercase();
+    [("am", false), ("pm", true), ("a.m.", false), ("p.m.", true)]
+        .into_iter()
+        .find(|(suffix, _)| lower.ends_with(suffix))
+        .map(|(suffix, pm)| (s[..s.len() - suffix.len()].trim_end(), pm))
+}
+
 impl FromStr for Time {
-    type Err = ();
+    type Err = ParseError;
 
+    /// parses `HH:MM`, optionally followed by seconds like `14:30:00` which are dropped, or a time
+    /// on a 12-hour clock like `2:30pm`, `2:30 PM` or `2pm`
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (hour, minute) = s.trim().split_once(":").ok_or(())?;
-        let hour = hour.trim().parse().map_err(|_| ())?;
-        let minute = minute.trim().parse().map_err(|_| ())?;
-        Self::from_hm(hour, minute).ok_or(())
+        let trimmed = s.trim();
+        if trimmed.is_empty() {
+            return Err(ParseError::new(s, trimmed, ParseErrorKind::Empty));
+        }
+
+        let (clock, pm) = match split_meridiem(trimmed) {
+            Some((clock, pm)) => (clock, Some(pm)),
+            None => (trimmed, None),
+        };
+
+        let (hour, minute) = match (clock.split_once(':'), pm) {
+            (Some((hour, minute)), _) => (hour, minute),
+            (None, Some(_)) => (clock, "0"),
+            (None, None) => {
+                let expected = ParseErrorKind::Expected("a time like 14:30 or 2:30pm");
+                return Err(ParseError::new(s, trimmed, expected));
+            }
+        };
+        let (minute, second) = match minute.split_once(':') {
+            Some((minute, second)) => (minute, Some(second.trim())),
+            None => (minute, None),
+        };
+        let (hour, minute) = (hour.trim(), minute.trim());
+
+        let out_of_range = |token, field, value| {
+            let kind = ParseErrorKind::OutOfRange { field, value };
+            Err(ParseError::new(s, token, kind))
+        };
+
+        let mut hour_value: u32 = parse_number(s, hour)?;
+        match pm {
+            Some(_) if !(1..=12).contains(&hour_value) => {
+                return out_of_range(hour, "hour", hour_value as u64);
+            }
+            Some(pm) => hour_value = hour_value % 12 + if pm { 12 } else { 0 },
+            None if hour_value > 23 => return out_of_range(hour, "hour", hour_value as u64),
+            None => (),
+        }
+
+        let minute_value: u32 = parse_number(s, minute)?;
+        if minute_value > 59 {
+            return out_of_range(minute, "minute", minute_value as u64);
+        }
+
+        if let Some(second) = second {
+            iso::parse_second(s, second)?;
+        }
+
+        Ok(Self::from_hm(hour_value, minute_value).expect("hour and minute are in range"))
     }
 }
 
+serde_with_str!(Time, Time::parse_iso);
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -131,6 +215,38 @@ mod test {
         assert_eq!(time(12, 30), "  12:30  ".parse().unwrap());
         assert_eq!(time(12, 30), "12:30  ".parse().unwrap());
         assert_eq!(time(12, 30), "  12:30".parse().unwrap());
+        assert_eq!(time(12, 30), "12:30:59".parse().unwrap());
+    }
+
+    #[test]
+    fn test_parse_12_hour() {
+        let time = |hour, minute| Ok(Time::from_hm(hour, minute).unwrap());
+        assert_eq!(time(14, 30), "2:30pm".parse());
+        assert_eq!(time(14, 30), " 2:30 PM ".parse());
+        assert_eq!(time(2, 30), "2:30 a.m.".parse());
+        assert_eq!(time(14, 0), "2pm".parse());
+        assert_eq!(time(0, 15), "12:15am".parse());
+        assert_eq!(time(12, 0), "12pm".parse());
+
+        let err = "13:00pm".parse::<Time>().unwrap_err();
+        assert_eq!(
+            "hour 13 out of range in '13:00pm' at position 0",
+            err.to_string()
+        );
+        assert!("0am".parse::<Time>().is_err());
+        assert!("pm".parse::<Time>().is_err());
+        assert!("2:60pm".parse::<Time>().is_err());
+    }
+
+    #[test]
+    fn test_iso() {
+        let time = Time::from_hm(14, 30).unwrap();
+        assert_eq!(Ok(time), Time::parse_iso("14:30:00.250"));
+        assert_eq!(Ok(time), Time::parse_iso("1430"));
+        assert!(Time::parse_iso("9:30").is_err());
+        assert!("1430".parse::<Time>().is_err());
+        assert!("14:30:60.5x".parse::<Time>().is_err());
+        assert_eq!("14:30:00", time.format_iso());
     }
 
     #[test]
@@ -139,6 +255,14 @@ mod test {
         assert!("12:".parse::<Time>().is_err());
         assert!("12".parse::<Time>().is_err());
         assert!("".parse::<Time>().is_err());
+
+        let err = "24:00".parse::<Time>().unwrap_err();
+        assert_eq!(
+            "hour 24 out of range in '24:00' at position 0",
+            err.to_string()
+        );
+        let err = "12:6o".parse::<Time>().unwrap_err();
+        assert_eq!((3, ParseErrorKind::InvalidNumber), (err.position, err.kind));
     }
 
     #[test]
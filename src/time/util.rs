@@ -1,8 +1,8 @@
 use super::prelude::*;
 
 const MONTH_LENGTHS: [[u32; 12]; 2] = [
-    [21, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31],
-    [21, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31],
+    [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31],
+    [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31],
 ];
 
 pub fn month_len(year: u32, month: u32) -> u32 {
@@ -10,20 +10,9 @@ pub fn month_len(year: u32, month: u32) -> u32 {
     MONTH_LENGTHS[mli][month as usize - 1]
 }
 
+/// the current date time in the local zone, see [`Zone::Local`]
 pub fn now() -> DateTime {
-    use chrono::prelude as chrono;
-    let local = chrono::Local::now().naive_local();
-    let year = <chrono::NaiveDateTime as chrono::Datelike>::year(&local) as u32;
-    let month = <chrono::NaiveDateTime as chrono::Datelike>::month(&local);
-    let day = <chrono::NaiveDateTime as chrono::Datelike>::day(&local);
-
-    let hour = <chrono::NaiveDateTime as chrono::Timelike>::hour(&local);
-    let minute = <chrono::NaiveDateTime as chrono::Timelike>::minute(&local);
-
-    let date = Date::from_ymd(year, month, day).unwrap();
-    let time = Time::from_hm(hour, minute).unwrap();
-
-    DateTime::new(date, time)
+    Zone::Local.now()
 }
 
 pub fn today() -> Date {
@@ -31,15 +20,15 @@ pub fn today() -> Date {
 }
 
 pub fn is_leap_year(year: u32) -> bool {
-    if year % 400 == 0 {
+    if year.is_multiple_of(400) {
         return true;
     }
 
-    if year % 100 == 0 {
+    if year.is_multiple_of(100) {
         return false;
     }
 
-    if year % 4 == 0 {
+    if year.is_multiple_of(4) {
         return true;
     }
 
@@ -55,9 +44,32 @@ pub fn is_valid_date(year: u32, month: u32, day: u32) -> bool {
         return false;
     }
 
-    if !(1..month_len(year, month)).contains(&day) {
+    if !(1..=month_len(year, month)).contains(&day) {
         return false;
     }
 
     true
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_month_len() {
+        assert_eq!(31, month_len(2025, 1));
+        assert_eq!(28, month_len(2025, 2));
+        assert_eq!(29, month_len(2024, 2));
+        assert_eq!(31, month_len(2025, 12));
+    }
+
+    #[test]
+    fn test_is_valid_date() {
+        assert!(is_valid_date(2025, 1, 31));
+        assert!(is_valid_date(2025, 4, 30));
+        assert!(is_valid_date(2024, 2, 29));
+        assert!(!is_valid_date(2025, 2, 29));
+        assert!(!is_valid_date(2025, 4, 31));
+        assert!(!is_valid_date(2025, 1, 0));
+    }
+}
@@ -0,0 +1,124 @@
+use std::{fmt::Display, str::FromStr};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    /// every weekday, starting with monday
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    /// the number of the day in an ISO week, `1` for monday up to `7` for sunday
+    pub fn number_from_monday(&self) -> u32 {
+        *self as u32 + 1
+    }
+
+    /// the weekday with the given number in an ISO week, see [`Weekday::number_from_monday`]
+    pub fn from_number_from_monday(number: u32) -> Option<Self> {
+        Self::ALL.get((number as usize).checked_sub(1)?).copied()
+    }
+
+    /// the number of days from `other` forward to this weekday, from `0` up to `6`
+    pub fn days_since(&self, other: Weekday) -> u32 {
+        (self.number_from_monday() + 7 - other.number_from_monday()) % 7
+    }
+
+    pub fn succ(&self) -> Self {
+        Self::ALL[(*self as usize + 1) % 7]
+    }
+
+    pub fn pred(&self) -> Self {
+        Self::ALL[(*self as usize + 6) % 7]
+    }
+}
+
+impl Display for Weekday {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Weekday::Monday => "monday",
+            Weekday::Tuesday => "tuesday",
+            Weekday::Wednesday => "wednesday",
+            Weekday::Thursday => "thursday",
+            Weekday::Friday => "friday",
+            Weekday::Saturday => "saturday",
+            Weekday::Sunday => "sunday",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for Weekday {
+    type Err = ();
+
+    /// parses full names like `monday` and abbreviations like `mon`, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        let weekday = match s.as_str() {
+            "mon" => Weekday::Monday,
+            "tue" | "tues" => Weekday::Tuesday,
+            "wed" => Weekday::Wednesday,
+            "thu" | "thur" | "thurs" => Weekday::Thursday,
+            "fri" => Weekday::Friday,
+            "sat" => Weekday::Saturday,
+            "sun" => Weekday::Sunday,
+            s => *Self::ALL
+                .iter()
+                .find(|weekday| weekday.to_string() == s)
+                .ok_or(())?,
+        };
+        Ok(weekday)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Ok(Weekday::Monday), "mon".parse());
+        assert_eq!(Ok(Weekday::Monday), "Monday".parse());
+        assert_eq!(Ok(Weekday::Thursday), "thurs".parse());
+        assert_eq!(Ok(Weekday::Sunday), " SUN ".parse());
+        assert!("mo".parse::<Weekday>().is_err());
+        assert!("".parse::<Weekday>().is_err());
+
+        for weekday in Weekday::ALL {
+            assert_eq!(Ok(weekday), weekday.to_string().parse());
+        }
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(1, Weekday::Monday.number_from_monday());
+        assert_eq!(7, Weekday::Sunday.number_from_monday());
+        assert_eq!(
+            Some(Weekday::Wednesday),
+            Weekday::from_number_from_monday(3)
+        );
+        assert_eq!(None, Weekday::from_number_from_monday(0));
+        assert_eq!(None, Weekday::from_number_from_monday(8));
+
+        assert_eq!(0, Weekday::Friday.days_since(Weekday::Friday));
+        assert_eq!(1, Weekday::Monday.days_since(Weekday::Sunday));
+        assert_eq!(6, Weekday::Sunday.days_since(Weekday::Monday));
+
+        assert_eq!(Weekday::Monday, Weekday::Sunday.succ());
+        assert_eq!(Weekday::Sunday, Weekday::Monday.pred());
+    }
+}
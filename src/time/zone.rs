@@ -0,0 +1,442 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{LocalResult, NaiveDateTime, Offset, TimeDelta, TimeZone};
+
+use super::error::parse_number;
+use super::prelude::*;
+use super::{ParseError, ParseErrorKind, iso};
+
+/// a time zone, date times without a zone are wall clock times in [`Zone::Local`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Zone {
+    /// the zone of the system, it can be changed with the `TZ` environment variable
+    Local,
+    /// a fixed offset east of UTC in minutes
+    Offset(i32),
+    /// a zone of the IANA database like `Europe/Berlin`, including its daylight saving rules
+    Named(chrono_tz::Tz),
+}
+
+/// converts a wall clock time in `tz` to UTC
+///
+/// Of times which occur twice when clocks are turned back the earlier one is taken, times which
+/// are skipped when clocks are turned forward are taken with the offset before the transition.
+fn local_to_utc<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> NaiveDateTime {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(datetime) | LocalResult::Ambiguous(datetime, _) => datetime.naive_utc(),
+        LocalResult::None => {
+            let before = tz.offset_from_utc_datetime(&(local - TimeDelta::days(1)));
+            local - TimeDelta::seconds(before.fix().local_minus_utc() as i64)
+        }
+    }
+}
+
+fn utc_to_local<Tz: TimeZone>(tz: &Tz, utc: NaiveDateTime) -> NaiveDateTime {
+    tz.from_utc_datetime(&utc).naive_local()
+}
+
+/// applies `f` to `datetime`, results outside of [`DateTime::MIN`] and [`DateTime::MAX`] are
+/// clamped
+///
+/// `DateTime::MIN` and `DateTime::MAX` themselves are kept, so open bounds of ranges stay open.
+fn clamped(datetime: DateTime, f: impl FnOnce(NaiveDateTime) -> NaiveDateTime) -> DateTime {
+    if datetime == DateTime::MIN || datetime == DateTime::MAX {
+        return datetime;
+    }
+
+    let naive = f(datetime.to_chrono());
+    DateTime::from_chrono(naive).unwrap_or(if naive < datetime.to_chrono() {
+        DateTime::MIN
+    } else {
+        DateTime::MAX
+    })
+}
+
+impl Zone {
+    pub const UTC: Zone = Zone::Offset(0);
+
+    /// the instant the wall clock time `datetime` in this zone describes, as date time in UTC
+    pub fn to_utc(&self, datetime: DateTime) -> DateTime {
+        clamped(datetime, |naive| match self {
+            Zone::Local => local_to_utc(&chrono::Local, naive),
+            Zone::Offset(minutes) => naive - TimeDelta::minutes(*minutes as i64),
+            Zone::Named(tz) => local_to_utc(tz, naive),
+        })
+    }
+
+    /// the wall clock time in this zone at the instant `datetime` describes in UTC
+    pub fn from_utc(&self, datetime: DateTime) -> DateTime {
+        clamped(datetime, |naive| match self {
+            Zone::Local => utc_to_local(&chrono::Local, naive),
+            Zone::Offset(minutes) => naive + TimeDelta::minutes(*minutes as i64),
+            Zone::Named(tz) => utc_to_local(tz, naive),
+        })
+    }
+
+    /// the wall clock time in `to` at the instant the wall clock time `datetime` in this zone
+    /// describes
+    pub fn convert(&self, datetime: DateTime, to: Zone) -> DateTime {
+        if *self == to {
+            return datetime;
+        }
+        to.from_utc(self.to_utc(datetime))
+    }
+
+    /// the offset east of UTC in minutes this zone has at the wall clock time `datetime`
+    pub fn offset_at(&self, datetime: DateTime) -> i32 {
+        match self {
+            Zone::Offset(minutes) => *minutes,
+            zone => zone.to_utc(datetime).minutes_between(datetime) as i32,
+        }
+    }
+
+    /// the current wall clock time in this zone
+    pub fn now(&self) -> DateTime {
+        let utc = DateTime::from_chrono(chrono::Utc::now().naive_utc())
+            .expect("the current time is a valid date time");
+        self.from_utc(utc)
+    }
+}
+
+impl Display for Zone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Zone::Local => write!(f, "local"),
+            Zone::Offset(0) => write!(f, "UTC"),
+            Zone::Offset(minutes) => {
+                let sign = if *minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.unsigned_abs();
+                write!(f, "{}{:>02}:{:>02}", sign, minutes / 60, minutes % 60)
+            }
+            Zone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+/// parses `offset`, a slice of `s`, which is an offset like `+02:00`, `-0530` or `+2` without its
+/// sign, into minutes
+fn parse_offset(s: &str, offset: &str) -> Result<i32, ParseError> {
+    let (hours, minutes) = match offset.split_once(':') {
+        Some((hours, minutes)) => (hours, Some(minutes)),
+        None if offset.len() > 2 => match offset.split_at_checked(offset.len() - 2) {
+            Some((hours, minutes)) => (hours, Some(minutes)),
+            None => {
+                let kind = ParseErrorKind::Expected("an offset like +02:00");
+                return Err(ParseError::new(s, offset, kind));
+            }
+        },
+        None => (offset, None),
+    };
+
+    let out_of_range = |token, field, value: u32| {
+        let value = value as u64;
+        ParseError::new(s, token, ParseErrorKind::OutOfRange { field, value })
+    };
+
+    let hours_value: u32 = parse_number(s, hours)?;
+    if hours_value > 23 {
+        return Err(out_of_range(hours, "offset hour", hours_value));
+    }
+    let minutes_value: u32 = match minutes {
+        Some(minutes) => parse_number(s, minutes)?,
+        None => 0,
+    };
+    if minutes_value > 59 {
+        return Err(out_of_range(
+            minutes.unwrap_or(offset),
+            "offset minute",
+            minutes_value,
+        ));
+    }
+    Ok((hours_value * 60 + minutes_value) as i32)
+}
+
+impl FromStr for Zone {
+    type Err = ParseError;
+
+    /// parses `local`, `UTC`, an offset like `+02:00` or an IANA zone name like `Europe/Berlin`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+
+        if trimmed.is_empty() {
+            return Err(ParseError::new(s, trimmed, ParseErrorKind::Empty));
+        }
+
+        if trimmed == "local" {
+            return Ok(Zone::Local);
+        }
+
+        if trimmed.eq_ignore_ascii_case("utc") || trimmed == "Z" {
+            return Ok(Zone::UTC);
+        }
+
+        if let Some(offset) = trimmed.strip_prefix('+') {
+            return parse_offset(s, offset).map(Zone::Offset);
+        }
+
+        if let Some(offset) = trimmed.strip_prefix('-') {
+            return parse_offset(s, offset).map(|minutes| Zone::Offset(-minutes));
+        }
+
+        trimmed.parse().map(Zone::Named).map_err(|_| {
+            let kind = ParseErrorKind::Unknown {
+                what: "zone",
+                expected: "local, UTC, an offset like +02:00 or a name like Europe/Berlin",
+            };
+            ParseError::new(s, trimmed, kind)
+        })
+    }
+}
+
+/// a wall clock time together with the zone it is meant in, which makes it an unambiguous
+/// instant
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ZonedDateTime {
+    pub datetime: DateTime,
+    pub zone: Zone,
+}
+
+impl ZonedDateTime {
+    pub fn new(datetime: DateTime, zone: Zone) -> Self {
+        Self { datetime, zone }
+    }
+
+    pub fn to_utc(&self) -> DateTime {
+        self.zone.to_utc(self.datetime)
+    }
+
+    /// the wall clock time in `zone` at this instant
+    pub fn in_zone(&self, zone: Zone) -> DateTime {
+        self.zone.convert(self.datetime, zone)
+    }
+
+    /// parses an ISO 8601 or RFC 3339 date time like `2025-03-01T14:30:00Z`,
+    /// `2025-03-01T14:30+02:00` or `20250301T1430`
+    ///
+    /// The date is anything [`Date::parse_iso`] understands and the time anything
+    /// [`Time::parse_iso`] does, separated by `T` or a space. An offset like `Z`, `+02:00`, `-0530`
+    /// or `+02` gives a [`Zone::Offset`], without one the date time is in [`Zone::Local`].
+    ///
+    /// ```
+    /// use koi::time::{Zone, ZonedDateTime};
+    ///
+    /// let zoned = ZonedDateTime::parse_iso("2025-03-01T14:30:00+02:00").unwrap();
+    /// assert_eq!(Zone::Offset(120), zoned.zone);
+    /// assert_eq!("2025-03-01T12:30:00Z", zoned.format_rfc3339_in(Zone::UTC));
+    /// ```
+    pub fn parse_iso(s: &str) -> Result<Self, ParseError> {
+        iso::parse_datetime(s)
+    }
+
+    /// formats as RFC 3339 date time with the offset of its zone like `2025-03-01T14:30:00+02:00`,
+    /// `Z` for UTC
+    pub fn format_rfc3339(&self) -> String {
+        let offset = self.zone.offset_at(self.datetime);
+        format!(
+            "{}{}",
+            self.datetime.format_iso(),
+            iso::format_offset(offset)
+        )
+    }
+
+    /// formats this instant as RFC 3339 date time in `zone`, see [`ZonedDateTime::format_rfc3339`]
+    pub fn format_rfc3339_in(&self, zone: Zone) -> String {
+        Self::new(self.in_zone(zone), zone).format_rfc3339()
+    }
+}
+
+impl Display for ZonedDateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.zone {
+            Zone::Local => write!(f, "{}", self.datetime),
+            zone => write!(f, "{} {}", self.datetime, zone),
+        }
+    }
+}
+
+impl FromStr for ZonedDateTime {
+    type Err = ParseError;
+
+    /// parses a date time optionally followed by a zone like `2025-03-01 14:00 Europe/Berlin`,
+    /// without a zone the date time is in [`Zone::Local`], or an ISO date time with an offset, see
+    /// [`ZonedDateTime::parse_iso`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if iso::is_datetime_like(s) {
+            return Self::parse_iso(s);
+        }
+
+        if let Some((datetime, zone)) = s.rsplit_once(char::is_whitespace)
+            && let Ok(zone) = zone.parse()
+        {
+            let datetime = datetime
+                .parse()
+                .map_err(|err: ParseError| err.within(s, datetime))?;
+            return Ok(Self::new(datetime, zone));
+        }
+
+        Ok(Self::new(s.parse()?, Zone::Local))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    const BERLIN: Zone = Zone::Named(chrono_tz::Europe::Berlin);
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Ok(Zone::Local), "local".parse());
+        assert_eq!(Ok(Zone::UTC), "UTC".parse());
+        assert_eq!(Ok(Zone::UTC), "Z".parse());
+        assert_eq!(Ok(Zone::Offset(120)), "+02:00".parse());
+        assert_eq!(Ok(Zone::Offset(-330)), "-0530".parse());
+        assert_eq!(Ok(Zone::Offset(60)), "+1".parse());
+        assert_eq!(Ok(BERLIN), "Europe/Berlin".parse());
+
+        let kind = |s: &str| s.parse::<Zone>().unwrap_err().kind;
+        let out_of_range = |field, value| ParseErrorKind::OutOfRange { field, value };
+        assert_eq!(out_of_range("offset hour", 24), kind("+24:00"));
+        assert_eq!(out_of_range("offset minute", 60), kind("+02:60"));
+        assert_eq!(ParseErrorKind::InvalidNumber, kind("+0x"));
+        assert!(matches!(
+            kind("Mars/Olympus"),
+            ParseErrorKind::Unknown { .. }
+        ));
+        assert_eq!(ParseErrorKind::Empty, kind(""));
+
+        let err = "-05:7x".parse::<Zone>().unwrap_err();
+        assert_eq!((4, "7x".into()), (err.position, err.token));
+    }
+
+    #[test]
+    fn test_display_parse() {
+        for s in ["local", "UTC", "+02:00", "-05:30", "America/New_York"] {
+            assert_eq!(s, s.parse::<Zone>().unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn test_to_from_utc() {
+        assert_eq!(
+            at("2025-01-15 09:00"),
+            BERLIN.to_utc(at("2025-01-15 10:00"))
+        );
+        assert_eq!(
+            at("2025-07-15 08:00"),
+            BERLIN.to_utc(at("2025-07-15 10:00"))
+        );
+        assert_eq!(
+            at("2025-07-15 10:00"),
+            BERLIN.from_utc(at("2025-07-15 08:00"))
+        );
+
+        assert_eq!(
+            at("2025-03-01 03:30"),
+            Zone::Offset(-330).to_utc(at("2025-02-28 22:00"))
+        );
+        assert_eq!(
+            at("2025-02-28 22:00"),
+            Zone::Offset(-330).from_utc(at("2025-03-01 03:30"))
+        );
+
+        // skipped when clocks are turned forward
+        assert_eq!(
+            at("2025-03-30 01:30"),
+            BERLIN.to_utc(at("2025-03-30 02:30"))
+        );
+        // happens twice when clocks are turned back
+        assert_eq!(
+            at("2025-10-26 00:30"),
+            BERLIN.to_utc(at("2025-10-26 02:30"))
+        );
+
+        for zone in [BERLIN, Zone::Offset(600), Zone::Offset(-600)] {
+            assert_eq!(DateTime::MIN, zone.to_utc(DateTime::MIN));
+            assert_eq!(DateTime::MAX, zone.from_utc(DateTime::MAX));
+        }
+        assert_eq!(
+            DateTime::MIN,
+            Zone::Offset(120).to_utc(at("0000-01-01 01:00"))
+        );
+        assert_eq!(
+            DateTime::MAX,
+            Zone::Offset(-120).to_utc(at("9999-12-31 23:00"))
+        );
+    }
+
+    #[test]
+    fn test_convert() {
+        let new_york = Zone::Named(chrono_tz::America::New_York);
+        assert_eq!(
+            at("2025-03-03 08:00"),
+            BERLIN.convert(at("2025-03-03 14:00"), new_york)
+        );
+        assert_eq!(
+            at("2025-03-03 14:00"),
+            Zone::Local.convert(at("2025-03-03 14:00"), Zone::Local)
+        );
+    }
+
+    #[test]
+    fn test_zoned_parse() {
+        assert_eq!(
+            Ok(ZonedDateTime::new(at("2025-03-01 14:00"), BERLIN)),
+            "2025-03-01 14:00 Europe/Berlin".parse()
+        );
+        assert_eq!(
+            Ok(ZonedDateTime::new(at("2025-03-01 00:00"), Zone::UTC)),
+            "2025-03-01 UTC".parse()
+        );
+        assert_eq!(
+            Ok(ZonedDateTime::new(at("2025-03-01 14:00"), Zone::Local)),
+            "2025-03-01 14:00".parse()
+        );
+        assert_eq!(
+            Ok(ZonedDateTime::new(at("2025-03-01 00:00"), Zone::Local)),
+            "2025-03-01".parse()
+        );
+        assert!("2025-03-01 14:00 Nowhere".parse::<ZonedDateTime>().is_err());
+
+        for s in [
+            "2025-03-01 14:00 Europe/Berlin",
+            "2025-03-01 14:00 -03:00",
+            "2025-03-01 14:00",
+        ] {
+            assert_eq!(s, s.parse::<ZonedDateTime>().unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn test_iso() {
+        assert_eq!(
+            Ok(ZonedDateTime::new(
+                at("2025-03-01 14:30"),
+                Zone::Offset(120)
+            )),
+            "2025-03-01T14:30:00+02:00".parse()
+        );
+        assert_eq!(
+            Ok(ZonedDateTime::new(at("2025-03-01 14:30"), Zone::UTC)),
+            "20250301T143000Z".parse()
+        );
+        assert!("2025-03-01T14:30+25:00".parse::<ZonedDateTime>().is_err());
+
+        assert_eq!(60, BERLIN.offset_at(at("2025-01-15 10:00")));
+        assert_eq!(120, BERLIN.offset_at(at("2025-07-15 10:00")));
+        assert_eq!(-330, Zone::Offset(-330).offset_at(at("2025-07-15 10:00")));
+
+        let zoned = ZonedDateTime::new(at("2025-07-15 10:00"), BERLIN);
+        assert_eq!("2025-07-15T10:00:00+02:00", zoned.format_rfc3339());
+        assert_eq!("2025-07-15T08:00:00Z", zoned.format_rfc3339_in(Zone::UTC));
+        let zoned = ZonedDateTime::new(at("2025-07-15 10:00"), Zone::Offset(-330));
+        assert_eq!("2025-07-15T10:00:00-05:30", zoned.format_rfc3339());
+        assert_eq!(Ok(zoned), ZonedDateTime::parse_iso(&zoned.format_rfc3339()));
+    }
+}
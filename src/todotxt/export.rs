@@ -0,0 +1,161 @@
+use crate::entry::{Entry, EntryType};
+use crate::store::{Query, Store, entry_uid};
+use crate::time::prelude::*;
+
+use super::{is_field, repeater, split_priority};
+
+/// escapes the words of `title` which would not be read back as part of it, like
+/// `\due:tomorrow` or `\@home`
+fn escape_title(title: &str) -> String {
+    let words = title.split(' ').map(|word| {
+        if is_field(word.trim_start_matches('\\')) {
+            format!("\\{}", word)
+        } else {
+            word.to_string()
+        }
+    });
+    words.collect::<Vec<_>>().join(" ")
+}
+
+/// the todo.txt line of `entry` from the store named `store_uid`, without a line break
+///
+/// The body, times of day and recurrences `rec:` can not express are left out. A priority at
+/// the start of the title stays in front of the line while the entry is open.
+fn to_line(entry: &Entry, store_uid: &str) -> String {
+    let date = |at: DateTime| at.date().format_iso();
+    let (priority, title) = split_priority(&entry.title);
+
+    let mut words = vec![];
+    match entry.closed {
+        Some(closed) => words.extend(["x".to_string(), date(closed)]),
+        None => words.extend(priority.map(String::from)),
+    }
+    words.push(date(entry.opened));
+    words.push(match entry.closed {
+        Some(_) => escape_title(&entry.title),
+        None => escape_title(title),
+    });
+
+    if !entry.prefix.is_empty() {
+        words.push(format!("+{}", entry.prefix));
+    }
+    for tag in &entry.tags {
+        if tag.starts_with('@') {
+            words.push(tag.clone());
+        } else {
+            words.push(format!("tag:{}", tag));
+        }
+    }
+
+    if let Some(deadline) = entry.deadline {
+        words.push(format!("due:{}", date(deadline)));
+    }
+    if let Some(scheduled) = entry.scheduled {
+        words.push(format!("t:{}", date(scheduled)));
+    }
+    if let Some(every) = entry.recurrence.and_then(repeater) {
+        words.push(format!("rec:{}", every));
+    }
+    words.push(format!("uid:{}", entry_uid(store_uid, entry.id)));
+
+    words.join(" ")
+}
+
+/// writes the todos of `entries` from the store named `store_uid` as todo.txt lines, meetings
+/// are left out
+pub fn to_todotxt(entries: &[Entry], store_uid: &str) -> String {
+    entries
+        .iter()
+        .filter(|entry| entry.entry_type == EntryType::Todo)
+        .map(|entry| format!("{}\n", to_line(entry, store_uid)))
+        .collect()
+}
+
+/// writes every todo matching `query` as todo.txt lines, see [`to_todotxt`]
+///
+/// The `uid:` of each written entry is one of `store`, so [`import`](super::import()) updates
+/// the entry when the file comes back to it.
+pub fn export(store: &mut Store, query: Query) -> anyhow::Result<String> {
+    let entries = store.query(Query {
+        entry_type: Some(EntryType::Todo),
+        ..query
+    })?;
+    Ok(to_todotxt(&entries, &store.uid()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_to_line() {
+        let mut entry = Entry::test_entry(7, "(A) call bank".into());
+        entry.opened = at("2025-03-01 09:00");
+        entry.prefix = "work/acme".into();
+        entry.tags = vec!["@phone".into(), "urgent".into()];
+        entry.deadline = Some(at("2025-03-07 17:00"));
+        entry.scheduled = Some(at("2025-03-03 00:00"));
+        entry.recurrence = Some("every 1m".parse().unwrap());
+        entry.body = "account 42".into();
+
+        assert_eq!(
+            "(A) 2025-03-01 call bank +work/acme @phone tag:urgent due:2025-03-07 t:2025-03-03 \
+             rec:1m uid:7@a1b2.koi",
+            to_line(&entry, "a1b2")
+        );
+
+        entry.closed = Some(at("2025-03-05 18:00"));
+        entry.prefix = "".into();
+        entry.tags = vec![];
+        entry.deadline = None;
+        entry.scheduled = None;
+        entry.recurrence = Some("every 1w 3 times".parse().unwrap());
+        assert_eq!(
+            "x 2025-03-05 2025-03-01 (A) call bank uid:7@a1b2.koi",
+            to_line(&entry, "a1b2")
+        );
+    }
+
+    #[test]
+    fn test_escape_title() {
+        assert_eq!("call bank", escape_title("call bank"));
+        assert_eq!(
+            "ask \\due:tomorrow at \\@home for \\+docs via a@b.c",
+            escape_title("ask due:tomorrow at @home for +docs via a@b.c")
+        );
+        assert_eq!("\\\\tag:a \\x", escape_title("\\tag:a \\x"));
+    }
+
+    #[test]
+    fn test_export() {
+        let mut store = Store::open_test();
+
+        let mut todo = Entry::test_entry(1, "a".into());
+        todo.prefix = "work".into();
+        store.add_entry(&todo).unwrap();
+        let mut meeting = Entry::test_entry(2, "b".into());
+        meeting.entry_type = EntryType::Meeting;
+        meeting.prefix = "work".into();
+        store.add_entry(&meeting).unwrap();
+        store.add_entry(&Entry::test_entry(3, "c".into())).unwrap();
+
+        let query = Query {
+            prefix: Some("work".into()),
+            ..Default::default()
+        };
+        let before = store.export_all().unwrap();
+        let lines = export(&mut store, query).unwrap();
+        assert_eq!(1, lines.lines().count());
+        let uid = entry_uid(&store.uid().unwrap(), 1);
+        assert!(lines.ends_with(&format!(" a +work uid:{}\n", uid)));
+        assert_eq!(Some(1), store.entry_id_by_uid(&uid).unwrap());
+
+        // nothing is written to the store, so uids of other stores are not taken for its own
+        assert_eq!(before, store.export_all().unwrap());
+        assert_eq!(None, store.entry_id_by_uid("1@koi").unwrap());
+    }
+}
@@ -0,0 +1,342 @@
+use anyhow::Context;
+
+use crate::entry::{Entry, EntryType, Recurrence};
+use crate::store::{Query, Store};
+use crate::time::prelude::*;
+
+use super::{is_field, is_priority, repeater};
+
+/// a todo read from a todo.txt line, with its `uid:`
+///
+/// Its id is `0`, todos without a creation date are opened now.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Imported {
+    pub uid: Option<String>,
+    pub entry: Entry,
+}
+
+/// the outcome of [`import`]
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ImportReport {
+    /// ids of the new entries
+    pub added: Vec<u64>,
+    /// ids of the entries which were imported or exported before
+    pub updated: Vec<u64>,
+}
+
+/// parses a todo.txt date like `2025-03-01`, other ISO 8601 dates are titles
+fn parse_date(s: &str) -> Option<Date> {
+    if s.len() != 10 {
+        return None;
+    }
+    Date::parse_iso(s).ok()
+}
+
+/// parses a `rec:` value like `1w` or `+2d`, see [`repeater`]
+fn parse_repeater(s: &str) -> Option<Recurrence> {
+    let every = s.strip_prefix('+').unwrap_or(s).parse().ok()?;
+    let recurrence = Recurrence { every, end: None };
+    repeater(recurrence).filter(|every| every.is_positive())?;
+    Some(recurrence)
+}
+
+/// parses one todo.txt line which is not blank
+///
+/// Words which are neither the first `+project`, a `@context` nor a known `key:value` stay
+/// in the title, so do further projects and the priority. Such words escaped with a `\` lose
+/// it and stay in the title too.
+fn parse_line(line: &str, prefix: &str) -> anyhow::Result<Imported> {
+    let mut words = line.split_whitespace().peekable();
+    let midnight = |date: Date| date.with_time(Time::MIN);
+
+    let done = words.next_if_eq(&"x").is_some();
+    let priority = if done {
+        None
+    } else {
+        words.next_if(|word| is_priority(word))
+    };
+    // a closed todo starts with its closing date, both are followed by the opening date
+    let max_dates = if done { 2 } else { 1 };
+    let mut dates = vec![];
+    while dates.len() < max_dates
+        && let Some(date) = words.peek().and_then(|word| parse_date(word))
+    {
+        dates.push(date);
+        words.next();
+    }
+    let (closed, opened) = match (done, &dates[..]) {
+        (true, [closed, opened]) => (Some(midnight(*closed)), Some(midnight(*opened))),
+        (true, [closed]) => (Some(midnight(*closed)), None),
+        (true, _) => (Some(now()), None),
+        (false, [opened]) => (None, Some(midnight(*opened))),
+        (false, _) => (None, None),
+    };
+
+    let mut title: Vec<&str> = priority.into_iter().collect();
+    let mut project = None;
+    let mut tags = vec![];
+    let mut uid = None;
+    let mut deadline = None;
+    let mut scheduled = None;
+    let mut recurrence = None;
+    for word in words {
+        let date = |value: &str| {
+            parse_date(value)
+                .map(midnight)
+                .with_context(|| format!("invalid date in '{}'", word))
+        };
+
+        match word.split_once(':') {
+            _ if word.starts_with('\\') && is_field(word.trim_start_matches('\\')) => {
+                title.push(&word[1..]);
+            }
+            Some(("due", value)) => deadline = Some(date(value)?),
+            Some(("t", value)) => scheduled = Some(date(value)?),
+            Some(("rec", value)) => {
+                let parsed = parse_repeater(value)
+                    .with_context(|| format!("invalid recurrence in '{}'", word))?;
+                recurrence = Some(parsed);
+            }
+            Some(("uid", value)) if !value.is_empty() => uid = Some(value.to_string()),
+            Some(("tag", tag)) if !tag.is_empty() => tags.push(tag.to_string()),
+            _ if word.len() > 1 && word.starts_with('@') => tags.push(word.to_string()),
+            _ if word.len() > 1 && word.starts_with('+') && project.is_none() => {
+                project = Some(&word[1..]);
+            }
+            _ => title.push(word),
+        }
+    }
+    tags.sort();
+    tags.dedup();
+
+    let prefix = [prefix, project.unwrap_or_default()]
+        .into_iter()
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let entry = Entry {
+        id: 0,
+        title: title.join(" "),
+        body: "".into(),
+        prefix,
+        entry_type: EntryType::Todo,
+        opened: opened.unwrap_or_else(now),
+        closed,
+        scheduled,
+        scheduled_end: None,
+        deadline,
+        recurrence,
+        tags,
+    };
+
+    Ok(Imported { uid, entry })
+}
+
+/// reads the todos of a todo.txt file, their prefix is `prefix` followed by their project
+///
+/// # Errors
+/// - if a `due:`, `t:` or `rec:` value is invalid
+pub fn from_todotxt(content: &str, prefix: &str) -> anyhow::Result<Vec<Imported>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_line(line, prefix).with_context(|| format!("line {}", i + 1)))
+        .collect()
+}
+
+/// `new` with what todo.txt can not express taken from `old`
+///
+/// The body, the opening time and recurrences without `rec:` are kept, so are the times of
+/// dates which are still on the same day.
+fn merge(old: Entry, new: Entry) -> Entry {
+    let same_day = |old: Option<DateTime>, new: Option<DateTime>| match (old, new) {
+        (Some(old), Some(new)) if old.date() == new.date() => Some(old),
+        _ => new,
+    };
+
+    let scheduled = same_day(old.scheduled, new.scheduled);
+    let recurrence = match (old.recurrence, new.recurrence) {
+        (Some(old), None) if repeater(old).is_none() => Some(old),
+        (_, new) => new,
+    };
+
+    Entry {
+        id: old.id,
+        body: old.body,
+        opened: old.opened,
+        closed: same_day(old.closed, new.closed),
+        scheduled_end: old.scheduled_end.filter(|_| scheduled == old.scheduled),
+        scheduled,
+        deadline: same_day(old.deadline, new.deadline),
+        recurrence,
+        ..new
+    }
+}
+
+/// imports the todos of a todo.txt file which match `query` into `store`, see
+/// [`from_todotxt`]
+///
+/// Only the filters of `query` apply, its order and limits are ignored. Lines with a `uid:`
+/// which was exported or imported before update their entry instead of adding a new one, it
+/// keeps what todo.txt can not express like the body.
+///
+/// # Errors
+/// - if `content` is not a valid todo.txt file or a todo can not be stored, nothing is imported
+///   then
+pub fn import(
+    store: &mut Store,
+    content: &str,
+    prefix: &str,
+    query: Query,
+) -> anyhow::Result<ImportReport> {
+    let todos = from_todotxt(content, prefix)?
+        .into_iter()
+        .filter(|imported| query.matches(&imported.entry))
+        .map(|Imported { uid, entry }| (uid, entry));
+    let imported = store.import_entries(todos, merge)?;
+
+    Ok(ImportReport {
+        added: imported.added,
+        updated: imported.updated,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::todotxt::{export, to_todotxt};
+
+    fn at(s: &str) -> DateTime {
+        s.parse().unwrap()
+    }
+
+    fn parse(line: &str) -> Imported {
+        parse_line(line, "").unwrap()
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let imported = parse(
+            "(A) 2025-03-01 call bank about http://example.com +work/acme +other @phone \
+             due:2025-03-07 t:2025-03-03 rec:+1m tag:urgent uid:7@koi",
+        );
+        assert_eq!(Some("7@koi".into()), imported.uid);
+
+        let entry = imported.entry;
+        assert_eq!("(A) call bank about http://example.com +other", entry.title);
+        assert_eq!("work/acme", entry.prefix);
+        assert_eq!(at("2025-03-01 00:00"), entry.opened);
+        assert_eq!(Some(at("2025-03-07 00:00")), entry.deadline);
+        assert_eq!(Some(at("2025-03-03 00:00")), entry.scheduled);
+        assert_eq!(Some("every 1m".parse().unwrap()), entry.recurrence);
+        assert_eq!(vec!["@phone".to_string(), "urgent".into()], entry.tags);
+        assert!(entry.is_open());
+
+        let entry = parse("x 2025-03-05 2025-03-01 2025 taxes").entry;
+        assert_eq!(Some(at("2025-03-05 00:00")), entry.closed);
+        assert_eq!(at("2025-03-01 00:00"), entry.opened);
+        assert_eq!("2025 taxes", entry.title);
+
+        let entry = parse("x 2025-03-05 taxes").entry;
+        assert_eq!(Some(at("2025-03-05 00:00")), entry.closed);
+
+        let entry = parse("xylophone lessons").entry;
+        assert!(entry.is_open());
+        assert_eq!("xylophone lessons", entry.title);
+
+        let entry = parse_line("plants +home", "todo").unwrap().entry;
+        assert_eq!("todo/home", entry.prefix);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = |content: &str| format!("{:#}", from_todotxt(content, "").unwrap_err());
+        assert_eq!(
+            "line 2: invalid date in 'due:tomorrow'",
+            error("a\nb due:tomorrow\n")
+        );
+        assert_eq!(
+            "line 1: invalid recurrence in 'rec:1b'",
+            error("a rec:1b\n")
+        );
+        assert!(from_todotxt("a rec:4h", "").is_err());
+        assert!(from_todotxt("a rec:0d", "").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut entry = Entry::test_entry(7, "(B) call bank".into());
+        entry.opened = at("2025-03-01 00:00");
+        entry.prefix = "work/acme".into();
+        entry.tags = vec!["@phone".into(), "urgent".into()];
+        entry.deadline = Some(at("2025-03-07 00:00"));
+        entry.scheduled = Some(at("2025-03-03 00:00"));
+        entry.recurrence = Some("every 2w".parse().unwrap());
+
+        // title words which look like fields
+        let mut words = Entry::test_entry(9, "ask due:tomorrow t:x @home \\+docs tag:".into());
+        words.opened = at("2025-03-01 00:00");
+        words.prefix = "work".into();
+
+        let mut closed = Entry::test_entry(8, "taxes \\rec:1w".into());
+        closed.opened = at("2025-02-01 00:00");
+        closed.closed = Some(at("2025-03-05 00:00"));
+
+        let entries = [entry.clone(), words.clone(), closed.clone()];
+        let todos = from_todotxt(&to_todotxt(&entries, "a1b2"), "").unwrap();
+        let entries: Vec<Entry> = todos
+            .into_iter()
+            .map(|imported| Entry {
+                id: imported
+                    .uid
+                    .unwrap()
+                    .trim_end_matches("@a1b2.koi")
+                    .parse()
+                    .unwrap(),
+                ..imported.entry
+            })
+            .collect();
+        assert_eq!(vec![entry, words, closed], entries);
+    }
+
+    #[test]
+    fn test_import() {
+        let mut store = Store::open_test();
+
+        let mut todo = Entry::test_entry(1, "report".into());
+        todo.body = "outline first".into();
+        todo.opened = at("2025-03-01 09:30");
+        todo.deadline = Some(at("2025-03-07 17:00"));
+        todo.recurrence = Some("every 1w 4 times".parse().unwrap());
+        store.add_entry(&todo).unwrap();
+
+        let content = export(&mut store, Query::default()).unwrap();
+        let content = content.replace("report", "write report")
+            + "2025-03-02 groceries +home @shop\n"
+            + "taxes +work\n";
+
+        let query = Query {
+            prefix: Some("home".into()),
+            ..Default::default()
+        };
+        let report = import(&mut store, &content, "", query).unwrap();
+        assert_eq!(1, report.added.len());
+        assert!(report.updated.is_empty());
+
+        let report = import(&mut store, &content, "", Query::default()).unwrap();
+        assert_eq!(vec![1], report.updated);
+        assert_eq!(2, report.added.len());
+
+        let updated = store.query_by_id(1).unwrap();
+        assert_eq!(
+            Entry {
+                title: "write report".into(),
+                ..todo
+            },
+            updated
+        );
+        assert_eq!(4, store.query(Query::default()).unwrap().len());
+    }
+}
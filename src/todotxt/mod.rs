@@ -0,0 +1,100 @@
+//! conversion between entries and todo.txt lines
+//!
+//! A line like `x 2025-03-05 2025-03-01 call bank +work/acme @phone due:2025-03-07` is a todo.
+//! `x` and the date after it mark it as closed, the next date is its opening date. The first
+//! `+project` is the prefix, `@contexts` are tags. Deadlines, scheduled dates and recurrences
+//! use the common `due:`, `t:` and `rec:` extensions, tags without `@` are written as `tag:`.
+//! The id of an entry is kept in `uid:` together with the name of its store, like
+//! `uid:7@3f2a9c1e0b4d5a68.koi`, so importing the file into that store again updates the
+//! entry, see [`entry_uid`](crate::store::entry_uid). Words
+//! of a title which would be read like that get a `\` in front, like `\due:tomorrow`.
+//!
+//! todo.txt has dates without times, imported dates are at midnight.
+
+use crate::entry::Recurrence;
+use crate::time::prelude::*;
+
+mod export;
+pub use export::{export, to_todotxt};
+
+mod import;
+pub use import::{ImportReport, Imported, from_todotxt, import};
+
+/// the interval of the `rec:` value of `recurrence`, like `1w`
+///
+/// # Returns
+/// - `None` if the recurrence ends or its interval is not a number of days, weeks, months or
+///   years, `rec:` can not express that
+fn repeater(recurrence: Recurrence) -> Option<Duration> {
+    match recurrence.every {
+        _ if recurrence.end.is_some() => None,
+        every @ (Duration::Day(_) | Duration::Week(_) | Duration::Month(_) | Duration::Year(_)) => {
+            Some(every)
+        }
+        _ => None,
+    }
+}
+
+/// whether `word` is a priority like `(A)`
+fn is_priority(word: &str) -> bool {
+    matches!(word.as_bytes(), [b'(', b'A'..=b'Z', b')'])
+}
+
+/// whether `word` of a line is read as a project, a context or a `key:value` instead of as
+/// part of the title
+fn is_field(word: &str) -> bool {
+    match word.split_once(':') {
+        Some(("due" | "t" | "rec", _)) => true,
+        Some(("uid" | "tag", value)) if !value.is_empty() => true,
+        _ => word.len() > 1 && word.starts_with(['@', '+']),
+    }
+}
+
+/// splits a priority like `(A)` off the start of a title
+fn split_priority(title: &str) -> (Option<&str>, &str) {
+    match title.split_once(' ') {
+        Some((priority, rest)) if is_priority(priority) => (Some(priority), rest),
+        _ => (None, title),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_repeater() {
+        let repeater = |s: &str| repeater(s.parse().unwrap());
+        assert_eq!(Some(Duration::Week(2)), repeater("every 2w"));
+        assert_eq!(Some(Duration::Year(1)), repeater("every 1y"));
+        assert_eq!(None, repeater("every 4h"));
+        assert_eq!(None, repeater("every 1w2d"));
+        assert_eq!(None, repeater("every 1d 5 times"));
+    }
+
+    #[test]
+    fn test_split_priority() {
+        assert_eq!((Some("(A)"), "call mom"), split_priority("(A) call mom"));
+        assert_eq!((None, "(a) call mom"), split_priority("(a) call mom"));
+        assert_eq!((None, "(A)call"), split_priority("(A)call"));
+        assert_eq!((None, "call"), split_priority("call"));
+    }
+
+    #[test]
+    fn test_is_field() {
+        assert!(is_field("due:tomorrow"));
+        assert!(is_field("t:"));
+        assert!(is_field("rec:1w"));
+        assert!(is_field("uid:7@koi"));
+        assert!(is_field("tag:a"));
+        assert!(is_field("@home"));
+        assert!(is_field("+work"));
+
+        assert!(!is_field("uid:"));
+        assert!(!is_field("tag:"));
+        assert!(!is_field("@"));
+        assert!(!is_field("http://example.com"));
+        assert!(!is_field("a@b"));
+        assert!(!is_field("\\@home"));
+    }
+}